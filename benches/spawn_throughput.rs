@@ -0,0 +1,41 @@
+//! Measures how fast 100k child tasks get spawned into a spawn group,
+//! without and with reserving room for them first.
+//!
+//! Run it with ``cargo bench --bench spawn_throughput``
+
+use spawn_groups::{spawn_group::SpawnGroupBuilder, Priority};
+use std::time::{Duration, Instant};
+
+const TASKS: usize = 100_000;
+const ROUNDS: usize = 5;
+
+/// Spawns ``TASKS`` child tasks and returns how long spawning them took, leaving out waiting for them
+fn spawn_all(reserve: bool) -> Duration {
+    let mut group = SpawnGroupBuilder::new().build::<usize>();
+    let now: Instant = Instant::now();
+    if reserve {
+        group.reserve(TASKS);
+    }
+    for i in 0..TASKS {
+        group.spawn_task(Priority::default(), async move { i });
+    }
+    let elapsed: Duration = now.elapsed();
+    assert_eq!(
+        spawn_groups::block_on(group.wait_for_all_items()).len(),
+        TASKS
+    );
+    elapsed
+}
+
+fn main() {
+    for (label, reserve) in [("without reserve", false), ("with reserve", true)] {
+        let best: Duration = (0..ROUNDS)
+            .map(|_| spawn_all(reserve))
+            .min()
+            .unwrap_or_default();
+        println!(
+            "{label}: spawned {TASKS} child tasks in {best:?} at best of {ROUNDS} rounds, {:.0} a second",
+            TASKS as f64 / best.as_secs_f64()
+        );
+    }
+}
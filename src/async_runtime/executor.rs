@@ -8,7 +8,7 @@ use parking_lot::{lock_api::MutexGuard, Condvar, Mutex, RawMutex};
 use std::{
     future::Future,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc,
     },
     task::{Context, Poll, Waker},
@@ -16,7 +16,7 @@ use std::{
 
 #[derive(Clone)]
 pub struct Executor {
-    cancel: Arc<AtomicBool>,
+    generation: Arc<AtomicUsize>,
     lock_pair: Arc<(Mutex<bool>, Condvar)>,
     pool: Arc<ThreadPool>,
     queue: TaskQueue,
@@ -25,10 +25,20 @@ pub struct Executor {
 
 impl Default for Executor {
     fn default() -> Self {
+        Executor::new(0, None)
+    }
+}
+
+impl Executor {
+    pub(crate) fn new(thread_count: usize, thread_name: Option<&str>) -> Self {
+        let pool: ThreadPool = match thread_name {
+            Some(thread_name) => ThreadPool::with_name(thread_count, thread_name),
+            None => ThreadPool::new(thread_count),
+        };
         let result: Executor = Self {
-            cancel: Arc::new(AtomicBool::new(false)),
+            generation: Arc::new(AtomicUsize::new(0)),
             lock_pair: Arc::new((Mutex::new(false), Condvar::new())),
-            pool: Arc::new(ThreadPool::default()),
+            pool: Arc::new(pool),
             queue: TaskQueue::default(),
             started: Arc::new(AtomicBool::new(false)),
         };
@@ -78,16 +88,26 @@ impl Executor {
         });
     }
 
+    /// Stops the running run loop, which ``start`` replaces with a new one, and drops the queued tasks
     pub(crate) fn cancel(&self) {
-        self.cancel.store(true, Ordering::Release);
+        self.stop();
+        self.queue.drain_all();
+    }
+
+    /// Stops the running run loop, which ``start`` replaces with a new one,
+    /// but keeps the queued tasks around for the next run loop
+    pub(crate) fn stop(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
         *self.lock_pair.0.lock() = false;
         self.update(false);
-        self.queue.drain_all();
-        self.cancel.store(false, Ordering::Release);
     }
 
+    /// Drives the queued tasks until cancelled.
+    ///
+    /// Only the run loop of the current generation keeps going, so restarting never leaves an older one running
     pub(crate) fn run(&self) {
-        while !self.cancel.load(Ordering::Acquire) {
+        let generation: usize = self.generation.load(Ordering::Acquire);
+        while self.generation.load(Ordering::Acquire) == generation {
             self.queue.clone().for_each(|task| {
                 let queue: TaskQueue = self.queue.clone();
                 self.submit(move || {
@@ -103,8 +123,32 @@ impl Executor {
                 });
             });
         }
+        // the queue may already hold the tasks of the next generation, so it isn't drained here
         self.poll_all();
-        self.queue.drain_all();
+    }
+
+    /// The number of pool threads polling a child task right now
+    pub(crate) fn active_count(&self) -> usize {
+        self.pool.active_count()
+    }
+
+    /// The number of child task polls waiting for a pool thread
+    pub(crate) fn queued_count(&self) -> usize {
+        self.pool.queued_count()
+    }
+
+    /// Returns ``Poll::Ready`` once no child task poll waits for a pool thread,
+    /// otherwise the waker of ``cx`` is woken once a pool thread took the next one of them
+    pub(crate) fn poll_capacity(&self, cx: &mut Context<'_>) -> Poll<()> {
+        match self.pool.poll_capacity(cx.waker()) {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    }
+
+    /// Resizes the thread pool, which keeps at least one thread
+    pub(crate) fn set_thread_count(&self, thread_count: usize) {
+        self.pool.set_thread_count(thread_count);
     }
 
     pub(crate) fn poll_all(&self) {
@@ -122,5 +166,9 @@ impl Executor {
             }
             executor.run();
         });
+        // tasks spawned while the previous run loop was still running didn't wake up this one
+        if !self.queue.is_empty() {
+            self.notify();
+        }
     }
 }
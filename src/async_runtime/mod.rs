@@ -1,5 +1,8 @@
+#[cfg(not(feature = "tokio"))]
 pub(crate) mod executor;
 pub(crate) mod notifier;
+#[cfg(not(feature = "tokio"))]
 pub(crate) mod task;
+#[cfg(not(feature = "tokio"))]
 mod task_queue;
 mod pin_macro;
\ No newline at end of file
@@ -1,5 +1,8 @@
 use cooked_waker::WakeRef;
-use std::sync::{Condvar, Mutex, MutexGuard};
+use std::{
+    sync::{Condvar, Mutex, MutexGuard},
+    time::Instant,
+};
 
 #[derive(Default)]
 pub struct Notifier {
@@ -26,4 +29,18 @@ impl Notifier {
         }
         *was_notified = false;
     }
+
+    /// Waits like ``wait`` does, but only until ``deadline``, and returns whether it was notified before that
+    pub(crate) fn wait_until(&self, deadline: Instant) -> bool {
+        let mut was_notified: MutexGuard<'_, bool> = self.was_notified.lock().unwrap();
+
+        while !*was_notified {
+            let Some(timeout) = deadline.checked_duration_since(Instant::now()) else {
+                return false;
+            };
+            was_notified = self.cv.wait_timeout(was_notified, timeout).unwrap().0;
+        }
+        *was_notified = false;
+        true
+    }
 }
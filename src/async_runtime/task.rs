@@ -1,4 +1,4 @@
-use parking_lot::Mutex;
+use parking_lot::{Condvar, Mutex};
 use std::{
     future::Future,
     pin::Pin,
@@ -15,6 +15,8 @@ type LocalBoxedFuture = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
 pub struct Task {
     pub(crate) future: Arc<Mutex<LocalBoxedFuture>>,
     pub(crate) complete: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    completion_pair: Arc<(Mutex<()>, Condvar)>,
 }
 
 impl Task {
@@ -22,6 +24,8 @@ impl Task {
         Self {
             future: Arc::new(Mutex::new(Box::pin(fut))),
             complete: Arc::new(AtomicBool::new(false)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            completion_pair: Arc::new((Mutex::new(()), Condvar::new())),
         }
     }
 
@@ -31,6 +35,36 @@ impl Task {
 
     fn complete(&self) {
         self.complete.store(true, Ordering::Release);
+        let (lock, cvar) = &*self.completion_pair;
+        let _guard = lock.lock();
+        cvar.notify_all();
+    }
+
+    /// Drops the task's future and marks the task as completed so the executor stops driving it.
+    ///
+    /// If the task is being polled right now, that happens the next time it is polled instead
+    pub(crate) fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+        if let Some(mut future) = self.future.try_lock() {
+            self.drop_future(&mut future);
+        }
+    }
+
+    fn drop_future(&self, future: &mut LocalBoxedFuture) {
+        if self.is_completed() {
+            return;
+        }
+        *future = Box::pin(async {});
+        self.complete();
+    }
+
+    /// Blocks the current thread until the task is driven to completion by the executor
+    pub(crate) fn wait(&self) {
+        let (lock, cvar) = &*self.completion_pair;
+        let mut guard = lock.lock();
+        while !self.is_completed() {
+            cvar.wait(&mut guard);
+        }
     }
 }
 
@@ -40,7 +74,14 @@ impl Future for Task {
         self: Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Self::Output> {
-        match self.future.lock().as_mut().poll(cx) {
+        let mut future = self.future.lock();
+        if self.cancelled.load(Ordering::Acquire) {
+            self.drop_future(&mut future);
+        }
+        if self.is_completed() {
+            return Poll::Ready(());
+        }
+        match future.as_mut().poll(cx) {
             Poll::Ready(()) => {
                 self.complete();
                 Poll::Ready(())
@@ -14,6 +14,10 @@ impl TaskQueue {
 }
 
 impl TaskQueue {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.buffer.lock().is_empty()
+    }
+
     pub(crate) fn drain_all(&self) {
         self.buffer.lock().clear();
     }
@@ -23,9 +27,7 @@ impl Iterator for TaskQueue {
     type Item = Task;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let Some(task) = self.buffer.lock().pop_front() else {
-            return None;
-        };
+        let task: Task = self.buffer.lock().pop_front()?;
         if !task.is_completed() {
             return Some(task);
         }
@@ -0,0 +1,51 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_lite::{Stream, StreamExt};
+
+use super::AsyncStream;
+
+/// Yields the items of an ``AsyncStream`` in batches of ``size`` items,
+/// and the remaining items in a smaller batch once the stream ends
+pub(crate) struct Chunks<ItemType> {
+    stream: AsyncStream<ItemType>,
+    size: usize,
+    chunk: Vec<ItemType>,
+}
+
+impl<ItemType> Chunks<ItemType> {
+    pub(crate) fn new(stream: AsyncStream<ItemType>, size: usize) -> Self {
+        assert!(size > 0, "The chunk size must be greater than zero");
+        Self {
+            stream,
+            size,
+            chunk: Vec::with_capacity(size),
+        }
+    }
+}
+
+// the items are only ever moved around, never pinned
+impl<ItemType> Unpin for Chunks<ItemType> {}
+
+impl<ItemType> Stream for Chunks<ItemType> {
+    type Item = Vec<ItemType>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this: &mut Self = self.get_mut();
+        loop {
+            match this.stream.poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    this.chunk.push(item);
+                    if this.chunk.len() == this.size {
+                        return Poll::Ready(Some(std::mem::take(&mut this.chunk)));
+                    }
+                }
+                Poll::Ready(None) if this.chunk.is_empty() => return Poll::Ready(None),
+                Poll::Ready(None) => return Poll::Ready(Some(std::mem::take(&mut this.chunk))),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+use parking_lot::Mutex;
+use std::sync::Arc;
+
+/// The seed that fixes the order results are drained in, shared between clones of a stream
+#[derive(Clone, Default)]
+pub(crate) struct DrainOrder {
+    seed: Arc<Mutex<Option<u64>>>,
+}
+
+impl DrainOrder {
+    pub(crate) fn seed(&self) -> Option<u64> {
+        *self.seed.lock()
+    }
+
+    pub(crate) fn set_seed(&self, seed: u64) {
+        *self.seed.lock() = Some(seed);
+    }
+}
+
+/// Shuffles the sequence numbers of the child tasks the same way for the same seed,
+/// by ordering them by their SplitMix64 hash
+pub(crate) fn drain_key(seed: u64, sequence: u64) -> u64 {
+    let mut key: u64 = seed ^ sequence.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    key = (key ^ (key >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    key = (key ^ (key >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    key ^ (key >> 31)
+}
@@ -2,43 +2,260 @@ use std::{
     collections::VecDeque,
     pin::Pin,
     sync::{
-        atomic::{AtomicUsize, Ordering},
+        atomic::{AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
-    task::{Context, Poll},
+    task::{Context, Poll, Waker},
+    time::Duration,
 };
 
 use async_mutex::{Mutex, MutexGuard};
-use futures_lite::{Stream, StreamExt};
+use futures_lite::{future, Stream, StreamExt};
 
-use crate::executors::block_on;
+use crate::{executors::block_on, shared::priority::Priority};
 
+use self::chunks::Chunks;
+#[cfg(feature = "deterministic")]
+use self::drain_order::{drain_key, DrainOrder};
+use self::progress::Progress;
+
+mod chunks;
+#[cfg(feature = "deterministic")]
+mod drain_order;
+mod progress;
+
+/// A buffered result along with the sequence number, priority and latency of the child task that returned it
+pub(crate) type Buffered<ItemType> = (u64, Priority, ItemType, Duration);
+
+/// Results are buffered along with the sequence number, priority and latency of the child task that returned them
+///
+/// Clones share all of their state, so every clone is a handle to the same single-consumer queue:
+/// they all pull from the same buffer in the same order, each result is yielded by exactly one of them,
+/// and every clone ends once all child tasks finished and the buffer is drained.
 pub struct AsyncStream<ItemType> {
-    buffer: Arc<Mutex<VecDeque<ItemType>>>,
-    started: bool,
+    buffer: Arc<Mutex<VecDeque<Buffered<ItemType>>>>,
     counts: (Arc<AtomicUsize>, Arc<AtomicUsize>),
-    cancelled: bool,
+    completed: Arc<AtomicUsize>,
+    released: Arc<AtomicU64>,
+    wakers: Arc<parking_lot::Mutex<Vec<Waker>>>,
+    producers: Arc<parking_lot::Mutex<Vec<Waker>>>,
+    sequence: Arc<AtomicU64>,
+    max_buffered: Arc<AtomicUsize>,
+    #[cfg(feature = "deterministic")]
+    drain_order: DrainOrder,
+}
+
+impl<ItemType> AsyncStream<ItemType> {
+    /// Waits until the buffer has room for the item when the buffer is limited,
+    /// parked until a result is taken out of the buffer or its limit changes
+    pub(crate) async fn insert_item(
+        &self,
+        sequence: u64,
+        priority: Priority,
+        value: ItemType,
+        latency: Duration,
+    ) {
+        let mut item: Option<Buffered<ItemType>> = Some((sequence, priority, value, latency));
+        future::poll_fn(|cx| {
+            block_on(async {
+                let mut buffer: MutexGuard<'_, VecDeque<Buffered<ItemType>>> =
+                    self.buffer.lock().await;
+                let max_buffered: usize = self.max_buffered();
+                if max_buffered == 0 || buffer.len() < max_buffered || self.is_seeded() {
+                    buffer.extend(item.take());
+                    drop(buffer);
+                    self.wake_consumers();
+                    return Poll::Ready(());
+                }
+                // registered while holding the buffer, so a result taken out afterwards wakes this producer
+                let mut producers = self.producers.lock();
+                if !producers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                    producers.push(cx.waker().clone());
+                }
+                Poll::Pending
+            })
+        })
+        .await
+    }
+
+    /// Wakes the child tasks waiting for room in the buffer
+    fn wake_producers(&self) {
+        let producers: Vec<Waker> = std::mem::take(&mut *self.producers.lock());
+        for waker in producers {
+            waker.wake();
+        }
+    }
+
+    pub(crate) fn max_buffered(&self) -> usize {
+        self.max_buffered.load(Ordering::Acquire)
+    }
+
+    /// Limits the number of results waiting to be consumed, where zero means no limit
+    pub(crate) fn set_max_buffered(&self, max_buffered: usize) {
+        self.max_buffered.store(max_buffered, Ordering::Release);
+        self.wake_producers();
+    }
+
+    /// Whether results are drained in a seeded order, which only starts once every child task finished,
+    /// so the buffer limit is ignored in the meantime
+    fn is_seeded(&self) -> bool {
+        #[cfg(feature = "deterministic")]
+        return self.drain_order.seed().is_some();
+        #[cfg(not(feature = "deterministic"))]
+        false
+    }
 }
 
+#[cfg(feature = "deterministic")]
 impl<ItemType> AsyncStream<ItemType> {
-    pub(crate) async fn insert_item(&mut self, value: ItemType) {
-        if !self.started {
-            self.started = true;
+    /// Drains the results in an order which only depends on the seed and the order the child tasks were spawned in
+    pub(crate) fn set_drain_seed(&self, seed: u64) {
+        self.drain_order.set_seed(seed);
+    }
+
+    /// Polls like ``poll_next`` does, but waits for every child task to finish,
+    /// then pops the buffered results in the seeded order
+    fn poll_next_seeded(&self, seed: u64, cx: &mut Context<'_>) -> Poll<Option<ItemType>> {
+        self.register_waker(cx);
+        // child tasks store their result before decrementing the task count
+        if self.task_count() > 0 {
+            return Poll::Pending;
         }
-        self.buffer.lock().await.push_back(value);
+        block_on(async move {
+            let mut inner_lock: MutexGuard<'_, VecDeque<Buffered<ItemType>>> =
+                self.buffer.lock().await;
+            let first: Option<usize> = inner_lock
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, (sequence, _, _, _))| drain_key(seed, *sequence))
+                .map(|(index, _)| index);
+            match first.and_then(|index| inner_lock.remove(index)) {
+                Some((_, _, value, _)) => {
+                    self.decrement_count();
+                    Poll::Ready(Some(value))
+                }
+                None => Poll::Ready(None),
+            }
+        })
     }
 }
 
 impl<ItemType> AsyncStream<ItemType> {
+    /// Allocates room for ``additional`` more results, up to the buffer limit when the buffer is limited,
+    /// unless another thread holds the buffer right now, and returns whether it did
+    pub(crate) fn try_reserve(&self, additional: usize) -> bool {
+        let additional: usize = match self.max_buffered() {
+            0 => additional,
+            max_buffered => additional.min(max_buffered),
+        };
+        let Some(mut buffer) = self.buffer.try_lock() else {
+            return false;
+        };
+        buffer.reserve(additional);
+        true
+    }
+
     pub(crate) async fn buffer_count(&self) -> usize {
         self.buffer.lock().await.len()
     }
+
+    /// Returns false while another thread holds the buffer, since it may be inserting an item
+    pub(crate) fn try_is_drained(&self) -> bool {
+        self.buffer
+            .try_lock()
+            .is_some_and(|buffer| buffer.is_empty())
+    }
+}
+
+impl<ItemType: Clone> AsyncStream<ItemType> {
+    /// Copies the buffered results in the order they were stored, leaving them buffered
+    pub(crate) async fn snapshot_items(&self) -> Vec<ItemType> {
+        self.buffer
+            .lock()
+            .await
+            .iter()
+            .map(|(_, _, item, _)| item.clone())
+            .collect()
+    }
+}
+
+impl<ItemType> AsyncStream<ItemType> {
+    /// Puts drained items back in front of the buffer in the same order
+    pub(crate) async fn requeue_items(&self, items: Vec<Buffered<ItemType>>) {
+        let mut buffer: MutexGuard<'_, VecDeque<Buffered<ItemType>>> = self.buffer.lock().await;
+        for item in items.into_iter().rev() {
+            buffer.push_front(item);
+            self.counts.0.fetch_add(1, Ordering::Acquire);
+        }
+        drop(buffer);
+        self.wake_consumers();
+    }
+
+    /// Removes the buffered results of the child tasks whose sequence number ``is_taken`` accepts,
+    /// in the order they were stored, leaving the other results buffered
+    pub(crate) async fn take_items(&self, is_taken: impl Fn(u64) -> bool) -> Vec<(u64, ItemType)> {
+        let mut buffer: MutexGuard<'_, VecDeque<Buffered<ItemType>>> = self.buffer.lock().await;
+        self.take_from(&mut buffer, is_taken)
+    }
+
+    /// Removes the results like ``take_items`` does, unless another thread holds the buffer right now
+    #[cfg(feature = "futures")]
+    pub(crate) fn try_take_items(
+        &self,
+        is_taken: impl Fn(u64) -> bool,
+    ) -> Option<Vec<(u64, ItemType)>> {
+        let mut buffer: MutexGuard<'_, VecDeque<Buffered<ItemType>>> = self.buffer.try_lock()?;
+        Some(self.take_from(&mut buffer, is_taken))
+    }
+
+    fn take_from(
+        &self,
+        buffer: &mut VecDeque<Buffered<ItemType>>,
+        is_taken: impl Fn(u64) -> bool,
+    ) -> Vec<(u64, ItemType)> {
+        let mut items: Vec<(u64, ItemType)> = vec![];
+        let mut index: usize = 0;
+        while index < buffer.len() {
+            if !is_taken(buffer[index].0) {
+                index += 1;
+                continue;
+            }
+            if let Some((sequence, _, item, _)) = buffer.remove(index) {
+                items.push((sequence, item));
+                self.decrement_count();
+            }
+        }
+        items
+    }
+
+    pub(crate) async fn drain_sequenced_items(&self) -> Vec<(u64, ItemType)> {
+        self.drain_buffered_items()
+            .await
+            .into_iter()
+            .map(|(sequence, _, item, _)| (sequence, item))
+            .collect()
+    }
+
+    pub(crate) async fn drain_buffered_items(&self) -> Vec<Buffered<ItemType>> {
+        #[allow(unused_mut)]
+        let mut items: Vec<Buffered<ItemType>> = self.buffer.lock().await.drain(..).collect();
+        #[cfg(feature = "deterministic")]
+        if let Some(seed) = self.drain_order.seed() {
+            items.sort_by_key(|(sequence, _, _, _)| drain_key(seed, *sequence));
+        }
+        for _ in 0..items.len() {
+            self.decrement_count();
+        }
+        items
+    }
 }
 
 impl<ItemType> AsyncStream<ItemType> {
-    pub(crate) fn increment(&self) {
+    /// Counts a newly spawned child task and returns its sequence number
+    pub(crate) fn increment(&self) -> u64 {
         self.counts.0.fetch_add(1, Ordering::Acquire);
         self.counts.1.fetch_add(1, Ordering::Acquire);
+        self.sequence.fetch_add(1, Ordering::AcqRel)
     }
 }
 
@@ -46,6 +263,14 @@ impl<ItemType> AsyncStream<ItemType> {
     pub(crate) async fn first(&mut self) -> Option<ItemType> {
         self.next().await
     }
+
+    pub(crate) fn chunks(self, size: usize) -> Chunks<ItemType> {
+        Chunks::new(self, size)
+    }
+
+    pub(crate) fn progress(self) -> Progress<ItemType> {
+        Progress::new(self)
+    }
 }
 
 impl<ItemType> AsyncStream<ItemType> {
@@ -54,8 +279,108 @@ impl<ItemType> AsyncStream<ItemType> {
     }
 
     pub(crate) fn decrement_task_count(&self) {
-        if self.task_count() > 0 {
-            self.counts.1.fetch_sub(1, Ordering::Acquire);
+        if self.release_task() {
+            self.completed.fetch_add(1, Ordering::Release);
+            self.wake_consumers();
+        }
+    }
+
+    /// Decrements the task count for a child task that was cancelled, which doesn't count as completed
+    pub(crate) fn decrement_cancelled_task_count(&self) {
+        if self.release_task() {
+            self.wake_consumers();
+        }
+    }
+
+    /// Decrements the task count without ever wrapping below zero, since a wrapped count would
+    /// keep every wait for the child tasks pending forever, and returns whether it decremented it.
+    ///
+    /// The task count is already zero when a child task finishes while all of them were being cancelled
+    fn release_task(&self) -> bool {
+        let released: u64 = self.released.fetch_add(1, Ordering::AcqRel) + 1;
+        debug_assert!(
+            released <= self.next_sequence(),
+            "a child task was counted as finished more than once"
+        );
+        self.counts
+            .1
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                count.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    pub(crate) fn completed_count(&self) -> usize {
+        self.completed.load(Ordering::Acquire)
+    }
+
+    /// The number of child tasks spawned so far
+    pub(crate) fn spawned_count(&self) -> usize {
+        usize::try_from(self.next_sequence()).unwrap_or(usize::MAX)
+    }
+
+    /// The sequence number the next child task gets
+    pub(crate) fn next_sequence(&self) -> u64 {
+        self.sequence.load(Ordering::Acquire)
+    }
+
+    /// Resolves to true once at least ``count`` child tasks completed,
+    /// or to false once no child task is left running before that
+    pub(crate) fn poll_completed(&self, count: usize, cx: &mut Context<'_>) -> Poll<bool> {
+        if self.completed_count() >= count {
+            return Poll::Ready(true);
+        }
+        self.register_waker(cx);
+        // a child task might have completed before the waker was registered
+        if self.completed_count() >= count {
+            return Poll::Ready(true);
+        }
+        if self.task_count() == 0 {
+            return Poll::Ready(false);
+        }
+        Poll::Pending
+    }
+
+    /// Registers the waker of a consumer, which gets woken once a result is stored or a child task finishes.
+    ///
+    /// Consumers register before checking whether to wait, so they can't miss what happens in between
+    pub(crate) fn register_waker(&self, cx: &Context<'_>) {
+        let mut wakers = self.wakers.lock();
+        if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+    }
+
+    /// Resolves once ``condition`` holds, checking it again whenever a result is stored
+    /// or a child task finishes or stops, instead of over and over
+    pub(crate) async fn wait_until(&self, mut condition: impl FnMut() -> bool) {
+        future::poll_fn(|cx| {
+            if condition() {
+                return Poll::Ready(());
+            }
+            self.register_waker(cx);
+            // the condition might have started to hold before the waker was registered
+            match condition() {
+                true => Poll::Ready(()),
+                false => Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Takes the buffered results along with whether every child task had finished before that,
+    /// registering the waker first so nothing stored or finishing afterwards goes unnoticed
+    pub(crate) fn poll_drain(&self, cx: &Context<'_>) -> (Vec<Buffered<ItemType>>, bool) {
+        self.register_waker(cx);
+        // child tasks store their result before decrementing the task count
+        let finished: bool = self.task_count() == 0;
+        (block_on(self.drain_buffered_items()), finished)
+    }
+
+    pub(crate) fn wake_consumers(&self) {
+        let wakers: Vec<Waker> = std::mem::take(&mut *self.wakers.lock());
+        for waker in wakers {
+            waker.wake();
         }
     }
 
@@ -63,15 +388,24 @@ impl<ItemType> AsyncStream<ItemType> {
         self.counts.0.load(Ordering::Acquire)
     }
 
+    pub(crate) fn increment_count(&self) {
+        self.counts.0.fetch_add(1, Ordering::Acquire);
+    }
+
+    /// Counts a result as consumed, or a child task as having stored none, which makes room in a limited buffer
     pub(crate) fn decrement_count(&self) {
-        if self.item_count() > 0 {
-            self.counts.0.fetch_sub(1, Ordering::Acquire);
-        }
+        _ = self
+            .counts
+            .0
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                count.checked_sub(1)
+            });
+        self.wake_producers();
     }
 
     pub(crate) fn cancel_tasks(&mut self) {
-        self.cancelled = true;
         self.counts.1.store(0, Ordering::Release);
+        self.wake_consumers();
     }
 }
 
@@ -79,9 +413,15 @@ impl<ItemType> Clone for AsyncStream<ItemType> {
     fn clone(&self) -> Self {
         Self {
             buffer: self.buffer.clone(),
-            started: self.started,
             counts: self.counts.clone(),
-            cancelled: self.cancelled,
+            completed: self.completed.clone(),
+            released: self.released.clone(),
+            wakers: self.wakers.clone(),
+            producers: self.producers.clone(),
+            sequence: self.sequence.clone(),
+            max_buffered: self.max_buffered.clone(),
+            #[cfg(feature = "deterministic")]
+            drain_order: self.drain_order.clone(),
         }
     }
 }
@@ -90,28 +430,72 @@ impl<ItemType> AsyncStream<ItemType> {
     pub(crate) fn new() -> Self {
         AsyncStream::<ItemType> {
             buffer: Arc::new(Mutex::new(VecDeque::new())),
-            started: false,
             counts: (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0))),
-            cancelled: false,
+            completed: Arc::new(AtomicUsize::new(0)),
+            released: Arc::new(AtomicU64::new(0)),
+            wakers: Arc::new(parking_lot::Mutex::new(vec![])),
+            producers: Arc::new(parking_lot::Mutex::new(vec![])),
+            sequence: Arc::new(AtomicU64::new(0)),
+            max_buffered: Arc::new(AtomicUsize::new(0)),
+            #[cfg(feature = "deterministic")]
+            drain_order: DrainOrder::default(),
         }
     }
 }
 
+impl<ItemType> AsyncStream<ItemType> {
+    /// Polls like ``poll_next`` does, but pops the buffered result of the highest priority child task first,
+    /// and the one that finished first among results of the same priority
+    pub(crate) fn poll_next_by_priority(&self, cx: &mut Context<'_>) -> Poll<Option<ItemType>> {
+        block_on(async move {
+            let mut inner_lock: MutexGuard<'_, VecDeque<Buffered<ItemType>>> =
+                self.buffer.lock().await;
+            let highest: Option<usize> = inner_lock
+                .iter()
+                .enumerate()
+                .max_by(|(a_index, a), (b_index, b)| a.1.cmp(&b.1).then(b_index.cmp(a_index)))
+                .map(|(index, _)| index);
+            if let Some((_, _, value, _)) = highest.and_then(|index| inner_lock.remove(index)) {
+                self.decrement_count();
+                return Poll::Ready(Some(value));
+            }
+            // registered while holding the buffer, so a result stored afterwards wakes this consumer
+            self.register_waker(cx);
+            if self.task_count() == 0 {
+                return Poll::Ready(None);
+            }
+            Poll::Pending
+        })
+    }
+}
+
+/// Yields the results of the child tasks as they finish and ends once every child task
+/// has either finished or been cancelled, and all their results were yielded.
+///
+/// A pending consumer is parked until a result gets stored or a child task finishes, rather than polled over and over
 impl<ItemType> Stream for AsyncStream<ItemType> {
     type Item = ItemType;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        #[cfg(feature = "deterministic")]
+        if let Some(seed) = self.drain_order.seed() {
+            return self.poll_next_seeded(seed, cx);
+        }
         block_on(async move {
-            let mut inner_lock: MutexGuard<'_, VecDeque<ItemType>> = self.buffer.lock().await;
-            if self.cancelled && inner_lock.is_empty() || self.item_count() == 0 {
+            let mut inner_lock: MutexGuard<'_, VecDeque<Buffered<ItemType>>> =
+                self.buffer.lock().await;
+            if let Some((_, _, value, _)) = inner_lock.pop_front() {
+                self.decrement_count();
+                return Poll::Ready(Some(value));
+            }
+            // registered while holding the buffer, so a result stored afterwards wakes this consumer
+            self.register_waker(cx);
+            // child tasks store their result before decrementing the task count,
+            // so no result can still be on its way here once the count is zero
+            if self.task_count() == 0 {
                 return Poll::Ready(None);
             }
-            let Some(value) = inner_lock.pop_front() else {
-                cx.waker().wake_by_ref();
-                return Poll::Pending;
-            };
-            self.decrement_count();
-            Poll::Ready(Some(value))
+            Poll::Pending
         })
     }
 }
@@ -0,0 +1,65 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use futures_lite::Stream;
+
+use super::AsyncStream;
+
+/// Yields the number of completed child tasks along with the number of spawned child tasks whenever either changed,
+/// and ends once no child task is running anymore and the latest progress was yielded
+pub(crate) struct Progress<ItemType> {
+    stream: AsyncStream<ItemType>,
+    last: Option<(usize, usize)>,
+}
+
+impl<ItemType> Progress<ItemType> {
+    pub(crate) fn new(stream: AsyncStream<ItemType>) -> Self {
+        Self { stream, last: None }
+    }
+
+    /// Returns the current progress unless it was already yielded
+    fn changed(&mut self) -> Option<(usize, usize)> {
+        let current: (usize, usize) = (self.stream.completed_count(), self.stream.spawned_count());
+        if self.last == Some(current) {
+            return None;
+        }
+        self.last = Some(current);
+        Some(current)
+    }
+}
+
+impl<ItemType> Clone for Progress<ItemType> {
+    fn clone(&self) -> Self {
+        Self {
+            stream: self.stream.clone(),
+            last: self.last,
+        }
+    }
+}
+
+// the stream is only a handle to the shared state, never pinned
+impl<ItemType> Unpin for Progress<ItemType> {}
+
+impl<ItemType> Stream for Progress<ItemType> {
+    type Item = (usize, usize);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this: &mut Self = self.get_mut();
+        // the task count is read first, since child tasks count as completed before they stop running
+        let running: bool = this.stream.task_count() > 0;
+        if let Some(progress) = this.changed() {
+            return Poll::Ready(Some(progress));
+        }
+        if !running {
+            return Poll::Ready(None);
+        }
+        this.stream.register_waker(cx);
+        // a child task might have completed before the waker was registered
+        match this.changed() {
+            Some(progress) => Poll::Ready(Some(progress)),
+            None => Poll::Pending,
+        }
+    }
+}
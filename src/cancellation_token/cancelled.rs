@@ -0,0 +1,46 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use super::CancellationToken;
+
+/// A future that finishes once its ``CancellationToken`` is cancelled
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Cancelled {
+    token: CancellationToken,
+    id: Option<usize>,
+}
+
+impl Cancelled {
+    pub(crate) fn new(token: CancellationToken) -> Self {
+        Self { token, id: None }
+    }
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        let id: usize = self.token.register(self.id, cx.waker());
+        self.id = Some(id);
+        // the token might have been cancelled before the waker was registered
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Cancelled {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.token.unregister(id);
+        }
+    }
+}
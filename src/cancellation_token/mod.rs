@@ -0,0 +1,170 @@
+mod cancelled;
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    future::Future,
+    pin::pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Weak,
+    },
+    task::Waker,
+};
+
+use parking_lot::Mutex;
+
+pub use self::cancelled::Cancelled;
+
+thread_local! {
+    /// The token of the spawn group whose child task the current thread is running, if any
+    static CURRENT: RefCell<Option<CancellationToken>> = const { RefCell::new(None) };
+}
+
+/// Cancellation Token
+///
+/// A cheaply clonable token that lets child tasks observe that their spawn group has been cancelled,
+/// so they get a chance to clean up before they are forcefully cancelled.
+///
+/// Every clone refers to the same token, so cancelling one of them cancels all of them.
+///
+/// Tokens form a tree: a child token is cancelled along with its parent, but can also be cancelled on its own.
+/// A spawn group created while a child task of another spawn group runs, such as by a nested ``with_spawn_group()``,
+/// gets a child token of that spawn group's token, so cancelling the outermost spawn group cancels the tokens of every spawn group beneath it.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::CancellationToken;
+///
+/// # spawn_groups::block_on(async move {
+/// let token = CancellationToken::new();
+/// let cloned_token = token.clone();
+/// assert!(!cloned_token.is_cancelled());
+///
+/// token.cancel();
+/// cloned_token.cancelled().await;
+/// assert!(cloned_token.is_cancelled());
+/// # });
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    next_id: AtomicUsize,
+    wakers: Mutex<HashMap<usize, Waker>>,
+    children: Mutex<Vec<Weak<Inner>>>,
+}
+
+impl CancellationToken {
+    /// Creates a token which is not cancelled yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl CancellationToken {
+    /// A Boolean value that indicates whether the token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Cancels the token along with all of its child tokens, and wakes every task awaiting their cancellation
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Release);
+        for (_, waker) in self.inner.wakers.lock().drain() {
+            waker.wake();
+        }
+        let children: Vec<Weak<Inner>> = std::mem::take(&mut *self.inner.children.lock());
+        for inner in children.iter().filter_map(Weak::upgrade) {
+            CancellationToken { inner }.cancel();
+        }
+    }
+
+    /// Returns a new token which is cancelled once this token is cancelled,
+    /// while cancelling the new token leaves this token as it is
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::CancellationToken;
+    ///
+    /// let crawl = CancellationToken::new();
+    /// let page = crawl.child_token();
+    /// let link = page.child_token();
+    /// let other_page = crawl.child_token();
+    ///
+    /// other_page.cancel();
+    /// assert!(!crawl.is_cancelled() && !page.is_cancelled());
+    ///
+    /// crawl.cancel();
+    /// assert!(page.is_cancelled() && link.is_cancelled());
+    /// assert!(crawl.child_token().is_cancelled());
+    /// ```
+    pub fn child_token(&self) -> CancellationToken {
+        let child: CancellationToken = CancellationToken::new();
+        let mut children = self.inner.children.lock();
+        // checked while holding the children, so a concurrent cancel either sees the child or is seen here
+        if self.is_cancelled() {
+            child.cancel();
+            return child;
+        }
+        children.retain(|inner| inner.strong_count() > 0);
+        children.push(Arc::downgrade(&child.inner));
+        child
+    }
+
+    /// Returns a future that finishes once the token is cancelled
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled::new(self.clone())
+    }
+}
+
+impl CancellationToken {
+    fn register(&self, id: Option<usize>, waker: &Waker) -> usize {
+        let id: usize = id.unwrap_or_else(|| self.inner.next_id.fetch_add(1, Ordering::Relaxed));
+        self.inner.wakers.lock().insert(id, waker.clone());
+        id
+    }
+
+    fn unregister(&self, id: usize) {
+        self.inner.wakers.lock().remove(&id);
+    }
+}
+
+impl CancellationToken {
+    /// The token of the spawn group whose child task the current thread is running, if any
+    pub(crate) fn current() -> Option<CancellationToken> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Runs the future as a child task of the spawn group this token belongs to,
+    /// so every spawn group created while it's polled gets a child token of this token
+    pub(crate) async fn as_current<F: Future>(&self, future: F) -> F::Output {
+        let mut future = pin!(future);
+        futures_lite::future::poll_fn(|cx| {
+            let _current: Current = Current::enter(self.clone());
+            future.as_mut().poll(cx)
+        })
+        .await
+    }
+}
+
+/// Makes a token the current one until dropped, then restores the previous one, even when the poll panics
+struct Current(Option<CancellationToken>);
+
+impl Current {
+    fn enter(token: CancellationToken) -> Self {
+        Self(CURRENT.with(|current| current.replace(Some(token))))
+    }
+}
+
+impl Drop for Current {
+    fn drop(&mut self) {
+        CURRENT.with(|current| *current.borrow_mut() = self.0.take());
+    }
+}
@@ -1,15 +1,27 @@
+use crate::cancellation_token::CancellationToken;
+#[cfg(feature = "stats")]
+use crate::shared::stats::TaskStats;
 use crate::shared::{
-    initializible::Initializible, priority::Priority, runtime::RuntimeEngine, sharedfuncs::Shared,
+    cancel_order::CancelOrder, drop_policy::DropPolicy, initializible::Initializible,
+    metrics::MetricsRecorder, panic_policy::PanicPolicy, priority::Priority,
+    queue_snapshot::QueueSnapshot, runtime::RuntimeEngine, saturation_policy::SaturationPolicy,
+    scheduling_policy::SchedulingPolicy, scope::Scope, semaphore::Permit, sharedfuncs::Shared,
+    spawn_error::SpawnError, spawner::Spawner, task_id::TaskId, task_info::TaskInfo,
+    task_status::TaskStatus,
 };
 
+use futures_lite::Stream;
+use std::collections::HashMap;
 use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// Discarding Spawn Group
 ///
 /// A kind of a spawn group that spawns asynchronous tasks that returns nothing,
 /// implicitly waits for all spawned tasks to finish before being dropped
 /// and releases all the resources before being dropped unless by
-/// explicitly calling ``dont_wait_at_drop()``
+/// explicitly calling ``dont_wait_at_drop()`` or ``set_drop_policy()``
 ///
 /// Child tasks are spawned by calling either ``spawn_task()`` or ``spawn_task_unless_cancelled()`` methods.
 ///
@@ -18,17 +30,236 @@ use std::future::Future;
 /// Child tasks spawned to a spawn group execute concurrently, and may be scheduled in
 /// any order.
 ///
+/// # Empty spawn groups
+///
+/// A discarding spawn group that no child task was spawned into is already finished,
+/// so every way of waiting for it returns right away
+///
+/// ```rust
+/// use spawn_groups::{spawn_group::SpawnGroupBuilder, with_discarding_spawn_group};
+///
+/// # spawn_groups::block_on(async move {
+/// with_discarding_spawn_group(|mut group| async move {
+///     assert!(group.is_empty());
+///     assert!(group.wait_until_completed(0).await);
+///     assert!(!group.wait_until_completed(1).await);
+///     group.cancel_and_wait().await;
+/// }).await;
+///
+/// SpawnGroupBuilder::new().build_discarding().close().await;
+/// # });
+/// ```
 pub struct DiscardingSpawnGroup {
     /// A field that indicates if the spawn group has been cancelled
     pub is_cancelled: bool,
     runtime: RuntimeEngine<()>,
-    wait_at_drop: bool,
+    drop_policy: DropPolicy,
 }
 
 impl DiscardingSpawnGroup {
-    /// Don't implicity wait for spawned child tasks to finish before being dropped
+    /// Don't implicity wait for spawned child tasks to finish before being dropped,
+    /// cancel them instead
+    ///
+    /// This is the same as setting the drop policy to ``DropPolicy::Cancel``
     pub fn dont_wait_at_drop(&mut self) {
-        self.wait_at_drop = false;
+        self.drop_policy = DropPolicy::Cancel;
+    }
+
+    /// Sets what happens to still running child tasks when the spawn group gets dropped.
+    ///
+    /// The default is ``DropPolicy::WaitAll``
+    ///
+    /// # Parameters
+    ///
+    /// * `drop_policy`: the drop policy to use
+    pub fn set_drop_policy(&mut self, drop_policy: DropPolicy) {
+        self.drop_policy = drop_policy;
+    }
+
+    /// Waits for the still running child tasks, or cancels or detaches them as the drop policy says,
+    /// without blocking the caller's thread, then consumes the spawn group.
+    ///
+    /// This is the recommended way to tear down a spawn group in asynchronous code,
+    /// since dropping it blocks the thread it gets dropped on, which only serves as a safety net.
+    /// Nothing is left for the drop to do once it returns.
+    pub async fn close(mut self) {
+        match self.drop_policy {
+            DropPolicy::WaitAll => self.runtime.wait_for_all_tasks_async().await,
+            DropPolicy::Cancel => self.cancel_and_wait().await,
+            DropPolicy::Detach => self.runtime.detach_to_background(),
+        }
+        // there is nothing left to wait for or cancel, so the drop only releases the child tasks' bookkeeping
+        self.drop_policy = DropPolicy::Detach;
+    }
+
+    /// Leaves the still running child tasks running in the background and consumes the spawn group
+    /// without blocking the caller's thread, whatever the drop policy says.
+    ///
+    /// Just like under ``DropPolicy::Detach``, the child tasks are handed over to a background thread
+    /// which keeps the spawn group's threads alive until they finish and discards their results as they come in,
+    /// so their results don't pile up when nobody consumes them.
+    pub fn detach(mut self) {
+        self.runtime.detach_to_background();
+        self.drop_policy = DropPolicy::Detach;
+    }
+
+    /// Limits how many of the child tasks spawned afterwards run at the same time,
+    /// the rest wait until a running child task finishes.
+    ///
+    /// Whenever a running child task finishes, the waiting child task of the highest priority starts next,
+    /// and among the ones of the same priority, the earliest spawned.
+    ///
+    /// Zero removes the limit, which is the default.
+    ///
+    /// # Parameters
+    ///
+    /// * `max_concurrency`: the maximum number of child tasks running at the same time
+    pub fn set_max_concurrency(&mut self, max_concurrency: usize) {
+        self.runtime.set_max_concurrency(max_concurrency);
+    }
+
+    /// Limits how many of the child tasks of each priority in ``limits``, spawned afterwards, run at the same time,
+    /// so child tasks of a saturated priority wait while those of the other priorities keep starting.
+    ///
+    /// Priorities missing from ``limits``, or mapped to zero, have no limit of their own.
+    /// Child tasks also wait for the limit set by ``set_max_concurrency``, but only once their priority's limit let them through,
+    /// so they never take a slot of it while waiting.
+    ///
+    /// # Parameters
+    ///
+    /// * `limits`: the maximum number of child tasks of each priority running at the same time
+    pub fn set_concurrency_limits(&mut self, limits: HashMap<Priority, usize>) {
+        self.runtime.set_concurrency_limits(limits);
+    }
+
+    /// Lets child tasks waiting for the concurrency limit set by ``set_max_concurrency`` age,
+    /// so their priority rises a level for every ``rate`` they waited, and child tasks of a low priority
+    /// eventually run even while child tasks of a higher priority keep getting spawned.
+    ///
+    /// Zero turns aging off, which is the default.
+    ///
+    /// # Parameters
+    ///
+    /// * `rate`: how long a child task waits for each level its priority rises
+    pub fn set_aging(&mut self, rate: Duration) {
+        self.runtime.set_aging(rate);
+    }
+
+    /// Sets how the child tasks waiting for the concurrency limit set by ``set_max_concurrency`` take turns,
+    /// such as ``SchedulingPolicy::WeightedFair`` to share the runs between their priorities rather than
+    /// always running the highest priority first, so child tasks of a low priority are never starved.
+    ///
+    /// The default is ``SchedulingPolicy::Strict``
+    ///
+    /// # Parameters
+    ///
+    /// * `scheduling_policy`: the scheduling policy to use
+    pub fn set_scheduling_policy(&mut self, scheduling_policy: SchedulingPolicy) {
+        self.runtime.set_scheduling_policy(scheduling_policy);
+    }
+
+    /// Waits for a slot of the concurrency limit set by ``set_max_concurrency``, for work outside of the spawn group
+    /// which should count against the same limit, and returns a guard which gives the slot back when dropped.
+    ///
+    /// The caller waits in line with the child tasks: behind the ones of a higher priority, and behind the ones of
+    /// the default priority spawned so far, while child tasks spawned afterwards wait behind it.
+    /// While the guard is held, one less child task runs at the same time, so holding it across waiting for the spawn group
+    /// with a limit of one never finishes.
+    ///
+    /// Without a concurrency limit, it returns right away with a guard which holds nothing.
+    /// Changing the limit afterwards doesn't affect guards already held.
+    pub async fn acquire_permit(&self) -> Permit {
+        self.runtime.acquire_permit(Priority::default()).await
+    }
+
+    /// Paces the starts of the child tasks spawned afterwards to at most ``per_second`` a second,
+    /// spreading a burst of them out evenly rather than starting them all at once.
+    ///
+    /// The rate limits how often child tasks start, while ``set_max_concurrency`` limits how many run at the same time.
+    /// With both, a child task first waits until it may run, then until its start is due.
+    ///
+    /// Zero removes the limit, which is the default.
+    ///
+    /// # Parameters
+    ///
+    /// * `per_second`: the highest number of child tasks starting a second
+    pub fn set_rate_limit(&mut self, per_second: u32) {
+        self.runtime.set_rate_limit(per_second);
+    }
+
+    /// Allocates room for ``additional`` more child tasks up front,
+    /// so spawning a large batch of child tasks doesn't keep reallocating while holding the spawn group's locks
+    ///
+    /// # Parameters
+    ///
+    /// * `additional`: the number of child tasks about to be spawned
+    pub fn reserve(&mut self, additional: usize) {
+        self.runtime.reserve(additional);
+    }
+
+    /// Registers a closure called with the id of every child task right before it starts running,
+    /// which is after it waited for the concurrency limit and the rate limit, if any.
+    ///
+    /// Closures are called on the threads driving the child tasks, in the order they were registered.
+    ///
+    /// # Parameters
+    ///
+    /// * `observer`: the closure to call
+    pub fn on_start<F>(&mut self, observer: F)
+    where
+        F: Fn(TaskId) + Send + Sync + 'static,
+    {
+        self.runtime.on_start(observer);
+    }
+
+    /// Returns the cancellation token of the spawn group.
+    ///
+    /// The token is cancelled as soon as the spawn group is cancelled, so child tasks holding a clone of it
+    /// can observe the cancellation and clean up within the grace period set by ``set_cancellation_grace_period``.
+    /// Once cancelled, the token stays cancelled.
+    ///
+    /// When the spawn group was created while a child task of another spawn group runs, its token is a child token
+    /// of that spawn group's token, so it gets cancelled along with the enclosing spawn group.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.runtime.token()
+    }
+
+    /// Returns a handle which spawns child tasks into the spawn group through a shared reference,
+    /// so child tasks can capture a clone of it to spawn more child tasks while they run.
+    ///
+    /// Waiting for the spawn group also waits for the child tasks spawned through it.
+    pub fn spawner(&self) -> Spawner<()> {
+        Spawner::new(self.runtime.clone(), None)
+    }
+
+    /// Sets how long cancelling waits for child tasks to finish on their own after the cancellation token is cancelled,
+    /// before they get forcefully cancelled.
+    ///
+    /// The default is zero, which forcefully cancels child tasks right away.
+    ///
+    /// ``cancel_all`` and dropping the spawn group block the thread they're called on until the child tasks finished
+    /// or the grace period is over, while ``cancel_and_wait`` and ``close`` wait for it without blocking the caller's executor.
+    ///
+    /// # Parameters
+    ///
+    /// * `grace_period`: the longest time to wait for child tasks to finish on their own
+    pub fn set_cancellation_grace_period(&mut self, grace_period: Duration) {
+        self.runtime.set_grace_period(grace_period);
+    }
+
+    /// Registers a recorder which receives an event whenever a child task is spawned, returns, is cancelled or panics,
+    /// replacing the previous one.
+    ///
+    /// Only child tasks spawned afterwards report their events to it.
+    ///
+    /// # Parameters
+    ///
+    /// * `recorder`: the recorder to report to
+    pub fn set_metrics_recorder<R>(&mut self, recorder: R)
+    where
+        R: MetricsRecorder + 'static,
+    {
+        self.runtime.set_metrics_recorder(Arc::new(recorder));
     }
 }
 
@@ -36,6 +267,14 @@ impl DiscardingSpawnGroup {
     pub(crate) fn new() -> Self {
         Self::init()
     }
+
+    pub(crate) fn from_parts(runtime: RuntimeEngine<()>, drop_policy: DropPolicy) -> Self {
+        Self {
+            is_cancelled: false,
+            runtime,
+            drop_policy,
+        }
+    }
 }
 
 impl DiscardingSpawnGroup {
@@ -45,31 +284,232 @@ impl DiscardingSpawnGroup {
     ///
     /// * `priority`: priority to use
     /// * `closure`: an async closure that doesn't return anything
-    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F)
+    ///
+    /// # Returns
+    /// The id of the child task, which ``cancel_task`` takes to cancel it on its own
+    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F) -> TaskId
+    where
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        self.add_task(priority, closure)
+    }
+
+    /// Spawns a new task into the spawn group which gets cancelled if it doesn't finish in time,
+    /// in which case it stores no result
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `timeout`: the longest time the child task may run for
+    /// * `closure`: an async closure that doesn't return anything
+    pub fn spawn_task_with_timeout<F>(
+        &mut self,
+        priority: Priority,
+        timeout: Duration,
+        closure: F,
+    ) -> TaskId
+    where
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        self.runtime
+            .write_task_with_timeout(priority, timeout, closure)
+    }
+
+    /// Spawns a new child task which runs a blocking closure, such as a CPU-bound computation
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: a closure to run
+    pub fn spawn_blocking_task<F>(&mut self, priority: Priority, closure: F) -> TaskId
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.runtime.write_blocking_task(priority, closure)
+    }
+
+    /// Spawns a new child task with a name that shows up in ``debug_snapshot()``
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `name`: the name of the child task
+    /// * `closure`: an async closure that return a value of type ``()``
+    pub fn spawn_named_task<F>(&mut self, priority: Priority, name: &str, closure: F) -> TaskId
+    where
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        self.runtime.write_named_task(priority, name, closure)
+    }
+
+    /// Spawns a new child task which only starts once the child tasks of the given ids finished,
+    /// so child tasks can depend on each other the way build steps do
+    ///
+    /// A child task counts as finished once it returned, panicked or was cancelled,
+    /// and ids of child tasks that were already waited for, or that are unknown to the spawn group, count as finished too.
+    /// Nothing detects cycles, so child tasks that depend on each other never start.
+    ///
+    /// The child task waits for its prerequisites before acquiring its turn under ``set_max_concurrency``,
+    /// so it never holds one up while waiting.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `after`: the ids of the child tasks to wait for
+    /// * `closure`: an async closure that return doesn't return anything
+    pub fn spawn_task_after<F>(
+        &mut self,
+        priority: Priority,
+        after: &[TaskId],
+        closure: F,
+    ) -> TaskId
+    where
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        self.runtime.write_task_after(priority, after, closure)
+    }
+
+    /// Spawn a new task only if the group is neither cancelled nor closed yet,
+    /// otherwise does nothing
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return doesn't return anything
+    ///
+    /// # Returns
+    /// Whether the child task was spawned
+    pub fn spawn_task_unless_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
+    where
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        self.add_task_unlessed_cancelled(priority, closure)
+    }
+
+    /// Spawns a new task like ``spawn_task_unless_cancelled`` does, but tells why the child task wasn't spawned
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that doesn't return anything
+    ///
+    /// # Returns
+    /// - Ok: the id of the child task, which ``cancel_task`` takes to cancel it on its own
+    /// - Err: ``SpawnError::Cancelled`` if the spawn group was cancelled, ``SpawnError::Closed`` if it was closed,
+    ///   or ``SpawnError::Saturated`` if ``SaturationPolicy::Reject`` rejected the child task
+    pub fn try_spawn_task<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+    ) -> Result<TaskId, SpawnError>
     where
         F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
     {
-        self.add_task(priority, closure);
+        self.runtime.try_write_task(priority, closure)
     }
 
-    /// Spawn a new task only if the group is not cancelled yet,
+    /// Spawns a new task like ``try_spawn_task`` does, but while ``SaturationPolicy::Block`` holds it back,
+    /// it waits on the caller's executor instead of blocking its thread
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that doesn't return anything
+    ///
+    /// # Returns
+    /// The same as ``try_spawn_task`` does
+    pub async fn try_spawn_task_async<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+    ) -> Result<TaskId, SpawnError>
+    where
+        F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
+    {
+        self.runtime.try_write_task_async(priority, closure).await
+    }
+
+    /// Spawn a new task only if the group is neither cancelled nor closed yet,
     /// otherwise does nothing
     ///
     /// # Parameters
     ///
     /// * `priority`: priority to use
     /// * `closure`: an async closure that return doesn't return anything
+    #[deprecated(since = "2.0.0", note = "Use spawn_task_unless_cancelled instead")]
     pub fn spawn_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
     where
         F: Future<Output = <DiscardingSpawnGroup as Shared>::Result> + Send + 'static,
     {
-        self.add_task_unlessed_cancelled(priority, closure);
+        self.spawn_task_unless_cancelled(priority, closure);
     }
 
     /// Cancels all running task in the spawn group
     pub fn cancel_all(&mut self) {
         self.cancel_all_tasks();
     }
+
+    /// Cancels all running task in the spawn group, and waits until every one of them actually stopped
+    ///
+    /// Unlike ``cancel_all``, which only requests the cancellation, a child task being polled on another thread
+    /// is done running once this returns.
+    pub async fn cancel_and_wait(&mut self) {
+        self.runtime.cancel_and_wait().await;
+        self.is_cancelled = true;
+    }
+
+    /// Closes the spawn group gracefully, so it rejects every child task spawned afterwards
+    /// while the already spawned ones keep running until they finish.
+    ///
+    /// Unlike cancelling, nothing gets cancelled. ``spawn_task_unless_cancelled`` returns false for a rejected child task,
+    /// while the other spawn methods return an id which matches no child task.
+    /// Waiting for the spawn group afterwards waits for the already spawned child tasks only.
+    pub fn close_gracefully(&mut self) {
+        self.runtime.close();
+    }
+
+    /// Returns whether the spawn group was closed gracefully with ``close_gracefully``
+    pub fn is_closed(&self) -> bool {
+        self.runtime.is_closed()
+    }
+
+    /// Cancels a single child task, leaving the other child tasks running
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: the id the child task was spawned with
+    ///
+    /// # Returns
+    /// Whether the child task was cancelled, which is false if it already finished
+    pub fn cancel_task(&mut self, id: TaskId) -> bool {
+        self.runtime.cancel_task(id)
+    }
+
+    /// Returns where the child task behind ``id`` is in its life, without waiting for it
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: the id the child task was spawned with
+    ///
+    /// # Returns
+    /// The status of the child task, or ``None`` if the id wasn't issued by this spawn group,
+    /// or if the child task already stopped when the spawn group was last waited for, which forgets the statuses of stopped child tasks
+    pub fn task_status(&self, id: TaskId) -> Option<TaskStatus> {
+        self.runtime.task_status(id)
+    }
+
+    /// Cancels every child task of a priority strictly lower than ``priority``,
+    /// leaving the child tasks of the same or a higher priority running
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: the lowest priority of the child tasks to keep
+    ///
+    /// # Returns
+    /// The number of child tasks cancelled, which leaves out those that already finished
+    pub fn cancel_below(&mut self, priority: Priority) -> usize {
+        self.runtime.cancel_below(priority)
+    }
 }
 
 impl DiscardingSpawnGroup {
@@ -87,14 +527,182 @@ impl DiscardingSpawnGroup {
         }
         false
     }
+
+    /// Returns the number of child tasks still running, without blocking.
+    ///
+    /// It reaches zero exactly when waiting for all child tasks would return
+    pub fn pending_count(&self) -> usize {
+        self.runtime.stream().task_count()
+    }
+
+    /// Returns the number of child tasks that finished so far, without blocking.
+    ///
+    /// Child tasks that were cancelled are not counted
+    pub fn completed_count(&self) -> usize {
+        self.runtime.stream().completed_count()
+    }
+
+    /// Returns a stream of the spawn group's progress as the number of completed child tasks
+    /// along with the number of child tasks spawned so far, yielded whenever a child task completes.
+    ///
+    /// It doesn't consume any result, so it can be polled alongside the results,
+    /// and each of its clones yields every progress update on its own.
+    /// It ends once no child task is running anymore. Cancelled child tasks never count as completed.
+    pub fn progress(&self) -> impl Stream<Item = (usize, usize)> + Clone {
+        self.runtime.stream().progress()
+    }
+
+    /// Waits until at least ``count`` child tasks completed, without consuming any of their results,
+    /// so they all stay in the stream for later.
+    ///
+    /// Returns right away when that many child tasks already completed, including when ``count`` is zero.
+    ///
+    /// # Parameters
+    ///
+    /// * `count`: the number of completed child tasks to wait for
+    ///
+    /// # Returns
+    /// Whether that many child tasks completed, which is false if no child task was left running before that,
+    /// because too few were spawned or the rest were cancelled
+    pub async fn wait_until_completed(&self, count: usize) -> bool {
+        self.runtime.wait_until_completed(count).await
+    }
+
+    /// Sets what happens to the child tasks spawned while the threads of the spawn group are saturated,
+    /// which is while child tasks are already waiting for one of them.
+    ///
+    /// ``SaturationPolicy::Reject`` rejects them the way ``close_gracefully`` does, applying backpressure to whoever spawns them,
+    /// while ``SaturationPolicy::Block`` blocks the spawning thread until the waiting child tasks got a thread,
+    /// so it must not be used to spawn from inside the spawn group's own child tasks.
+    ///
+    /// The default is ``SaturationPolicy::Queue``
+    ///
+    /// # Parameters
+    ///
+    /// * `saturation_policy`: the saturation policy to use
+    pub fn set_saturation_policy(&mut self, saturation_policy: SaturationPolicy) {
+        self.runtime.set_saturation_policy(saturation_policy);
+    }
+
+    /// Sets the order in which the child tasks still running get cancelled when the spawn group is cancelled.
+    ///
+    /// ``cancel_and_wait`` only cancels each child task once the one before it stopped,
+    /// so their futures are dropped in that order, while ``cancel_all`` and dropping the spawn group
+    /// only tell the backend to stop them in that order, which may drop their futures in another one.
+    ///
+    /// The default is ``CancelOrder::Spawned``
+    ///
+    /// # Parameters
+    ///
+    /// * `cancel_order`: the cancel order to use
+    pub fn set_cancel_order(&mut self, cancel_order: CancelOrder) {
+        self.runtime.set_cancel_order(cancel_order);
+    }
+
+    /// Sets what happens when a child task panics.
+    ///
+    /// The default is ``PanicPolicy::Isolate``
+    ///
+    /// # Parameters
+    ///
+    /// * `panic_policy`: the panic policy to use
+    pub fn set_panic_policy(&mut self, panic_policy: PanicPolicy) {
+        self.runtime.set_panic_policy(panic_policy);
+    }
+
+    /// Returns the number of child tasks that panicked so far
+    pub fn panicked_count(&self) -> usize {
+        self.runtime.panicked_count()
+    }
+
+    /// Returns whether the spawn group was cancelled, either explicitly or by a method that cancels the remaining child tasks
+    pub fn was_cancelled(&self) -> bool {
+        self.runtime.was_cancelled()
+    }
+
+    /// Describes the child tasks that have not finished yet, which helps finding out why a spawn group hangs
+    ///
+    /// They are listed in the order the spawn group waits for them.
+    pub fn debug_snapshot(&self) -> Vec<TaskInfo> {
+        self.runtime.debug_snapshot()
+    }
+
+    /// The number of threads of the spawn group running a child task right now
+    ///
+    /// Along with ``pool_queued()``, this helps tuning the thread count and the maximum concurrency.
+    /// With the ``tokio`` feature, every child task of the spawn group that hasn't finished counts as running.
+    pub fn pool_active(&self) -> usize {
+        self.runtime.pool_active()
+    }
+
+    /// The number of child tasks of the spawn group waiting for a thread to run them
+    ///
+    /// With the ``tokio`` feature this is unavailable and always zero,
+    /// since Tokio doesn't tell which of its queued tasks belong to the spawn group.
+    pub fn pool_queued(&self) -> usize {
+        self.runtime.pool_queued()
+    }
+
+    /// Reports how deep the queues of the spawn group are to a callback at a fixed interval,
+    /// such as to feed a time series of its load into a metrics backend
+    ///
+    /// The callback runs on a background thread of its own, rather than on the spawn group's threads,
+    /// first right away and then once every interval, until the spawn group is dropped or closed,
+    /// which waits for a call in progress to return.
+    ///
+    /// # Parameters
+    ///
+    /// * `interval`: how long to wait between two snapshots
+    /// * `report`: the callback receiving every snapshot
+    pub fn sample_queue_depth<F>(&self, interval: Duration, report: F)
+    where
+        F: Fn(QueueSnapshot) + Send + 'static,
+    {
+        self.runtime.sample_queue_depth(interval, report);
+    }
+
+    /// Returns how long the child tasks that returned so far took from being spawned to returning,
+    /// as their count along with their mean, maximum and 99th percentile latency.
+    ///
+    /// Only available with the ``stats`` feature.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> TaskStats {
+        self.runtime.stats()
+    }
+
+    /// Grows or shrinks the threads of the spawn group to ``thread_count``, clamping zero to a single thread.
+    ///
+    /// It is safe to call while child tasks are running: surplus threads exit once they got through the work
+    /// already queued up for them, and the work queued afterwards runs on the resized threads.
+    /// Together with ``pool_queued()``, this lets a long-lived spawn group scale its threads with its load.
+    /// With the ``tokio`` feature, the runtime can't be resized, so this does nothing.
+    ///
+    /// # Parameters
+    ///
+    /// * `thread_count`: the number of threads to drive the child tasks on
+    ///
+    /// # Returns
+    /// Whether the threads were resized, which is never the case with the ``tokio`` feature
+    pub fn set_thread_count(&mut self, thread_count: usize) -> bool {
+        self.runtime.set_thread_count(thread_count)
+    }
+}
+
+impl DiscardingSpawnGroup {
+    pub(crate) fn scope(&self) -> Scope<()> {
+        Scope::new(self.runtime.clone())
+    }
 }
 
 impl Drop for DiscardingSpawnGroup {
     fn drop(&mut self) {
-        if self.wait_at_drop {
-            self.runtime.wait_for_all_tasks();
-        } else {
-            self.runtime.end()
+        self.runtime.stop_sampling();
+        match self.drop_policy {
+            // blocking while unwinding could hang on child tasks the panic left waiting
+            DropPolicy::WaitAll if std::thread::panicking() => self.runtime.cancel(),
+            DropPolicy::WaitAll => self.runtime.wait_for_all_tasks(),
+            DropPolicy::Cancel => self.runtime.cancel(),
+            DropPolicy::Detach => self.runtime.detach_to_background(),
         }
     }
 }
@@ -102,20 +710,21 @@ impl Drop for DiscardingSpawnGroup {
 impl Shared for DiscardingSpawnGroup {
     type Result = ();
 
-    fn add_task<F>(&mut self, priority: Priority, closure: F)
+    fn add_task<F>(&mut self, priority: Priority, closure: F) -> TaskId
     where
         F: Future<Output = Self::Result> + Send + 'static,
     {
-        self.runtime.write_task(priority, closure);
+        self.runtime.write_task(priority, closure)
     }
 
-    fn add_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
+    fn add_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
     where
         F: Future<Output = Self::Result> + Send + 'static,
     {
-        if !self.is_cancelled {
-            self.add_task(priority, closure)
+        if !self.runtime.write_task_unless_cancelled(priority, closure) {
+            return false;
         }
+        true
     }
 
     fn cancel_all_tasks(&mut self) {
@@ -126,10 +735,6 @@ impl Shared for DiscardingSpawnGroup {
 
 impl Initializible for DiscardingSpawnGroup {
     fn init() -> Self {
-        DiscardingSpawnGroup {
-            is_cancelled: false,
-            runtime: RuntimeEngine::init(),
-            wait_at_drop: true,
-        }
+        DiscardingSpawnGroup::from_parts(RuntimeEngine::init(), DropPolicy::default())
     }
 }
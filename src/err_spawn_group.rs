@@ -1,21 +1,31 @@
+use crate::cancellation_token::CancellationToken;
+#[cfg(feature = "stats")]
+use crate::shared::stats::TaskStats;
 use crate::shared::{
-    initializible::Initializible, priority::Priority, runtime::RuntimeEngine, sharedfuncs::Shared,
+    cancel_order::CancelOrder, completion::Completion, drop_policy::DropPolicy,
+    initializible::Initializible, metrics::MetricsRecorder, panic_policy::PanicPolicy,
+    priority::Priority, queue_snapshot::QueueSnapshot, runtime::RuntimeEngine,
+    saturation_policy::SaturationPolicy, scheduling_policy::SchedulingPolicy, scope::Scope,
+    semaphore::Permit, sharedfuncs::Shared, spawn_error::SpawnError, spawner::Spawner,
+    task_id::TaskId, task_info::TaskInfo, task_result::TaskResult, task_status::TaskStatus,
     wait::Waitable,
 };
 use async_trait::async_trait;
 use futures_lite::{Stream, StreamExt};
+use std::collections::HashMap;
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
     Arc,
 };
 use std::task::{Context, Poll};
+use std::time::Duration;
 use std::{future::Future, pin::Pin};
 
 /// Err Spawn Group
 ///
 /// A kind of a spawn group that spawns asynchronous child tasks that returns a value of Result<ValueType, ErrorType>,
 /// that implicitly wait for the spawned tasks to return before being dropped unless by
-/// explicitly calling ``dont_wait_at_drop()``
+/// explicitly calling ``dont_wait_at_drop()`` or ``set_drop_policy()``
 ///
 /// Child tasks are spawned by calling either ``spawn_task()`` or ``spawn_task_unless_cancelled()`` methods.
 ///
@@ -26,84 +36,1238 @@ use std::{future::Future, pin::Pin};
 ///
 /// It dereferences into a ``futures`` crate ``Stream`` type where the results of each finished child task is stored and it pops out the result in First-In First-Out
 /// FIFO order whenever it is being used
+///
+/// # Empty spawn groups
+///
+/// Like an empty ``SpawnGroup``, an err spawn group that no child task was spawned into is already finished,
+/// so every way of waiting for it returns right away, with no results and no errors
+///
+/// ```rust
+/// use futures_lite::StreamExt;
+/// use spawn_groups::{err_spawn_group::ErrSpawnGroup, with_err_spawn_group};
+/// use std::time::Duration;
+///
+/// # spawn_groups::block_on(async move {
+/// with_err_spawn_group(|mut group: ErrSpawnGroup<u64, String>| async move {
+///     assert!(group.is_empty());
+///     assert!(group.is_finished());
+///     group.wait_for_all().await;
+///     assert_eq!(group.wait_for_all_items().await, vec![]);
+///     assert_eq!(group.wait_for_all_ordered().await, vec![]);
+///     assert_eq!(group.wait_for_all_timed().await, vec![]);
+///     assert_eq!(group.wait_for_all_results().await, Ok(vec![]));
+///     assert_eq!(group.wait_for_all_partitioned().await, (vec![], vec![]));
+///     assert_eq!(group.wait_for_all_checked().await.unwrap(), vec![]);
+///     assert_eq!(group.wait_for_all_within(Duration::from_secs(10)).await, (vec![], true));
+///     assert_eq!(group.wait_for_all_until(Duration::from_secs(10)).await, vec![]);
+///     assert_eq!(group.wait_for_first(0).await, vec![]);
+///     assert_eq!(group.take_and_cancel(0).await, vec![]);
+///     assert_eq!(group.first().await, None);
+///     assert_eq!(group.first_and_cancel().await, None);
+///     assert_eq!(group.next_completed().await, None);
+///     assert_eq!(group.join(&[]).await, vec![]);
+///     assert!(group.wait_until_completed(0).await);
+///     assert!(!group.wait_until_completed(1).await);
+///     assert_eq!(group.try_drain(), vec![]);
+///     assert_eq!(group.next().await, None);
+///     assert_eq!(group.cancel_and_collect().await, vec![]);
+/// }).await;
+/// # });
+/// ```
 pub struct ErrSpawnGroup<ValueType: Send + 'static, ErrorType: Send + 'static> {
     /// A field that indicates if the spawn group had been cancelled
     pub is_cancelled: bool,
     count: Arc<AtomicUsize>,
     runtime: RuntimeEngine<Result<ValueType, ErrorType>>,
-    wait_at_drop: bool,
+    drop_policy: DropPolicy,
+}
+
+impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
+    pub(crate) fn new() -> Self {
+        Self::init()
+    }
+
+    pub(crate) fn from_parts(
+        runtime: RuntimeEngine<Result<ValueType, ErrorType>>,
+        drop_policy: DropPolicy,
+    ) -> Self {
+        Self {
+            count: Arc::new(AtomicUsize::new(0)),
+            is_cancelled: false,
+            runtime,
+            drop_policy,
+        }
+    }
 }
 
-impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
-    pub(crate) fn new() -> Self {
-        Self::init()
+impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
+    /// Don't implicity wait for spawned child tasks to finish before being dropped,
+    /// cancel them instead
+    ///
+    /// This is the same as setting the drop policy to ``DropPolicy::Cancel``
+    pub fn dont_wait_at_drop(&mut self) {
+        self.drop_policy = DropPolicy::Cancel;
+    }
+
+    /// Sets what happens to still running child tasks when the spawn group gets dropped.
+    ///
+    /// The default is ``DropPolicy::WaitAll``
+    ///
+    /// # Parameters
+    ///
+    /// * `drop_policy`: the drop policy to use
+    pub fn set_drop_policy(&mut self, drop_policy: DropPolicy) {
+        self.drop_policy = drop_policy;
+    }
+
+    /// Waits for the still running child tasks, or cancels or detaches them as the drop policy says,
+    /// without blocking the caller's thread, then consumes the spawn group.
+    ///
+    /// This is the recommended way to tear down a spawn group in asynchronous code,
+    /// since dropping it blocks the thread it gets dropped on, which only serves as a safety net.
+    /// Nothing is left for the drop to do once it returns.
+    pub async fn close(mut self) {
+        match self.drop_policy {
+            DropPolicy::WaitAll => self.runtime.wait_for_all_tasks_async().await,
+            DropPolicy::Cancel => self.cancel_and_wait().await,
+            DropPolicy::Detach => self.runtime.detach_to_background(),
+        }
+        // there is nothing left to wait for or cancel, so the drop only releases the child tasks' bookkeeping
+        self.drop_policy = DropPolicy::Detach;
+    }
+
+    /// Leaves the still running child tasks running in the background and consumes the spawn group
+    /// without blocking the caller's thread, whatever the drop policy says.
+    ///
+    /// Just like under ``DropPolicy::Detach``, the child tasks are handed over to a background thread
+    /// which keeps the spawn group's threads alive until they finish and discards their results as they come in,
+    /// so their results don't pile up when nobody consumes them.
+    pub fn detach(mut self) {
+        self.runtime.detach_to_background();
+        self.drop_policy = DropPolicy::Detach;
+    }
+
+    /// Limits how many of the child tasks spawned afterwards run at the same time,
+    /// the rest wait until a running child task finishes.
+    ///
+    /// Whenever a running child task finishes, the waiting child task of the highest priority starts next,
+    /// and among the ones of the same priority, the earliest spawned.
+    ///
+    /// Zero removes the limit, which is the default.
+    ///
+    /// # Parameters
+    ///
+    /// * `max_concurrency`: the maximum number of child tasks running at the same time
+    pub fn set_max_concurrency(&mut self, max_concurrency: usize) {
+        self.runtime.set_max_concurrency(max_concurrency);
+    }
+
+    /// Limits how many of the child tasks of each priority in ``limits``, spawned afterwards, run at the same time,
+    /// so child tasks of a saturated priority wait while those of the other priorities keep starting.
+    ///
+    /// Priorities missing from ``limits``, or mapped to zero, have no limit of their own.
+    /// Child tasks also wait for the limit set by ``set_max_concurrency``, but only once their priority's limit let them through,
+    /// so they never take a slot of it while waiting.
+    ///
+    /// # Parameters
+    ///
+    /// * `limits`: the maximum number of child tasks of each priority running at the same time
+    pub fn set_concurrency_limits(&mut self, limits: HashMap<Priority, usize>) {
+        self.runtime.set_concurrency_limits(limits);
+    }
+
+    /// Lets child tasks waiting for the concurrency limit set by ``set_max_concurrency`` age,
+    /// so their priority rises a level for every ``rate`` they waited, and child tasks of a low priority
+    /// eventually run even while child tasks of a higher priority keep getting spawned.
+    ///
+    /// Zero turns aging off, which is the default.
+    ///
+    /// # Parameters
+    ///
+    /// * `rate`: how long a child task waits for each level its priority rises
+    pub fn set_aging(&mut self, rate: Duration) {
+        self.runtime.set_aging(rate);
+    }
+
+    /// Sets how the child tasks waiting for the concurrency limit set by ``set_max_concurrency`` take turns,
+    /// such as ``SchedulingPolicy::WeightedFair`` to share the runs between their priorities rather than
+    /// always running the highest priority first, so child tasks of a low priority are never starved.
+    ///
+    /// The default is ``SchedulingPolicy::Strict``
+    ///
+    /// # Parameters
+    ///
+    /// * `scheduling_policy`: the scheduling policy to use
+    pub fn set_scheduling_policy(&mut self, scheduling_policy: SchedulingPolicy) {
+        self.runtime.set_scheduling_policy(scheduling_policy);
+    }
+
+    /// Waits for a slot of the concurrency limit set by ``set_max_concurrency``, for work outside of the spawn group
+    /// which should count against the same limit, and returns a guard which gives the slot back when dropped.
+    ///
+    /// The caller waits in line with the child tasks: behind the ones of a higher priority, and behind the ones of
+    /// the default priority spawned so far, while child tasks spawned afterwards wait behind it.
+    /// While the guard is held, one less child task runs at the same time, so holding it across waiting for the spawn group
+    /// with a limit of one never finishes.
+    ///
+    /// Without a concurrency limit, it returns right away with a guard which holds nothing.
+    /// Changing the limit afterwards doesn't affect guards already held.
+    pub async fn acquire_permit(&self) -> Permit {
+        self.runtime.acquire_permit(Priority::default()).await
+    }
+
+    /// Paces the starts of the child tasks spawned afterwards to at most ``per_second`` a second,
+    /// spreading a burst of them out evenly rather than starting them all at once.
+    ///
+    /// The rate limits how often child tasks start, while ``set_max_concurrency`` limits how many run at the same time.
+    /// With both, a child task first waits until it may run, then until its start is due.
+    ///
+    /// Zero removes the limit, which is the default.
+    ///
+    /// # Parameters
+    ///
+    /// * `per_second`: the highest number of child tasks starting a second
+    pub fn set_rate_limit(&mut self, per_second: u32) {
+        self.runtime.set_rate_limit(per_second);
+    }
+
+    /// Limits the number of finished child tasks' results waiting to be consumed,
+    /// so child tasks wait for the consumer before storing more results.
+    ///
+    /// Zero, the default, means no limit
+    ///
+    /// # Parameters
+    ///
+    /// * `max_buffered`: the highest number of results waiting to be consumed
+    pub fn set_max_buffered(&mut self, max_buffered: usize) {
+        self.runtime.set_max_buffered(max_buffered);
+    }
+
+    /// Allocates room for ``additional`` more child tasks and their results up front,
+    /// so spawning a large batch of child tasks doesn't keep reallocating while holding the spawn group's locks
+    ///
+    /// Room for the results is only reserved up to the limit set by ``set_max_buffered``,
+    /// and skipped rather than waited for while the results are being consumed at the same time.
+    ///
+    /// # Parameters
+    ///
+    /// * `additional`: the number of child tasks about to be spawned
+    pub fn reserve(&mut self, additional: usize) {
+        self.runtime.reserve(additional);
+    }
+
+    /// Fixes the order the results are yielded in to one which only depends on the seed
+    /// and the order the child tasks were spawned in, so tests can assert the exact output.
+    ///
+    /// Child tasks still run concurrently, but no result is yielded until every child task finished,
+    /// and the limit set by ``set_max_buffered`` no longer applies.
+    /// Only available with the ``deterministic`` feature, which is meant for tests.
+    ///
+    /// # Parameters
+    ///
+    /// * `seed`: the seed the order is derived from
+    #[cfg(feature = "deterministic")]
+    pub fn set_deterministic_seed(&mut self, seed: u64) {
+        self.runtime.set_drain_seed(seed);
+    }
+
+    /// Registers a closure called with the result of each child task as soon as it finishes,
+    /// before the result is stored in the spawn group.
+    ///
+    /// Closures are called on the threads driving the child tasks, in the order they were registered.
+    ///
+    /// # Parameters
+    ///
+    /// * `observer`: the closure to call
+    pub fn on_complete<F>(&mut self, observer: F)
+    where
+        F: Fn(&Result<ValueType, ErrorType>) + Send + Sync + 'static,
+    {
+        self.runtime.on_complete(observer);
+    }
+
+    /// Registers a closure called with the id of every child task right before it starts running,
+    /// which is after it waited for the concurrency limit and the rate limit, if any.
+    ///
+    /// Closures are called on the threads driving the child tasks, in the order they were registered.
+    ///
+    /// # Parameters
+    ///
+    /// * `observer`: the closure to call
+    pub fn on_start<F>(&mut self, observer: F)
+    where
+        F: Fn(TaskId) + Send + Sync + 'static,
+    {
+        self.runtime.on_start(observer);
+    }
+
+    /// Returns the cancellation token of the spawn group.
+    ///
+    /// The token is cancelled as soon as the spawn group is cancelled, so child tasks holding a clone of it
+    /// can observe the cancellation and clean up within the grace period set by ``set_cancellation_grace_period``.
+    /// Once cancelled, the token stays cancelled.
+    ///
+    /// When the spawn group was created while a child task of another spawn group runs, its token is a child token
+    /// of that spawn group's token, so it gets cancelled along with the enclosing spawn group.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.runtime.token()
+    }
+
+    /// Returns a handle which spawns child tasks into the spawn group through a shared reference,
+    /// so child tasks can capture a clone of it to spawn more child tasks while they run.
+    ///
+    /// Waiting for the spawn group also waits for the child tasks spawned through it.
+    pub fn spawner(&self) -> Spawner<Result<ValueType, ErrorType>> {
+        Spawner::new(self.runtime.clone(), Some(self.count.clone()))
+    }
+
+    /// Sets how long cancelling waits for child tasks to finish on their own after the cancellation token is cancelled,
+    /// before they get forcefully cancelled.
+    ///
+    /// The default is zero, which forcefully cancels child tasks right away.
+    ///
+    /// ``cancel_all`` and dropping the spawn group block the thread they're called on until the child tasks finished
+    /// or the grace period is over, while ``first_and_cancel``, ``wait_until``, ``take_and_cancel``, ``cancel_and_wait`` and ``close``
+    /// wait for it without blocking the caller's executor.
+    ///
+    /// # Parameters
+    ///
+    /// * `grace_period`: the longest time to wait for child tasks to finish on their own
+    pub fn set_cancellation_grace_period(&mut self, grace_period: Duration) {
+        self.runtime.set_grace_period(grace_period);
+    }
+
+    /// Registers a recorder which receives an event whenever a child task is spawned, returns, is cancelled or panics,
+    /// replacing the previous one.
+    ///
+    /// Only child tasks spawned afterwards report their events to it.
+    ///
+    /// # Parameters
+    ///
+    /// * `recorder`: the recorder to report to
+    pub fn set_metrics_recorder<R>(&mut self, recorder: R)
+    where
+        R: MetricsRecorder + 'static,
+    {
+        self.runtime.set_metrics_recorder(Arc::new(recorder));
+    }
+}
+
+impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
+    /// Spawns a new task into the spawn group
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    ///
+    /// # Returns
+    /// The id of the child task, which ``cancel_task`` takes to cancel it on its own
+    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F) -> TaskId
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        self.add_task(priority, closure)
+    }
+
+    /// Spawns a new task into the spawn group whose result can also be awaited on its own,
+    /// through the returned ``TaskResult``, while it still gets stored like any other child task's result
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    pub fn spawn_task_with_result<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+    ) -> TaskResult<Result<ValueType, ErrorType>>
+    where
+        F: Future<Output = Result<ValueType, ErrorType>> + Send + 'static,
+        ValueType: Clone + 'static,
+        ErrorType: Clone + 'static,
+    {
+        self.increment_count();
+        self.runtime.write_task_with_result(priority, closure)
+    }
+
+    /// Spawns a new task into the spawn group which drives the stream to its end,
+    /// storing every item it yields as a result on its own, so a single child task can contribute many results
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `stream`: a stream that yields values of type ``Result<ValueType, ErrorType>``
+    ///
+    /// # Returns
+    /// The id of the child task, which ``cancel_task`` takes to cancel it on its own
+    pub fn spawn_stream_task<St>(&mut self, priority: Priority, stream: St) -> TaskId
+    where
+        St: Stream<Item = Result<ValueType, ErrorType>> + Send + 'static,
+        ValueType: 'static,
+        ErrorType: 'static,
+    {
+        self.increment_count();
+        self.runtime.write_stream_task(priority, stream)
+    }
+
+    /// Spawns a new task into the spawn group which is retried whenever it returns an error,
+    /// and only stores the result of its last attempt
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `retries`: how many more times the child task may be retried after its first attempt
+    /// * `backoff`: how long to sleep before each retry, where zero means not sleeping at all
+    /// * `factory`: a closure that returns a new attempt of the async closure every time it's called
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_err_spawn_group, Priority};
+    /// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let attempts = Arc::new(AtomicUsize::new(0));
+    /// let counter = attempts.clone();
+    /// let results: Vec<Result<&str, &str>> = with_err_spawn_group(move |mut group| async move {
+    ///     group.spawn_retrying_task(Priority::default(), 3, Duration::from_millis(1), move || {
+    ///         let attempt = counter.fetch_add(1, Ordering::SeqCst);
+    ///         async move { if attempt < 2 { Err("flaky") } else { Ok("done") } }
+    ///     });
+    ///     group.wait_for_all_items().await
+    /// }).await;
+    ///
+    /// assert_eq!(results, vec![Ok("done")]);
+    /// assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    /// # });
+    /// ```
+    pub fn spawn_retrying_task<F, Fut>(
+        &mut self,
+        priority: Priority,
+        retries: usize,
+        backoff: Duration,
+        factory: F,
+    ) -> TaskId
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        self.increment_count();
+        self.runtime
+            .write_retrying_task(priority, retries, backoff, factory)
+    }
+
+    /// Spawns a new task into the spawn group which gets cancelled if it doesn't finish in time,
+    /// in which case it stores no result
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `timeout`: the longest time the child task may run for
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    pub fn spawn_task_with_timeout<F>(
+        &mut self,
+        priority: Priority,
+        timeout: Duration,
+        closure: F,
+    ) -> TaskId
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        self.increment_count();
+        self.runtime
+            .write_task_with_timeout(priority, timeout, closure)
+    }
+
+    /// Cancels all running task in the spawn group
+    pub fn cancel_all(&mut self) {
+        self.cancel_all_tasks();
+    }
+
+    /// Cancels all running task in the spawn group, and waits until every one of them actually stopped
+    ///
+    /// Unlike ``cancel_all``, which only requests the cancellation, a child task being polled on another thread
+    /// is done running once this returns.
+    pub async fn cancel_and_wait(&mut self) {
+        self.runtime.cancel_and_wait().await;
+        self.is_cancelled = true;
+        self.decrement_count_to_zero();
+    }
+
+    /// Cancels all running task in the spawn group, waits until every one of them actually stopped like ``cancel_and_wait`` does,
+    /// and returns every result stored before the abort, in the order they finished
+    ///
+    /// Child tasks that were cancelled right after returning still contribute their result,
+    /// while those aborted halfway, such as while waiting for room under ``set_max_buffered``, don't.
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks that returned, which is empty if none did
+    pub async fn cancel_and_collect(&mut self) -> Vec<Result<ValueType, ErrorType>> {
+        let results: Vec<Result<ValueType, ErrorType>> = self.runtime.cancel_and_collect().await;
+        self.is_cancelled = true;
+        self.decrement_count_to_zero();
+        results
+    }
+
+    /// Closes the spawn group gracefully, so it rejects every child task spawned afterwards
+    /// while the already spawned ones keep running until they finish.
+    ///
+    /// Unlike cancelling, nothing gets cancelled. ``spawn_task_unless_cancelled`` returns false for a rejected child task,
+    /// while the other spawn methods return an id which matches no child task.
+    /// Waiting for the spawn group afterwards waits for the already spawned child tasks only.
+    pub fn close_gracefully(&mut self) {
+        self.runtime.close();
+    }
+
+    /// Returns whether the spawn group was closed gracefully with ``close_gracefully``
+    pub fn is_closed(&self) -> bool {
+        self.runtime.is_closed()
+    }
+
+    /// Cancels a single child task, leaving the other child tasks running
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: the id the child task was spawned with
+    ///
+    /// # Returns
+    /// Whether the child task was cancelled, which is false if it already finished
+    pub fn cancel_task(&mut self, id: TaskId) -> bool {
+        let cancelled: bool = self.runtime.cancel_task(id);
+        if cancelled {
+            self.decrement_count();
+        }
+        cancelled
+    }
+
+    /// Cancels every child task of a priority strictly lower than ``priority``,
+    /// leaving the child tasks of the same or a higher priority running
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: the lowest priority of the child tasks to keep
+    ///
+    /// # Returns
+    /// The number of child tasks cancelled, which leaves out those that already finished
+    pub fn cancel_below(&mut self, priority: Priority) -> usize {
+        let cancelled: usize = self.runtime.cancel_below(priority);
+        for _ in 0..cancelled {
+            self.decrement_count();
+        }
+        cancelled
+    }
+
+    /// Spawns a new child task which runs a blocking closure, such as a CPU-bound computation
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: a closure that return a value of type ``Result<ValueType, ErrorType>``
+    pub fn spawn_blocking_task<F>(&mut self, priority: Priority, closure: F) -> TaskId
+    where
+        F: FnOnce() -> Result<ValueType, ErrorType> + Send + 'static,
+    {
+        self.increment_count();
+        self.runtime.write_blocking_task(priority, closure)
+    }
+
+    /// Spawns a new child task with a name that shows up in ``debug_snapshot()``
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `name`: the name of the child task
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    pub fn spawn_named_task<F>(&mut self, priority: Priority, name: &str, closure: F) -> TaskId
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        self.increment_count();
+        self.runtime.write_named_task(priority, name, closure)
+    }
+
+    /// Spawns a new child task which only starts once the child tasks of the given ids finished,
+    /// so child tasks can depend on each other the way build steps do
+    ///
+    /// A child task counts as finished once it returned, panicked or was cancelled,
+    /// and ids of child tasks that were already waited for, or that are unknown to the spawn group, count as finished too.
+    /// Nothing detects cycles, so child tasks that depend on each other never start.
+    ///
+    /// The child task waits for its prerequisites before acquiring its turn under ``set_max_concurrency``,
+    /// so it never holds one up while waiting.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `after`: the ids of the child tasks to wait for
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    pub fn spawn_task_after<F>(
+        &mut self,
+        priority: Priority,
+        after: &[TaskId],
+        closure: F,
+    ) -> TaskId
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        self.increment_count();
+        self.runtime.write_task_after(priority, after, closure)
+    }
+
+    /// Spawn a new task only if the group is neither cancelled nor closed yet,
+    /// otherwise does nothing
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    ///
+    /// # Returns
+    /// Whether the child task was spawned
+    pub fn spawn_task_unless_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        self.add_task_unlessed_cancelled(priority, closure)
+    }
+
+    /// Spawns a new task like ``spawn_task_unless_cancelled`` does, but tells why the child task wasn't spawned
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    ///
+    /// # Returns
+    /// - Ok: the id of the child task, which ``cancel_task`` takes to cancel it on its own
+    /// - Err: ``SpawnError::Cancelled`` if the spawn group was cancelled, ``SpawnError::Closed`` if it was closed,
+    ///   or ``SpawnError::Saturated`` if ``SaturationPolicy::Reject`` rejected the child task
+    pub fn try_spawn_task<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+    ) -> Result<TaskId, SpawnError>
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        let id: TaskId = self.runtime.try_write_task(priority, closure)?;
+        self.increment_count();
+        Ok(id)
+    }
+
+    /// Spawns a new task like ``try_spawn_task`` does, but while ``SaturationPolicy::Block`` holds it back,
+    /// it waits on the caller's executor instead of blocking its thread
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    ///
+    /// # Returns
+    /// The same as ``try_spawn_task`` does
+    pub async fn try_spawn_task_async<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+    ) -> Result<TaskId, SpawnError>
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        let id: TaskId = self.runtime.try_write_task_async(priority, closure).await?;
+        self.increment_count();
+        Ok(id)
+    }
+
+    /// Spawn a new task only if the group is neither cancelled nor closed yet,
+    /// otherwise does nothing
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
+    #[deprecated(since = "2.0.0", note = "Use spawn_task_unless_cancelled instead")]
+    pub fn spawn_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
+    where
+        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
+            + Send
+            + 'static,
+    {
+        self.spawn_task_unless_cancelled(priority, closure);
+    }
+}
+
+impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
+    /// Returns the first element of the stream, or None if it is empty.
+    pub async fn first(&self) -> Option<<ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result> {
+        self.runtime.stream().first().await
+    }
+
+    /// Waits for the earliest child task to finish, then cancels all the other child tasks.
+    ///
+    /// Results of other child tasks that finished before the cancellation stay in the stream.
+    ///
+    /// # Returns
+    /// The result of the earliest child task to finish, or None if no child task was spawned
+    pub async fn first_and_cancel(
+        &mut self,
+    ) -> Option<<ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result> {
+        let result: Option<Result<ValueType, ErrorType>> = self.runtime.stream().first().await;
+        self.cancel_all_tasks_async().await;
+        result
+    }
+
+    /// Waits for the earliest child task whose result matches the predicate, then cancels all the other child tasks.
+    ///
+    /// Results that don't match are dropped on the way, while results of other child tasks
+    /// that finished before the cancellation stay in the stream.
+    ///
+    /// # Parameters
+    ///
+    /// * `predicate`: a closure telling whether a result is the one waited for
+    ///
+    /// # Returns
+    /// The earliest matching result, or None if every child task finished without a match
+    pub async fn wait_until<F>(&mut self, predicate: F) -> Option<Result<ValueType, ErrorType>>
+    where
+        F: Fn(&Result<ValueType, ErrorType>) -> bool,
+    {
+        let result: Option<Result<ValueType, ErrorType>> =
+            self.runtime.stream().find(|item| predicate(item)).await;
+        self.cancel_all_tasks_async().await;
+        result
+    }
+
+    /// Waits for the earliest child tasks to finish, then cancels all the other child tasks.
+    ///
+    /// Waiting for more child tasks than are left returns the results of all of them.
+    /// Results of other child tasks that finished before the cancellation stay in the stream.
+    ///
+    /// # Parameters
+    ///
+    /// * `count`: the number of child tasks to wait for
+    ///
+    /// # Returns
+    /// A vector of at most `count` results of the child tasks in the order they finished
+    pub async fn take_and_cancel(&mut self, count: usize) -> Vec<Result<ValueType, ErrorType>> {
+        let results: Vec<Result<ValueType, ErrorType>> = self.runtime.wait_for_first(count).await;
+        self.cancel_all_tasks_async().await;
+        results
+    }
+
+    /// Waits for the next child task to finish without cancelling the other child tasks,
+    /// so results can be inspected one at a time before deciding whether to keep going
+    ///
+    /// # Returns
+    /// The result of the next child task to finish along with how many results are still to come,
+    /// or None once every child task finished and all their results were consumed
+    pub async fn next_completed(
+        &mut self,
+    ) -> Option<(
+        <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result,
+        usize,
+    )> {
+        let result: Result<ValueType, ErrorType> = self.runtime.stream().first().await?;
+        self.decrement_count();
+        Some((result, self.runtime.stream().item_count()))
+    }
+}
+
+impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
+    /// Returns an instance of the `Stream` trait.
+    pub fn stream(&self) -> impl Stream<Item = Result<ValueType, ErrorType>> {
+        self.runtime.stream()
+    }
+
+    /// Returns a stream which yields the results of the child tasks in batches of `size` results
+    /// in the order they finished, and the remaining results in a smaller batch once all child tasks finished.
+    ///
+    /// # Panics
+    /// If the `size` parameter is zero
+    ///
+    /// # Parameters
+    ///
+    /// * `size`: the number of results in each batch
+    pub fn chunks(&self, size: usize) -> impl Stream<Item = Vec<Result<ValueType, ErrorType>>> {
+        self.runtime.stream().chunks(size)
+    }
+}
+
+impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
+    /// Waits for all remaining child tasks for finish.
+    pub async fn wait_for_all(&mut self) {
+        self.wait().await;
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their results
+    /// in the order they finished.
+    ///
+    /// Results already popped off the spawn group's stream are not returned again.
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks, which is empty if no child task was spawned
+    pub async fn wait_for_all_items(&mut self) -> Vec<Result<ValueType, ErrorType>> {
+        let results: Vec<Result<ValueType, ErrorType>> = self.runtime.wait_for_all().await;
+        self.decrement_count_to_zero();
+        results
+    }
+
+    /// Blocks the current thread until all remaining child tasks finished, and returns their results
+    /// in the order they finished, for callers without any asynchronous code, such as a plain ``fn main()``
+    ///
+    /// The wait is driven by the crate itself while the spawn group's backend drives the child tasks,
+    /// so no executor has to be picked or imported.
+    ///
+    /// # Deadlocks
+    /// It must not be called from asynchronous code, such as a child task of a spawn group or a future driven by Tokio,
+    /// since it blocks the thread that code runs on, which the child tasks it waits for may need to make progress.
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks, which is empty if no child task was spawned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{spawn_group::SpawnGroupBuilder, Priority};
+    ///
+    /// let mut group = SpawnGroupBuilder::new().build_err::<u32, String>();
+    /// for i in 0..5 {
+    ///     group.spawn_task(Priority::default(), async move { if i == 3 { Err(format!("job {i} failed")) } else { Ok(i) } });
+    /// }
+    ///
+    /// let mut results = group.wait_for_all_blocking();
+    /// results.sort();
+    /// assert_eq!(results, vec![Ok(0), Ok(1), Ok(2), Ok(4), Err("job 3 failed".to_string())]);
+    /// ```
+    pub fn wait_for_all_blocking(&mut self) -> Vec<Result<ValueType, ErrorType>> {
+        crate::block_on(self.wait_for_all_items())
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their results in the order they finished,
+    /// along with whether they are all of the child tasks' results.
+    ///
+    /// Results already popped off the spawn group's stream are not returned again.
+    pub async fn wait_for_all_items_with_completion(
+        &mut self,
+    ) -> (Vec<Result<ValueType, ErrorType>>, Completion) {
+        let results: Vec<Result<ValueType, ErrorType>> = self.wait_for_all_items().await;
+        (results, self.runtime.completion())
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their results
+    /// in the order the child tasks were spawned.
+    ///
+    /// Results already popped off the spawn group's stream are not returned again.
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks, which is empty if no child task was spawned
+    pub async fn wait_for_all_ordered(&mut self) -> Vec<Result<ValueType, ErrorType>> {
+        let results: Vec<Result<ValueType, ErrorType>> = self.runtime.wait_for_all_ordered().await;
+        self.decrement_count_to_zero();
+        results
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their results in the order they finished,
+    /// each paired with how long its child task took from being spawned to returning.
+    ///
+    /// Results already popped off the spawn group's stream are not returned again,
+    /// and cancelled child tasks leave no result behind.
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks along with their latencies, which is empty if no child task was spawned
+    pub async fn wait_for_all_timed(&mut self) -> Vec<(Result<ValueType, ErrorType>, Duration)> {
+        let results: Vec<(Result<ValueType, ErrorType>, Duration)> =
+            self.runtime.wait_for_all_timed().await;
+        self.decrement_count_to_zero();
+        results
     }
-}
 
-impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
-    /// Don't implicity wait for spawned child tasks to finish before being dropped
-    pub fn dont_wait_at_drop(&mut self) {
-        self.wait_at_drop = false;
+    /// Waits for all remaining child tasks to finish within a timeout, then cancels the child tasks still running.
+    ///
+    /// Results are drained while waiting, so child tasks never wait on a full buffer,
+    /// and results stored before the cancellation are never lost.
+    ///
+    /// # Parameters
+    ///
+    /// * `timeout`: the longest time to wait for the child tasks
+    ///
+    /// # Returns
+    /// The results of the finished child tasks in the order they finished,
+    /// along with whether every child task finished before the timeout elapsed
+    pub async fn wait_for_all_within(
+        &mut self,
+        timeout: Duration,
+    ) -> (Vec<Result<ValueType, ErrorType>>, bool) {
+        let (mut results, finished) = self.runtime.drain_until(timeout).await;
+        if !finished {
+            self.cancel_all_tasks();
+        }
+        results.extend(self.wait_for_all_items().await);
+        (results, finished)
     }
-}
 
-impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
-    /// Spawns a new task into the spawn group
+    /// Waits for all remaining child tasks to finish within a timeout, then cancels the child tasks still running
+    /// and returns the results of the finished ones in the order they finished,
+    /// like ``wait_for_all_within`` does without telling whether every child task finished.
+    ///
+    /// Results already popped off the spawn group's stream are not returned again.
     ///
     /// # Parameters
     ///
-    /// * `priority`: priority to use
-    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
-    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F)
+    /// * `timeout`: the longest time to wait for the child tasks
+    pub async fn wait_for_all_until(
+        &mut self,
+        timeout: Duration,
+    ) -> Vec<Result<ValueType, ErrorType>> {
+        self.wait_for_all_within(timeout).await.0
+    }
+
+    /// Waits for the earliest child tasks to finish and returns their results
+    /// in the order they finished, leaving the other child tasks running.
+    ///
+    /// Waiting for more child tasks than are left returns the results of all of them.
+    ///
+    /// # Parameters
+    ///
+    /// * `count`: the number of child tasks to wait for
+    ///
+    /// # Returns
+    /// A vector of at most `count` results of the child tasks
+    pub async fn wait_for_first(&mut self, count: usize) -> Vec<Result<ValueType, ErrorType>> {
+        self.runtime.wait_for_first(count).await
+    }
+
+    /// Waits for the child tasks behind ``ids`` to finish and returns their results in the order of ``ids``,
+    /// leaving the other child tasks running and their results in the spawn group.
+    ///
+    /// Ids of child tasks that were cancelled or whose result was already consumed are skipped,
+    /// while the result of a child task that finished earlier is still returned.
+    ///
+    /// # Parameters
+    ///
+    /// * `ids`: the ids the child tasks were spawned with
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks, at most one for each id unless it was spawned with ``spawn_stream_task``
+    pub async fn join(&mut self, ids: &[TaskId]) -> Vec<Result<ValueType, ErrorType>> {
+        self.runtime.join(ids).await
+    }
+
+    /// Waits for the child task behind ``id`` to finish and returns its result,
+    /// leaving the other child tasks running and their results in the spawn group
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: the id the child task was spawned with
+    ///
+    /// # Returns
+    /// The result of the child task, or ``None`` if it was cancelled, panicked or its result was already consumed
+    pub async fn await_task(&mut self, id: TaskId) -> Option<Result<ValueType, ErrorType>> {
+        self.runtime.join(&[id]).await.into_iter().next()
+    }
+
+    /// Returns where the child task behind ``id`` is in its life, without waiting for it
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: the id the child task was spawned with
+    ///
+    /// # Returns
+    /// The status of the child task, or ``None`` if the id wasn't issued by this spawn group,
+    /// or if the child task already stopped when the spawn group was last waited for, which forgets the statuses of stopped child tasks
+    pub fn task_status(&self, id: TaskId) -> Option<TaskStatus> {
+        self.runtime.task_status(id)
+    }
+
+    /// Pops the results of the child tasks that already finished, without waiting for the running ones
+    ///
+    /// # Returns
+    /// A vector of the results in the order their child tasks finished, which is empty if none finished yet
+    pub fn try_drain(&mut self) -> Vec<Result<ValueType, ErrorType>> {
+        self.runtime.try_drain()
+    }
+
+    /// Folds the results of the child tasks into an accumulator as they finish,
+    /// until all remaining child tasks are finished.
+    ///
+    /// Results already popped off the spawn group's stream are not folded again.
+    ///
+    /// # Parameters
+    ///
+    /// * `init`: the initial value of the accumulator
+    /// * `f`: the closure combining the accumulator with each result
+    ///
+    /// # Returns
+    /// The final accumulator, which is `init` if no child task was spawned
+    pub async fn fold_items<Acc, F>(&mut self, init: Acc, f: F) -> Acc
     where
-        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
-            + Send
-            + 'static,
+        F: FnMut(Acc, Result<ValueType, ErrorType>) -> Acc,
     {
-        self.add_task(priority, closure);
+        let acc: Acc = self.runtime.fold(init, f).await;
+        self.decrement_count_to_zero();
+        acc
     }
 
-    /// Cancels all running task in the spawn group
-    pub fn cancel_all(&mut self) {
-        self.cancel_all_tasks();
+    /// Waits for all remaining child tasks to finish and gathers their results into any collection
+    /// implementing ``Default`` and ``Extend``, consuming the spawn group.
+    ///
+    /// Results are added in the order they finished, so when collecting into a map from child tasks
+    /// returning the same key, the result that finished last wins, as ``Extend`` does.
+    pub async fn collect_into<C>(mut self) -> C
+    where
+        C: Default + Extend<Result<ValueType, ErrorType>>,
+    {
+        self.fold_items(C::default(), |mut collection, item| {
+            collection.extend(Some(item));
+            collection
+        })
+        .await
     }
 
-    /// Spawn a new task only if the group is not cancelled yet,
-    /// otherwise does nothing
+    /// Blocks the current thread until all remaining child tasks finished, and collects their results
+    /// into any collection that can be extended with them, like ``collect_into`` does for asynchronous callers
+    ///
+    /// # Deadlocks
+    /// It must not be called from asynchronous code, for the same reason as ``wait_for_all_blocking``.
+    pub fn collect_blocking<C>(self) -> C
+    where
+        C: Default + Extend<Result<ValueType, ErrorType>>,
+    {
+        crate::block_on(self.collect_into())
+    }
+
+    /// Forwards the results of the child tasks into a sink as they finish, such as a channel or a framed writer,
+    /// rather than collecting them into a vector first, then flushes the sink once every child task finished.
+    ///
+    /// The next result is only pulled once the sink is ready for it, so a slow sink leaves the results waiting in the spawn group.
+    /// With ``set_max_buffered`` and ``set_max_concurrency`` that backpressure reaches the child tasks themselves,
+    /// since child tasks waiting for room for their result keep holding their turn to run.
+    ///
+    /// Only available with the ``sink`` feature.
     ///
     /// # Parameters
     ///
-    /// * `priority`: priority to use
-    /// * `closure`: an async closure that return a value of type ``Result<ValueType, ErrorType>``
-    pub fn spawn_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
+    /// * `sink`: the sink receiving the results
+    ///
+    /// # Returns
+    /// - Ok: once every result was sent and the sink was flushed
+    /// - Err: the error of the sink, which stops the forwarding, leaving the remaining child tasks to the drop policy
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_err_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let mut received: Vec<Result<u64, String>> = with_err_spawn_group(|mut group| async move {
+    ///     for i in 0..3 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             if i == 1 {
+    ///                 return Err(format!("shard {i} failed"));
+    ///             }
+    ///             Ok(i)
+    ///         });
+    ///     }
+    ///     let mut received = vec![];
+    ///     group.drain_into(&mut received).await.unwrap();
+    ///     received
+    /// }).await;
+    ///
+    /// received.sort();
+    /// assert_eq!(received, vec![Ok(0), Ok(2), Err("shard 1 failed".to_string())]);
+    /// # });
+    /// ```
+    #[cfg(feature = "sink")]
+    pub async fn drain_into<Si>(self, sink: Si) -> Result<(), Si::Error>
     where
-        F: Future<Output = <ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>
-            + Send
-            + 'static,
+        Si: futures_sink::Sink<Result<ValueType, ErrorType>> + Unpin,
     {
-        self.add_task_unlessed_cancelled(priority, closure);
+        self.runtime.drain_into(sink).await
     }
-}
 
-impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
-    /// Returns the first element of the stream, or None if it is empty.
-    pub async fn first(&self) -> Option<<ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result> {
-        self.runtime.stream().first().await
+    /// Sends the results of the child tasks down a channel as they finish, such as one feeding a downstream consumer task,
+    /// then drops the sender once every child task finished, so the receiver ends unless other senders are left.
+    ///
+    /// The next result is only sent once the channel has room for it, so a slow receiver leaves the results waiting in the spawn group.
+    /// Once the receiver was dropped, which is also noticed while waiting for the next result, every remaining child task gets cancelled.
+    ///
+    /// Only available with the ``channel`` feature.
+    ///
+    /// # Parameters
+    ///
+    /// * `tx`: the sender of the channel receiving the results
+    ///
+    /// # Returns
+    /// - true: once every result was sent
+    /// - false: if the receiver was dropped first, which cancelled the spawn group
+    #[cfg(feature = "channel")]
+    pub async fn forward_to(
+        mut self,
+        tx: futures_channel::mpsc::Sender<Result<ValueType, ErrorType>>,
+    ) -> bool {
+        let forwarded: bool = self.runtime.forward_to(tx).await;
+        if !forwarded {
+            self.cancel_all_tasks();
+        }
+        forwarded
     }
-}
 
-impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
-    /// Returns an instance of the `Stream` trait.
-    pub fn stream(&self) -> impl Stream<Item = Result<ValueType, ErrorType>> {
-        self.runtime.stream()
+    /// Waits for all remaining child tasks to finish and collects their values,
+    /// unless a child task fails, then all the other child tasks are cancelled.
+    ///
+    /// # Returns
+    /// - Ok: the values of all the child tasks in the order they finished
+    /// - Err: the error of the earliest child task to fail
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_err_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let result = with_err_spawn_group(|mut group| async move {
+    ///     for i in 0..5 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             if i == 2 {
+    ///                 return Err(format!("child task {i} failed"));
+    ///             }
+    ///             // the other child tasks never finish unless cancelled
+    ///             std::future::pending::<Result<i32, String>>().await
+    ///         });
+    ///     }
+    ///     group.wait_for_all_results().await
+    /// }).await;
+    ///
+    /// assert_eq!(result, Err("child task 2 failed".to_string()));
+    /// # });
+    /// ```
+    pub async fn wait_for_all_results(&mut self) -> Result<Vec<ValueType>, ErrorType> {
+        let mut stream = self.runtime.stream();
+        let mut values: Vec<ValueType> = vec![];
+        while let Some(result) = stream.next().await {
+            match result {
+                Ok(value) => values.push(value),
+                Err(error) => {
+                    self.cancel_all_tasks();
+                    return Err(error);
+                }
+            }
+        }
+        self.wait().await;
+        Ok(values)
+    }
+
+    /// Waits for all remaining child tasks to finish and splits their results into the values and the errors,
+    /// each in the order they finished.
+    ///
+    /// Unlike ``wait_for_all_results``, an error cancels nothing, so every child task gets to report its outcome.
+    ///
+    /// Results already popped off the spawn group's stream are not returned again.
+    ///
+    /// # Returns
+    /// The values of the child tasks that succeeded and the errors of the child tasks that failed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_err_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let (mut values, mut errors) = with_err_spawn_group(|mut group| async move {
+    ///     for i in 0..5 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             match i % 2 {
+    ///                 0 => Ok(i),
+    ///                 _ => Err(format!("child task {i} failed")),
+    ///             }
+    ///         });
+    ///     }
+    ///     group.wait_for_all_partitioned().await
+    /// }).await;
+    ///
+    /// values.sort();
+    /// errors.sort();
+    /// assert_eq!(values, vec![0, 2, 4]);
+    /// assert_eq!(errors, vec!["child task 1 failed", "child task 3 failed"]);
+    /// # });
+    /// ```
+    pub async fn wait_for_all_partitioned(&mut self) -> (Vec<ValueType>, Vec<ErrorType>) {
+        let mut values: Vec<ValueType> = vec![];
+        let mut errors: Vec<ErrorType> = vec![];
+        for result in self.wait_for_all_items().await {
+            match result {
+                Ok(value) => values.push(value),
+                Err(error) => errors.push(error),
+            }
+        }
+        (values, errors)
     }
-}
 
+    /// Waits for all remaining child tasks to finish and collects their results,
+    /// unless a child task panics, then all the other child tasks are cancelled.
+    ///
+    /// Unlike the panic policy, which either swallows panics or resumes them, this surfaces the panic as an error.
+    ///
+    /// # Returns
+    /// - Ok: the results of all the child tasks in the order they finished
+    /// - Err: ``SpawnError::Panicked`` with the payload of the earliest child task to panic,
+    ///   or ``SpawnError::Cancelled`` if the spawn group was cancelled
+    pub async fn wait_for_all_checked(
+        &mut self,
+    ) -> Result<Vec<<ErrSpawnGroup<ValueType, ErrorType> as Shared>::Result>, SpawnError> {
+        let results: Result<Vec<Result<ValueType, ErrorType>>, SpawnError> =
+            self.runtime.wait_for_all_checked().await;
+        self.decrement_count_to_zero();
+        results
+    }
+}
 
-impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
-    /// Waits for all remaining child tasks for finish.
-    pub async fn wait_for_all(&mut self) {
-        self.wait().await;
+impl<ValueType: Send + Clone, ErrorType: Send + Clone> ErrSpawnGroup<ValueType, ErrorType> {
+    /// Copies the results of the child tasks that already finished but weren't consumed yet,
+    /// leaving them in the spawn group, unlike ``try_drain`` which pops them
+    ///
+    /// Only available when the results can be cloned.
+    ///
+    /// # Returns
+    /// A vector of copies of the results in the order their child tasks finished, which is empty if none is waiting
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_err_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_err_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async { Err::<u8, _>("timed out") });
+    ///     while group.results_snapshot().is_empty() {
+    ///         sleep(Duration::from_millis(1)).await;
+    ///     }
+    ///     // peeking leaves the results for whoever consumes the spawn group
+    ///     assert_eq!(group.results_snapshot(), vec![Err("timed out")]);
+    ///     assert_eq!(group.try_drain(), vec![Err("timed out")]);
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn results_snapshot(&self) -> Vec<Result<ValueType, ErrorType>> {
+        self.runtime.results_snapshot()
     }
 }
 
@@ -116,9 +1280,24 @@ impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
         self.count.load(Ordering::Acquire)
     }
 
+    fn decrement_count(&self) {
+        _ = self
+            .count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                count.checked_sub(1)
+            });
+    }
+
     fn decrement_count_to_zero(&self) {
         self.count.store(0, Ordering::Release);
     }
+
+    /// Cancels all running child tasks like ``cancel_all`` does, but waits for the grace period on the caller's executor
+    async fn cancel_all_tasks_async(&mut self) {
+        self.runtime.cancel_async().await;
+        self.is_cancelled = true;
+        self.decrement_count_to_zero();
+    }
 }
 
 impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
@@ -136,6 +1315,170 @@ impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
         }
         false
     }
+
+    /// Returns whether every child task finished and all their results were consumed, without blocking
+    pub fn is_finished(&self) -> bool {
+        self.runtime.is_finished()
+    }
+
+    /// Returns the number of child tasks still running, without blocking.
+    ///
+    /// It reaches zero exactly when waiting for all child tasks would return
+    pub fn pending_count(&self) -> usize {
+        self.runtime.stream().task_count()
+    }
+
+    /// Returns the number of child tasks that finished so far, without blocking.
+    ///
+    /// Child tasks that were cancelled are not counted
+    pub fn completed_count(&self) -> usize {
+        self.runtime.stream().completed_count()
+    }
+
+    /// Returns a stream of the spawn group's progress as the number of completed child tasks
+    /// along with the number of child tasks spawned so far, yielded whenever a child task completes.
+    ///
+    /// It doesn't consume any result, so it can be polled alongside the results,
+    /// and each of its clones yields every progress update on its own.
+    /// It ends once no child task is running anymore. Cancelled child tasks never count as completed.
+    pub fn progress(&self) -> impl Stream<Item = (usize, usize)> + Clone {
+        self.runtime.stream().progress()
+    }
+
+    /// Waits until at least ``count`` child tasks completed, without consuming any of their results,
+    /// so they all stay in the stream for later.
+    ///
+    /// Returns right away when that many child tasks already completed, including when ``count`` is zero.
+    ///
+    /// # Parameters
+    ///
+    /// * `count`: the number of completed child tasks to wait for
+    ///
+    /// # Returns
+    /// Whether that many child tasks completed, which is false if no child task was left running before that,
+    /// because too few were spawned or the rest were cancelled
+    pub async fn wait_until_completed(&self, count: usize) -> bool {
+        self.runtime.wait_until_completed(count).await
+    }
+
+    /// Sets what happens to the child tasks spawned while the threads of the spawn group are saturated,
+    /// which is while child tasks are already waiting for one of them.
+    ///
+    /// ``SaturationPolicy::Reject`` rejects them the way ``close_gracefully`` does, applying backpressure to whoever spawns them,
+    /// while ``SaturationPolicy::Block`` blocks the spawning thread until the waiting child tasks got a thread,
+    /// so it must not be used to spawn from inside the spawn group's own child tasks.
+    ///
+    /// The default is ``SaturationPolicy::Queue``
+    ///
+    /// # Parameters
+    ///
+    /// * `saturation_policy`: the saturation policy to use
+    pub fn set_saturation_policy(&mut self, saturation_policy: SaturationPolicy) {
+        self.runtime.set_saturation_policy(saturation_policy);
+    }
+
+    /// Sets the order in which the child tasks still running get cancelled when the spawn group is cancelled.
+    ///
+    /// ``cancel_and_wait`` only cancels each child task once the one before it stopped,
+    /// so their futures are dropped in that order, while ``cancel_all`` and dropping the spawn group
+    /// only tell the backend to stop them in that order, which may drop their futures in another one.
+    ///
+    /// The default is ``CancelOrder::Spawned``
+    ///
+    /// # Parameters
+    ///
+    /// * `cancel_order`: the cancel order to use
+    pub fn set_cancel_order(&mut self, cancel_order: CancelOrder) {
+        self.runtime.set_cancel_order(cancel_order);
+    }
+
+    /// Sets what happens when a child task panics.
+    ///
+    /// The default is ``PanicPolicy::Isolate``
+    ///
+    /// # Parameters
+    ///
+    /// * `panic_policy`: the panic policy to use
+    pub fn set_panic_policy(&mut self, panic_policy: PanicPolicy) {
+        self.runtime.set_panic_policy(panic_policy);
+    }
+
+    /// Returns the number of child tasks that panicked so far
+    pub fn panicked_count(&self) -> usize {
+        self.runtime.panicked_count()
+    }
+
+    /// Returns whether the spawn group was cancelled, either explicitly or by a method that cancels the remaining child tasks
+    pub fn was_cancelled(&self) -> bool {
+        self.runtime.was_cancelled()
+    }
+
+    /// Describes the child tasks that have not finished yet, which helps finding out why a spawn group hangs
+    ///
+    /// They are listed in the order the spawn group waits for them.
+    pub fn debug_snapshot(&self) -> Vec<TaskInfo> {
+        self.runtime.debug_snapshot()
+    }
+
+    /// The number of threads of the spawn group running a child task right now
+    ///
+    /// Along with ``pool_queued()``, this helps tuning the thread count and the maximum concurrency.
+    /// With the ``tokio`` feature, every child task of the spawn group that hasn't finished counts as running.
+    pub fn pool_active(&self) -> usize {
+        self.runtime.pool_active()
+    }
+
+    /// The number of child tasks of the spawn group waiting for a thread to run them
+    ///
+    /// With the ``tokio`` feature this is unavailable and always zero,
+    /// since Tokio doesn't tell which of its queued tasks belong to the spawn group.
+    pub fn pool_queued(&self) -> usize {
+        self.runtime.pool_queued()
+    }
+
+    /// Reports how deep the queues of the spawn group are to a callback at a fixed interval,
+    /// such as to feed a time series of its load into a metrics backend
+    ///
+    /// The callback runs on a background thread of its own, rather than on the spawn group's threads,
+    /// first right away and then once every interval, until the spawn group is dropped or closed,
+    /// which waits for a call in progress to return.
+    ///
+    /// # Parameters
+    ///
+    /// * `interval`: how long to wait between two snapshots
+    /// * `report`: the callback receiving every snapshot
+    pub fn sample_queue_depth<F>(&self, interval: Duration, report: F)
+    where
+        F: Fn(QueueSnapshot) + Send + 'static,
+    {
+        self.runtime.sample_queue_depth(interval, report);
+    }
+
+    /// Returns how long the child tasks that returned so far took from being spawned to returning,
+    /// as their count along with their mean, maximum and 99th percentile latency.
+    ///
+    /// Only available with the ``stats`` feature.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> TaskStats {
+        self.runtime.stats()
+    }
+
+    /// Grows or shrinks the threads of the spawn group to ``thread_count``, clamping zero to a single thread.
+    ///
+    /// It is safe to call while child tasks are running: surplus threads exit once they got through the work
+    /// already queued up for them, and the work queued afterwards runs on the resized threads.
+    /// Together with ``pool_queued()``, this lets a long-lived spawn group scale its threads with its load.
+    /// With the ``tokio`` feature, the runtime can't be resized, so this does nothing.
+    ///
+    /// # Parameters
+    ///
+    /// * `thread_count`: the number of threads to drive the child tasks on
+    ///
+    /// # Returns
+    /// Whether the threads were resized, which is never the case with the ``tokio`` feature
+    pub fn set_thread_count(&mut self, thread_count: usize) -> bool {
+        self.runtime.set_thread_count(thread_count)
+    }
 }
 
 impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
@@ -183,24 +1526,28 @@ impl<ValueType: Send, ErrorType: Send> ErrSpawnGroup<ValueType, ErrorType> {
     }
 }
 
+impl<ValueType: Send, ErrorType: Send + 'static> ErrSpawnGroup<ValueType, ErrorType> {
+    pub(crate) fn scope(&self) -> Scope<Result<ValueType, ErrorType>> {
+        Scope::new(self.runtime.clone())
+    }
+}
+
 impl<ValueType: Send, ErrorType: Send + 'static> Drop for ErrSpawnGroup<ValueType, ErrorType> {
     fn drop(&mut self) {
-        if self.wait_at_drop {
-            self.runtime.wait_for_all_tasks();
-        } else {
-            self.runtime.end()
+        self.runtime.stop_sampling();
+        match self.drop_policy {
+            // blocking while unwinding could hang on child tasks the panic left waiting
+            DropPolicy::WaitAll if std::thread::panicking() => self.runtime.cancel(),
+            DropPolicy::WaitAll => self.runtime.wait_for_all_tasks(),
+            DropPolicy::Cancel => self.runtime.cancel(),
+            DropPolicy::Detach => self.runtime.detach_to_background(),
         }
     }
 }
 
 impl<ValueType: Send, ErrorType: Send> Initializible for ErrSpawnGroup<ValueType, ErrorType> {
     fn init() -> Self {
-        ErrSpawnGroup::<ValueType, ErrorType> {
-            count: Arc::new(AtomicUsize::new(0)),
-            is_cancelled: false,
-            runtime: RuntimeEngine::init(),
-            wait_at_drop: true,
-        }
+        ErrSpawnGroup::from_parts(RuntimeEngine::init(), DropPolicy::default())
     }
 }
 
@@ -209,12 +1556,12 @@ impl<ValueType: Send + 'static, ErrorType: Send + 'static> Shared
 {
     type Result = Result<ValueType, ErrorType>;
 
-    fn add_task<F>(&mut self, priority: Priority, closure: F)
+    fn add_task<F>(&mut self, priority: Priority, closure: F) -> TaskId
     where
         F: Future<Output = Self::Result> + Send + 'static,
     {
         self.increment_count();
-        self.runtime.write_task(priority, closure);
+        self.runtime.write_task(priority, closure)
     }
 
     fn cancel_all_tasks(&mut self) {
@@ -223,12 +1570,54 @@ impl<ValueType: Send + 'static, ErrorType: Send + 'static> Shared
         self.decrement_count_to_zero();
     }
 
-    fn add_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
+    fn add_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
     where
         F: Future<Output = Self::Result> + Send + 'static,
     {
-        if !self.is_cancelled {
-            self.add_task(priority, closure)
+        if !self.runtime.write_task_unless_cancelled(priority, closure) {
+            return false;
+        }
+        self.increment_count();
+        true
+    }
+}
+
+/// Blocks the current thread until all remaining child tasks finish,
+/// then iterates over their results in the order they finished
+impl<ValueType: Send + 'static, ErrorType: Send + 'static> IntoIterator
+    for ErrSpawnGroup<ValueType, ErrorType>
+{
+    type Item = Result<ValueType, ErrorType>;
+    type IntoIter = std::vec::IntoIter<Result<ValueType, ErrorType>>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        crate::block_on(self.wait_for_all_items()).into_iter()
+    }
+}
+
+/// Creates a spawn group like ``SpawnGroupBuilder::new().build_err()`` does,
+/// with a child task spawned for each future at ``Priority::default()``
+impl<ValueType: Send + 'static, ErrorType: Send + 'static, F> FromIterator<F>
+    for ErrSpawnGroup<ValueType, ErrorType>
+where
+    F: Future<Output = Result<ValueType, ErrorType>> + Send + 'static,
+{
+    fn from_iter<I: IntoIterator<Item = F>>(futures: I) -> Self {
+        let mut group: ErrSpawnGroup<ValueType, ErrorType> = ErrSpawnGroup::init();
+        group.extend(futures);
+        group
+    }
+}
+
+/// Spawns a child task for each future at ``Priority::default()``
+impl<ValueType: Send + 'static, ErrorType: Send + 'static, F> Extend<F>
+    for ErrSpawnGroup<ValueType, ErrorType>
+where
+    F: Future<Output = Result<ValueType, ErrorType>> + Send + 'static,
+{
+    fn extend<I: IntoIterator<Item = F>>(&mut self, futures: I) {
+        for future in futures {
+            self.add_task(Priority::default(), future);
         }
     }
 }
@@ -246,7 +1635,7 @@ impl<ValueType: Send + 'static, ErrorType: Send + 'static> Waitable
     for ErrSpawnGroup<ValueType, ErrorType>
 {
     async fn wait(&self) {
-        self.runtime.wait_for_all_tasks();
+        self.runtime.wait_for_all_tasks_async().await;
         self.decrement_count_to_zero();
     }
 }
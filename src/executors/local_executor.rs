@@ -2,6 +2,7 @@ use std::{
     future::Future,
     sync::Arc,
     task::{Context, Waker},
+    time::Instant,
 };
 
 use cooked_waker::IntoWaker;
@@ -26,8 +27,27 @@ pub(crate) fn block_future<Fut: Future>(
     loop {
         match future.as_mut().poll(&mut context) {
             std::task::Poll::Ready(output) => return output,
+            std::task::Poll::Pending => notifier.wait(),
+        }
+    }
+}
+
+/// Blocks like ``block_future`` does, but gives up on the future once ``deadline`` passed
+pub(crate) fn block_future_until<Fut: Future>(
+    future: Fut,
+    notifier: Arc<Notifier>,
+    waker: &Waker,
+    deadline: Instant,
+) -> Option<Fut::Output> {
+    let mut context: Context<'_> = Context::from_waker(waker);
+    pin_future!(future);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            std::task::Poll::Ready(output) => return Some(output),
             std::task::Poll::Pending => {
-                notifier.wait()
+                if !notifier.wait_until(deadline) {
+                    return None;
+                }
             }
         }
     }
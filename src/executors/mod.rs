@@ -1,13 +1,25 @@
-use std::{future::Future, sync::Arc, task::Waker};
+use std::{future::Future, sync::Arc, task::Waker, time::Instant};
 
 use cooked_waker::IntoWaker;
 
-use crate::async_runtime::{notifier::Notifier, task::Task};
+use crate::async_runtime::notifier::Notifier;
 
-use self::{local_executor::block_future, task_executor::block_on_task};
+use self::local_executor::{block_future, block_future_until};
 
 mod local_executor;
-mod task_executor;
+mod spawner;
+#[cfg(feature = "tokio")]
+mod tokio_spawner;
+
+pub(crate) use spawner::Spawner;
+
+/// The backend spawn groups use to drive their child tasks
+#[cfg(not(feature = "tokio"))]
+pub(crate) type DefaultSpawner = crate::async_runtime::executor::Executor;
+
+/// The backend spawn groups use to drive their child tasks
+#[cfg(feature = "tokio")]
+pub(crate) type DefaultSpawner = tokio_spawner::TokioSpawner;
 
 /// Blocks the current thread until the future is polled to finish.
 ///
@@ -34,16 +46,10 @@ pub fn block_on<Fut: Future>(future: Fut) -> Fut::Output {
     }
 }
 
-pub(crate) fn block_task(task: Task) {
-    let waker_pair: Result<(Arc<Notifier>, Waker), std::thread::AccessError> =
-        local_executor::WAKER_PAIR
-            .try_with(|waker_pair: &(Arc<Notifier>, Waker)| waker_pair.clone());
-    match waker_pair {
-        Ok((notifier, waker)) => block_on_task(task, notifier, &waker),
-        Err(_) => {
-            let notifier: Arc<Notifier> = Arc::new(Notifier::default());
-            let waker: Waker = notifier.clone().into_waker();
-            block_on_task(task, notifier, &waker)
-        }
-    }
+/// Blocks the current thread like ``block_on`` does, but only until ``deadline``,
+/// and returns ``None`` if the future didn't finish before that
+pub(crate) fn block_on_until<Fut: Future>(future: Fut, deadline: Instant) -> Option<Fut::Output> {
+    let notifier: Arc<Notifier> = Arc::new(Notifier::default());
+    let waker: Waker = notifier.clone().into_waker();
+    block_future_until(future, notifier, &waker, deadline)
 }
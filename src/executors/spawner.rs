@@ -0,0 +1,128 @@
+use std::{
+    future::Future,
+    task::{Context, Poll},
+};
+
+#[cfg(not(feature = "tokio"))]
+use crate::async_runtime::executor::Executor;
+
+/// The backend that drives the child tasks spawned into a spawn group
+///
+/// Clones of a backend must drive child tasks on the same threads as the original
+pub(crate) trait Spawner: Clone + Default + Send + Sync + 'static {
+    /// A handle to a spawned child task
+    type JoinHandle: Send + 'static;
+
+    /// Creates a backend that drives child tasks on ``thread_count`` threads,
+    /// where zero means as many threads as the available parallelism
+    ///
+    /// The threads are named after ``thread_name`` when given, otherwise the backend names them
+    fn with_threads(thread_count: usize, thread_name: Option<&str>) -> Self;
+
+    /// Spawns a new child task onto the backend
+    fn spawn<Fut>(&self, task: Fut) -> Self::JoinHandle
+    where
+        Fut: Future<Output = ()> + Send + 'static;
+
+    /// Spawns a new child task which blocks the thread it runs on,
+    /// straight onto the threads driving the child tasks unless the backend has threads set aside for blocking
+    fn spawn_blocking<Fut>(&self, task: Fut) -> Self::JoinHandle
+    where
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn(task)
+    }
+
+    /// Blocks the current thread until every child task behind the handles is finished
+    fn block_on(&self, handles: Vec<Self::JoinHandle>);
+
+    /// Cancels every child task behind the handles
+    fn cancel(&self, handles: Vec<Self::JoinHandle>);
+
+    /// Cancels the child task behind the handle, leaving the other child tasks running
+    fn abort(&self, handle: &Self::JoinHandle);
+
+    /// Gets the backend ready to drive new child tasks after a wait or a cancellation
+    fn restart(&self) {}
+
+    /// Blocks the current thread until all the work already submitted to the backend is done
+    fn poll(&self) {}
+
+    /// Resizes the backend to drive the child tasks on ``thread_count`` threads, which is at least one,
+    /// and returns false if the backend can't be resized while it runs
+    fn set_thread_count(&self, _thread_count: usize) -> bool {
+        false
+    }
+
+    /// The number of child tasks the backend's threads are running right now
+    fn active_count(&self) -> usize;
+
+    /// The number of child tasks waiting for one of the backend's threads
+    fn queued_count(&self) -> usize;
+
+    /// Returns ``Poll::Ready`` once no child task waits for one of the backend's threads,
+    /// otherwise the waker of ``cx`` is woken once one of the backend's threads took a waiting child task
+    fn poll_capacity(&self, cx: &mut Context<'_>) -> Poll<()>;
+}
+
+#[cfg(not(feature = "tokio"))]
+impl Spawner for Executor {
+    type JoinHandle = crate::async_runtime::task::Task;
+
+    fn with_threads(thread_count: usize, thread_name: Option<&str>) -> Self {
+        Executor::new(thread_count, thread_name)
+    }
+
+    fn spawn<Fut>(&self, task: Fut) -> Self::JoinHandle
+    where
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Executor::spawn(self, task)
+    }
+
+    fn block_on(&self, handles: Vec<Self::JoinHandle>) {
+        // the run loop keeps driving every child task while this thread only waits for them,
+        // so child tasks that depend on each other can't starve the thread pool
+        for handle in handles {
+            handle.wait();
+        }
+        // child tasks spawned in the meantime are still queued, and get driven once the run loop restarts
+        Executor::stop(self);
+    }
+
+    fn cancel(&self, handles: Vec<Self::JoinHandle>) {
+        for handle in handles {
+            handle.cancel();
+        }
+        Executor::cancel(self);
+    }
+
+    fn abort(&self, handle: &Self::JoinHandle) {
+        handle.cancel();
+    }
+
+    fn restart(&self) {
+        self.start();
+    }
+
+    fn poll(&self) {
+        self.poll_all();
+    }
+
+    fn set_thread_count(&self, thread_count: usize) -> bool {
+        Executor::set_thread_count(self, thread_count);
+        true
+    }
+
+    fn active_count(&self) -> usize {
+        Executor::active_count(self)
+    }
+
+    fn queued_count(&self) -> usize {
+        Executor::queued_count(self)
+    }
+
+    fn poll_capacity(&self, cx: &mut Context<'_>) -> Poll<()> {
+        Executor::poll_capacity(self, cx)
+    }
+}
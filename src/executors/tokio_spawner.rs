@@ -0,0 +1,162 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use tokio::{
+    runtime::{Builder, Handle, Runtime, RuntimeFlavor},
+    task::JoinHandle,
+};
+
+use super::spawner::Spawner;
+
+/// Drives child tasks on a Tokio runtime
+///
+/// Unless a thread count or a thread name is given, it reuses the ambient runtime when created inside a multi-threaded
+/// Tokio runtime, otherwise it owns a multi-threaded runtime of its own, because blocking on the only thread
+/// of a current-thread runtime would never let the child tasks make progress.
+///
+/// The runtime's own metrics count the tasks of the whole runtime, which an ambient runtime shares with the rest
+/// of the application, so the spawner counts the child tasks it spawned itself.
+#[derive(Clone)]
+pub(crate) struct TokioSpawner {
+    handle: Handle,
+    _runtime: Option<Arc<OwnedRuntime>>,
+    alive: Arc<AtomicUsize>,
+}
+
+/// Counts a spawned child task until its future is dropped, whether it finished or got aborted
+struct Alive(Arc<AtomicUsize>);
+
+impl Alive {
+    fn new(alive: &Arc<AtomicUsize>) -> Self {
+        alive.fetch_add(1, Ordering::AcqRel);
+        Alive(alive.clone())
+    }
+}
+
+impl Drop for Alive {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+struct OwnedRuntime(Option<Runtime>);
+
+impl Drop for OwnedRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.0.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+impl Default for TokioSpawner {
+    fn default() -> Self {
+        TokioSpawner::with_threads(0, None)
+    }
+}
+
+impl Spawner for TokioSpawner {
+    type JoinHandle = JoinHandle<()>;
+
+    fn with_threads(thread_count: usize, thread_name: Option<&str>) -> Self {
+        match Handle::try_current() {
+            Ok(handle)
+                if thread_count == 0
+                    && thread_name.is_none()
+                    && handle.runtime_flavor() == RuntimeFlavor::MultiThread =>
+            {
+                Self {
+                    handle,
+                    _runtime: None,
+                    alive: Arc::new(AtomicUsize::new(0)),
+                }
+            }
+            _ => {
+                let mut builder: Builder = Builder::new_multi_thread();
+                if thread_count > 0 {
+                    builder.worker_threads(thread_count);
+                }
+                if let Some(thread_name) = thread_name {
+                    builder.thread_name(thread_name);
+                }
+                let runtime: Runtime = builder
+                    .enable_all()
+                    .build()
+                    .expect("Failed to build the tokio runtime");
+                Self {
+                    handle: runtime.handle().clone(),
+                    _runtime: Some(Arc::new(OwnedRuntime(Some(runtime)))),
+                    alive: Arc::new(AtomicUsize::new(0)),
+                }
+            }
+        }
+    }
+
+    fn spawn<Fut>(&self, task: Fut) -> Self::JoinHandle
+    where
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let alive: Alive = Alive::new(&self.alive);
+        self.handle.spawn(async move {
+            let _alive: Alive = alive;
+            task.await
+        })
+    }
+
+    fn spawn_blocking<Fut>(&self, task: Fut) -> Self::JoinHandle
+    where
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let alive: Alive = Alive::new(&self.alive);
+        self.handle.spawn_blocking(move || {
+            let _alive: Alive = alive;
+            crate::block_on(task)
+        })
+    }
+
+    fn block_on(&self, handles: Vec<Self::JoinHandle>) {
+        let wait = move || {
+            for handle in handles {
+                _ = crate::block_on(handle);
+            }
+        };
+        match Handle::try_current() {
+            Ok(current) if current.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(wait)
+            }
+            _ => wait(),
+        }
+    }
+
+    fn cancel(&self, handles: Vec<Self::JoinHandle>) {
+        for handle in handles {
+            handle.abort();
+        }
+    }
+
+    fn abort(&self, handle: &Self::JoinHandle) {
+        handle.abort();
+    }
+
+    fn active_count(&self) -> usize {
+        // Tokio doesn't tell how many tasks its workers are polling right now,
+        // so every child task that hasn't finished counts as active
+        self.alive.load(Ordering::Acquire)
+    }
+
+    fn queued_count(&self) -> usize {
+        // Tokio doesn't tell which of its queued tasks are child tasks of this spawner
+        0
+    }
+
+    fn poll_capacity(&self, _cx: &mut Context<'_>) -> Poll<()> {
+        // no child task ever counts as queued, so there is nothing to wait for
+        Poll::Ready(())
+    }
+}
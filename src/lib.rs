@@ -73,28 +73,40 @@
 //!
 //! To properly use this crate
 //! * ``with_spawn_group`` for the creation of a dynamic number of asynchronous tasks that return a value. See [`with_spawn_group`](self::with_spawn_group)
-//! for more information
+//!   for more information
+//!
+//! * ``with_threaded_spawn_group`` for the creation of a dynamic number of asynchronous tasks that return a value and run on a specific number of threads.
+//!   See [`with_threaded_spawn_group`](self::with_threaded_spawn_group)
+//!   for more information
 //!
 //! * ``with_type_spawn_group`` for the creation of a dynamic number of asynchronous tasks that return a value by specifying the type explicitly. See [`with_type_spawn_group`](self::with_type_spawn_group)
-//! for more information
+//!   for more information
 //!
 //! * ``with_err_spawn_group`` for the creation of a dynamic number of asynchronous tasks that return a value or an error.
-//! See [`with_err_spawn_group`](self::with_err_spawn_group)
-//! for more information
+//!   See [`with_err_spawn_group`](self::with_err_spawn_group)
+//!   for more information
 //!
 //! * ``with_err_type_spawn_group`` for the creation of a dynamic number of asynchronous tasks that return a value or an error by specifiying the return type and the error type explicitly.
-//! See [`with_err_type_spawn_group`](self::with_err_type_spawn_group)
-//! for more information
+//!   See [`with_err_type_spawn_group`](self::with_err_type_spawn_group)
+//!   for more information
 //!
 //! * ``with_discarding_spawn_group`` for the creation of a dynamic number of asynchronous tasks that returns nothing.
-//! See [`with_discarding_spawn_group`](self::with_discarding_spawn_group)
-//! for more information
+//!   See [`with_discarding_spawn_group`](self::with_discarding_spawn_group)
+//!   for more information
+//!
+//! * ``with_scoped_spawn_group`` for the creation of a dynamic number of asynchronous tasks that may borrow data from the caller's stack, blocking until they finish.
+//!   See [`with_scoped_spawn_group`](self::with_scoped_spawn_group)
+//!   for more information
 //!
 //! * ``sleep`` similar to ``std::thread::sleep`` but for sleeping in asynchronous environments. See [`sleep`](self::sleep)
-//! for more information
+//!   for more information
 //!
 //! * ``block_on`` polls future to finish. See [`block_on`](self::block_on)
-//! for more information
+//!   for more information
+//!
+//! * ``spawn_group_enum!`` generates an enum over differently typed results, so their child tasks can share a spawn group.
+//!   See [`spawn_group_enum`](self::spawn_group_enum)
+//!   for more information
 //!
 //! # Spawning Child Tasks
 //!
@@ -111,6 +123,9 @@
 //! By calling explicitly calling the ``cancel_all`` method on any of the spawn groups' instance, all running child tasks
 //! are immediately cancelled.
 //!
+//! Child tasks can also cooperate with the cancellation by observing the spawn group's [`CancellationToken`](self::CancellationToken)
+//! and cleaning up within the grace period set by ``set_cancellation_grace_period`` before they get forcefully cancelled.
+//!
 //! # Waiting
 //!
 //! By calling explicitly calling the ``wait_for_all_tasks`` method on any of the spawn groups' instance, all child tasks
@@ -142,7 +157,7 @@
 //!      while let Some(x) = group.next().await {
 //!         counter += x;
 //!      }
-//! 
+//!
 //!     assert_eq!(counter, 55);
 //!
 //! }).await;
@@ -154,32 +169,104 @@
 //!
 //!
 //!
+//! # Feature flags
+//!
+//! * ``tokio``: drives the child tasks of every spawn group on Tokio rather than the crate's built-in executor.
+//!   When a spawn group is created inside a multi-threaded Tokio runtime such as the one ``#[tokio::main]`` starts,
+//!   its child tasks are spawned onto that runtime, otherwise the spawn group owns a multi-threaded Tokio runtime of its own.
+//! * ``deterministic``: adds ``set_deterministic_seed`` to the spawn groups, which fixes the order they yield their results in
+//!   for a given seed, so tests can assert their exact output. It's meant for tests only.
+//! * ``local``: adds ``with_local_spawn_group`` and ``LocalSpawnGroup``, which run their child tasks on the thread they're awaited on
+//!   rather than on a thread pool, so the child tasks and their results need not be ``Send``, as in WebAssembly.
+//! * ``stats``: adds ``stats`` to the spawn groups, which reports the count and the mean, maximum
+//!   and 99th percentile latency of their child tasks, without any cost to those who leave it off.
+//! * ``tracing``: runs every child task inside a ``child_task`` span of the ``tracing`` crate,
+//!   carrying its id, priority and name, with the code that spawned it as its parent span.
+//!   The span is entered whenever the child task runs and closes once it finished, panicked or got cancelled.
+//! * ``sink``: adds ``drain_into`` to the spawn groups, which forwards their results into a ``futures`` sink as they finish,
+//!   only pulling the next result once the sink is ready for it, rather than collecting them into a vector first.
+//! * ``channel``: adds ``forward_to`` to the spawn groups, which sends their results down a ``futures`` channel as they finish,
+//!   waiting for room in the channel, and cancels the spawn group once the receiver was dropped.
+//! * ``futures``: adds ``SpawnGroup::from_futures_unordered``, which spawns the futures of a ``FuturesUnordered`` as child tasks,
+//!   and ``into_futures_unordered``, which hands the child tasks back as a ``FuturesUnordered`` of futures resolving to their results.
+//!
+//! With the ``tokio`` feature on, a spawn group created inside a Tokio runtime spawns its child tasks onto it,
+//! so they can use Tokio's own facilities such as timers or its runtime handle:
+//!
+//! ```rust
+//! # #[cfg(feature = "tokio")]
+//! # fn main() {
+//! use futures_lite::StreamExt;
+//! use spawn_groups::{with_spawn_group, Priority};
+//!
+//! let runtime = tokio::runtime::Runtime::new().unwrap();
+//! let sum = runtime.block_on(async {
+//!     with_spawn_group(|mut group| async move {
+//!         for i in 0..=10 {
+//!             group.spawn_task(Priority::default(), async move {
+//!                 assert!(tokio::runtime::Handle::try_current().is_ok());
+//!                 i
+//!             });
+//!         }
+//!         group.fold(0, |acc, x| acc + x).await
+//!     })
+//!     .await
+//! });
+//! assert_eq!(sum, 55);
+//! # }
+//! # #[cfg(not(feature = "tokio"))]
+//! # fn main() {}
+//! ```
+//!
 //! # Note
 //! * Import ``StreamExt`` trait from ``futures_lite::StreamExt`` or ``futures::stream::StreamExt`` or ``async_std::stream::StreamExt`` to provide a variety of convenient combinator functions on the various spawn groups.
 //! * To await all running child tasks to finish their execution, call ``wait_for_all`` method on the spawn group instance unless using the [`with_discarding_spawn_group`](self::with_discarding_spawn_group) function.
 //!
 //! # Warning
 //! * This crate relies on atomics
-//! * Avoid using a spawn group from outside the above functions this crate provides
+//! * Avoid using a spawn group from outside the above functions this crate provides, unless it was created by a [`SpawnGroupBuilder`](spawn_group::SpawnGroupBuilder)
 //! * Avoid calling long, blocking, non asynchronous functions while using any of the spawn groups because it was built with asynchrony in mind.
 //! * Avoid spawning off an asynchronous function such as calling spawn methods from crate such as tokio, async_std, smol, etc.
 
 pub mod discarding_spawn_group;
 pub mod err_spawn_group;
+#[cfg(feature = "local")]
+pub mod local_spawn_group;
+pub mod scoped_spawn_group;
 pub mod spawn_group;
 
 mod async_runtime;
 mod async_stream;
+mod cancellation_token;
 mod executors;
 mod meta_types;
 mod shared;
 mod sleeper;
+#[cfg(not(feature = "tokio"))]
 mod threadpool_impl;
 mod yield_now;
 
+pub use cancellation_token::{CancellationToken, Cancelled};
 pub use executors::block_on;
 pub use meta_types::GetType;
+pub use shared::cancel_order::CancelOrder;
+pub use shared::completion::Completion;
+pub use shared::drop_policy::DropPolicy;
+pub use shared::metrics::MetricsRecorder;
+pub use shared::panic_policy::PanicPolicy;
+pub use shared::semaphore::Permit;
 pub use shared::priority::Priority;
+pub use shared::queue_snapshot::QueueSnapshot;
+pub use shared::saturation_policy::SaturationPolicy;
+pub use shared::scheduling_policy::SchedulingPolicy;
+pub use shared::spawn_error::SpawnError;
+pub use shared::spawner::Spawner;
+#[cfg(feature = "stats")]
+pub use shared::stats::TaskStats;
+pub use shared::task_id::TaskId;
+pub use shared::task_info::TaskInfo;
+pub use shared::task_result::TaskResult;
+pub use shared::task_status::TaskStatus;
 pub use sleeper::sleep;
 pub use yield_now::yield_now;
 
@@ -190,6 +277,7 @@ use std::marker::PhantomData;
 ///
 /// This closure ensures that before the function call ends, all spawned child tasks are implicitly waited for, or the programmer can explicitly wait by calling  its ``wait_for_all()`` method
 /// of the ``SpawnGroup`` struct.
+/// If the closure panics, or the returned future is dropped before it completes, the child tasks get cancelled instead.
 ///
 /// See [`SpawnGroup`](spawn_group::SpawnGroup)
 /// for more.
@@ -239,13 +327,14 @@ where
 {
     _ = of_type;
     let task_group = spawn_group::SpawnGroup::<ResultType>::new();
-    body(task_group).await
+    task_group.scope().run(body(task_group)).await
 }
 
 /// Starts a scoped closure that takes a mutable ``SpawnGroup`` instance as an argument which can execute any number of child tasks which its result values are of the generic ``ResultType`` type.
 ///
 /// This closure ensures that before the function call ends, all spawned child tasks are implicitly waited for, or the programmer can explicitly wait by calling  its ``wait_for_all()`` method
 /// of the ``SpawnGroup`` struct.
+/// If the closure panics, or the returned future is dropped before it completes, the child tasks get cancelled instead.
 ///
 /// See [`SpawnGroup`](spawn_group::SpawnGroup)
 /// for more.
@@ -283,6 +372,22 @@ where
 ///  assert_eq!(final_result, 55);
 /// # });
 /// ```
+///
+/// A panicking closure cancels the child tasks instead of waiting for them forever:
+///
+/// ```rust
+/// use spawn_groups::with_spawn_group;
+/// use spawn_groups::Priority;
+/// use std::panic::{catch_unwind, AssertUnwindSafe};
+///
+/// let result = catch_unwind(AssertUnwindSafe(|| {
+///     spawn_groups::block_on(with_spawn_group(|mut group| async move {
+///         group.spawn_task(Priority::default(), std::future::pending::<i32>());
+///         panic!("giving up");
+///     }))
+/// }));
+/// assert!(result.is_err());
+/// ```
 pub async fn with_spawn_group<Closure, Fut, ResultType, ReturnType>(body: Closure) -> ReturnType
 where
     Closure: FnOnce(spawn_group::SpawnGroup<ResultType>) -> Fut + Send + 'static,
@@ -290,14 +395,71 @@ where
     ResultType: Send + 'static,
 {
     let task_group = spawn_group::SpawnGroup::<ResultType>::new();
-    body(task_group).await
+    task_group.scope().run(body(task_group)).await
+}
+
+/// Starts a scoped closure that takes a mutable ``SpawnGroup`` instance as an argument which can execute any number of child tasks which its result values are of the generic ``ResultType`` type,
+/// driving those child tasks on a specific number of threads.
+///
+/// This closure ensures that before the function call ends, all spawned child tasks are implicitly waited for, or the programmer can explicitly wait by calling  its ``wait_for_all()`` method
+/// of the ``SpawnGroup`` struct.
+/// If the closure panics, or the returned future is dropped before it completes, the child tasks get cancelled instead.
+///
+/// See [`SpawnGroup`](spawn_group::SpawnGroup)
+/// for more.
+///
+/// # Parameters
+///
+/// * `thread_count`: The number of threads the child tasks run on. Zero means as many threads as the available parallelism
+/// * `body`: an async closure that takes a mutable instance of ``SpawnGroup`` as an argument
+///
+/// # Returns
+///
+/// Anything the ``body`` parameter returns
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::with_threaded_spawn_group;
+/// use futures_lite::StreamExt;
+/// use spawn_groups::Priority;
+///
+/// # spawn_groups::block_on(async move {
+/// let final_result = with_threaded_spawn_group(2, |mut group| async move {
+///      for i in 0..=10 {
+///         group.spawn_task(Priority::default(), async move {
+///            // simulate asynchronous operation
+///            i
+///         });
+///      }
+///
+///      group.fold(0, |acc, x| {
+///          acc + x
+///      }).await
+///  }).await;
+///
+///  assert_eq!(final_result, 55);
+/// # });
+/// ```
+pub async fn with_threaded_spawn_group<Closure, Fut, ResultType, ReturnType>(
+    thread_count: usize,
+    body: Closure,
+) -> ReturnType
+where
+    Closure: FnOnce(spawn_group::SpawnGroup<ResultType>) -> Fut + Send + 'static,
+    Fut: Future<Output = ReturnType> + Send + 'static,
+    ResultType: Send + 'static,
+{
+    let task_group = spawn_group::SpawnGroup::<ResultType>::with_threads(thread_count);
+    task_group.scope().run(body(task_group)).await
 }
 
 /// Starts a scoped closure that takes a mutable ``ErrSpawnGroup`` instance as an argument which can execute any number of child tasks which its result values are of the type ``Result<ResultType, ErrorType>``
 /// where ``ResultType`` can be of type and ``ErrorType`` which is any type that implements the standard ``Error`` type.
 ///
 /// This closure ensures that before the function call ends, all spawned child tasks are implicitly waited for, or the programmer can explicitly wait by calling its ``wait_for_all()`` method
-/// of the ``ErrSpawnGroup`` struct
+/// of the ``ErrSpawnGroup`` struct.
+/// If the closure panics, or the returned future is dropped before it completes, the child tasks get cancelled instead.
 ///
 /// See [`ErrSpawnGroup`](err_spawn_group::ErrSpawnGroup)
 /// for more.
@@ -393,14 +555,15 @@ where
 {
     _ = (of_type, error_type);
     let task_group = err_spawn_group::ErrSpawnGroup::<ResultType, ErrorType>::new();
-    body(task_group).await
+    task_group.scope().run(body(task_group)).await
 }
 
 /// Starts a scoped closure that takes a mutable ``ErrSpawnGroup`` instance as an argument which can execute any number of child tasks which its result values are of the type ``Result<ResultType, ErrorType>``
 /// where ``ResultType`` can be of type and ``ErrorType`` which is any type that implements the standard ``Error`` type.
 ///
 /// This closure ensures that before the function call ends, all spawned child tasks are implicitly waited for, or the programmer can explicitly wait by calling its ``wait_for_all()`` method
-/// of the ``ErrSpawnGroup`` struct
+/// of the ``ErrSpawnGroup`` struct.
+/// If the closure panics, or the returned future is dropped before it completes, the child tasks get cancelled instead.
 ///
 /// See [`ErrSpawnGroup`](err_spawn_group::ErrSpawnGroup)
 /// for more.
@@ -491,12 +654,13 @@ where
     ResultType: Send + 'static,
 {
     let task_group = err_spawn_group::ErrSpawnGroup::<ResultType, ErrorType>::new();
-    body(task_group).await
+    task_group.scope().run(body(task_group)).await
 }
 
 /// Starts a scoped closure that takes a mutable ``DiscardingSpawnGroup`` instance as an argument which can execute any number of child tasks which return nothing.
 ///
 /// Ensures that before the function call ends, all spawned tasks are implicitly waited for
+/// If the closure panics, or the returned future is dropped before it completes, the child tasks get cancelled instead.
 ///
 /// See [`DiscardingSpawnGroup`](discarding_spawn_group::DiscardingSpawnGroup)
 /// for more.
@@ -535,5 +699,190 @@ where
     Closure: FnOnce(discarding_spawn_group::DiscardingSpawnGroup) -> Fut + Send + 'static,
 {
     let discarding_tg = discarding_spawn_group::DiscardingSpawnGroup::new();
-    body(discarding_tg).await
+    discarding_tg.scope().run(body(discarding_tg)).await
+}
+
+/// Starts a scoped closure that takes a mutable ``LocalSpawnGroup`` instance as an argument which can execute any number of child tasks
+/// on the current thread, which neither need to be ``Send`` nor return ``Send`` values.
+///
+/// This closure ensures that before the function call ends, all spawned child tasks are implicitly waited for, or the programmer can explicitly wait by calling  its ``wait_for_all()`` method
+/// of the ``LocalSpawnGroup`` struct.
+/// If the closure panics, or the returned future is dropped before it completes, the child tasks get dropped instead.
+///
+/// Only available with the ``local`` feature.
+///
+/// See [`LocalSpawnGroup`](local_spawn_group::LocalSpawnGroup)
+/// for more.
+///
+/// # Parameters
+///
+/// * `body`: an async closure that takes a mutable instance of ``LocalSpawnGroup`` as an argument
+///
+/// # Returns
+///
+/// Anything the ``body`` parameter returns
+///
+/// # Example
+///
+/// ```rust
+/// use futures_lite::StreamExt;
+/// use spawn_groups::{with_local_spawn_group, Priority};
+/// use std::{cell::Cell, rc::Rc};
+///
+/// # spawn_groups::block_on(async move {
+/// let counter = Rc::new(Cell::new(0));
+/// let observed = counter.clone();
+/// let sum = with_local_spawn_group(|mut group| async move {
+///     for i in 1..=4 {
+///         let counter = counter.clone();
+///         group.spawn_task(Priority::default(), async move {
+///             counter.set(counter.get() + 1);
+///             i
+///         });
+///     }
+///     group.fold(0, |acc, x| acc + x).await
+/// }).await;
+///
+/// assert_eq!(sum, 10);
+/// assert_eq!(observed.get(), 4);
+/// # });
+/// ```
+#[cfg(feature = "local")]
+pub async fn with_local_spawn_group<Closure, Fut, ResultType, ReturnType>(
+    body: Closure,
+) -> ReturnType
+where
+    Closure: FnOnce(local_spawn_group::LocalSpawnGroup<ResultType>) -> Fut,
+    Fut: Future<Output = ReturnType>,
+    ResultType: 'static,
+{
+    let local_tg = local_spawn_group::LocalSpawnGroup::<ResultType>::new();
+    let mut remaining = local_tg.handle();
+    let result: ReturnType = body(local_tg).await;
+    remaining.wait_for_all().await;
+    result
+}
+
+/// Starts a scoped closure that takes a mutable reference to a ``ScopedSpawnGroup`` whose child tasks may borrow data
+/// from outside of the closure, such as a ``&Config`` on the caller's stack, rather than capturing only ``'static`` data.
+///
+/// Like ``std::thread::scope``, it blocks the current thread until every child task spawned in the closure finished,
+/// and if the closure panics, the child tasks get cancelled and waited for until they stopped instead.
+/// Being a blocking function rather than a future is what makes the borrowing sound,
+/// see [`ScopedSpawnGroup`](scoped_spawn_group::ScopedSpawnGroup)
+/// for the safety invariants.
+///
+/// # Parameters
+///
+/// * `body`: a closure that takes a mutable reference to a ``ScopedSpawnGroup`` as an argument
+///
+/// # Returns
+///
+/// Anything the ``body`` parameter returns
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{with_scoped_spawn_group, Priority};
+///
+/// struct Config {
+///     factor: u64,
+/// }
+///
+/// let config = Config { factor: 3 };
+/// let mut results = with_scoped_spawn_group(|group| {
+///     for i in 1..=4 {
+///         let config: &Config = &config;
+///         group.spawn_task(Priority::default(), async move { i * config.factor });
+///     }
+///     group.wait_for_all_items()
+/// });
+///
+/// results.sort();
+/// assert_eq!(results, vec![3, 6, 9, 12]);
+/// ```
+///
+/// Data created inside the closure doesn't live long enough to be borrowed:
+///
+/// ```rust,compile_fail
+/// use spawn_groups::{with_scoped_spawn_group, Priority};
+///
+/// with_scoped_spawn_group(|group| {
+///     let local = 1;
+///     let local: &i32 = &local;
+///     group.spawn_task(Priority::default(), async move { *local });
+/// });
+/// ```
+pub fn with_scoped_spawn_group<'env, Closure, ResultType, ReturnType>(body: Closure) -> ReturnType
+where
+    Closure: FnOnce(&mut scoped_spawn_group::ScopedSpawnGroup<'env, ResultType>) -> ReturnType,
+    ResultType: Send + 'static,
+{
+    let mut scoped_tg = scoped_spawn_group::ScopedSpawnGroup::<ResultType>::new();
+    let result: ReturnType = body(&mut scoped_tg);
+    // dropping the spawn group waits for the child tasks, before any of the data they borrow goes away
+    drop(scoped_tg);
+    result
+}
+
+/// Maps the inputs through an async closure on a spawn group of its own, with at most ``concurrency`` of them in flight,
+/// and streams the outputs in the order they finished.
+///
+/// The inputs are pulled lazily, so the iterator may be endless.
+///
+/// See [`ConcurrentMap`](spawn_group::ConcurrentMap)
+/// for more.
+///
+/// # Parameters
+///
+/// * `inputs`: the inputs to map
+/// * `concurrency`: the highest number of child tasks either running or waiting for their output to be consumed.
+///   Zero means no limit
+/// * `f`: a closure that returns the future mapping an input to its output
+///
+/// # Returns
+///
+/// A stream of the outputs
+///
+/// # Example
+///
+/// ```rust
+/// use futures_lite::StreamExt;
+/// use spawn_groups::{sleep, spawn_group_map};
+/// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+/// use std::time::Duration;
+///
+/// # spawn_groups::block_on(async move {
+/// let (running, most_running) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+/// let (running_count, most_running_count) = (running.clone(), most_running.clone());
+/// let mut doubled: Vec<u64> = spawn_group_map(0..1000, 8, move |i: u64| {
+///     let (running, most_running) = (running_count.clone(), most_running_count.clone());
+///     async move {
+///         let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+///         most_running.fetch_max(now_running, Ordering::SeqCst);
+///         sleep(Duration::from_millis(1)).await;
+///         running.fetch_sub(1, Ordering::SeqCst);
+///         i * 2
+///     }
+/// })
+/// .collect()
+/// .await;
+///
+/// doubled.sort();
+/// assert_eq!(doubled, (0..1000).map(|i| i * 2).collect::<Vec<u64>>());
+/// assert!(most_running.load(Ordering::SeqCst) <= 8);
+/// # });
+/// ```
+pub fn spawn_group_map<I, F, Fut>(
+    inputs: I,
+    concurrency: usize,
+    f: F,
+) -> spawn_group::ConcurrentMap<I::IntoIter, F, Fut::Output>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future + Send + 'static,
+    Fut::Output: Send + 'static,
+{
+    spawn_group::ConcurrentMap::new(inputs.into_iter(), concurrency, f)
 }
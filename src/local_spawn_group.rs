@@ -0,0 +1,229 @@
+use crate::shared::{priority::Priority, task_id::TaskId};
+use futures_lite::{Stream, StreamExt};
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+/// Local Spawn Group
+///
+/// A kind of a spawn group that runs its child tasks on the thread it's awaited on, rather than on a thread pool,
+/// so neither the child tasks nor their results need to be ``Send``.
+/// It suits single-threaded environments such as WebAssembly in the browser.
+///
+/// Child tasks only make progress while the spawn group is waited for or its ``Stream`` is polled,
+/// and child tasks of a higher priority are polled first.
+///
+/// Only available with the ``local`` feature, and created by ``with_local_spawn_group()``.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{with_local_spawn_group, Priority};
+/// use std::rc::Rc;
+///
+/// # spawn_groups::block_on(async move {
+/// let shared = Rc::new(10);
+/// let mut results = with_local_spawn_group(|mut group| async move {
+///     for i in 0..3 {
+///         let shared = shared.clone();
+///         group.spawn_task(Priority::default(), async move { Rc::new(*shared + i) });
+///     }
+///     group.wait_for_all_items().await
+/// }).await;
+///
+/// results.sort();
+/// assert_eq!(results, vec![Rc::new(10), Rc::new(11), Rc::new(12)]);
+/// # });
+/// ```
+pub struct LocalSpawnGroup<ValueType> {
+    /// A field that indicates if the spawn group has been cancelled
+    pub is_cancelled: bool,
+    inner: Rc<RefCell<LocalTasks<ValueType>>>,
+}
+
+/// A spawned child task along with what it was spawned with
+struct LocalTask<ValueType> {
+    priority: Priority,
+    sequence: u64,
+    future: Pin<Box<dyn Future<Output = ValueType>>>,
+}
+
+struct LocalTasks<ValueType> {
+    tasks: Vec<LocalTask<ValueType>>,
+    results: VecDeque<ValueType>,
+    sequence: u64,
+}
+
+impl<ValueType> LocalSpawnGroup<ValueType> {
+    pub(crate) fn new() -> Self {
+        Self {
+            is_cancelled: false,
+            inner: Rc::new(RefCell::new(LocalTasks {
+                tasks: vec![],
+                results: VecDeque::new(),
+                sequence: 0,
+            })),
+        }
+    }
+
+    /// A handle sharing the child tasks, which ``with_local_spawn_group()`` waits through once its body returns
+    pub(crate) fn handle(&self) -> Self {
+        Self {
+            is_cancelled: self.is_cancelled,
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<ValueType: 'static> LocalSpawnGroup<ValueType> {
+    /// Spawns a new task into the spawn group
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Returns
+    /// The id of the child task, which ``cancel_task`` takes to cancel it on its own
+    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F) -> TaskId
+    where
+        F: Future<Output = ValueType> + 'static,
+    {
+        let mut inner = self.inner.borrow_mut();
+        let sequence: u64 = inner.sequence;
+        inner.sequence += 1;
+        // kept ordered by descending priority, then by the order they were spawned in
+        let index: usize = inner
+            .tasks
+            .iter()
+            .position(|task| task.priority < priority)
+            .unwrap_or(inner.tasks.len());
+        inner.tasks.insert(
+            index,
+            LocalTask {
+                priority,
+                sequence,
+                future: Box::pin(closure),
+            },
+        );
+        TaskId::new(sequence)
+    }
+
+    /// Spawn a new task only if the group is not cancelled yet,
+    /// otherwise does nothing
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Returns
+    /// Whether the child task was spawned
+    pub fn spawn_task_unless_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
+    where
+        F: Future<Output = ValueType> + 'static,
+    {
+        if self.is_cancelled {
+            return false;
+        }
+        self.spawn_task(priority, closure);
+        true
+    }
+}
+
+impl<ValueType> LocalSpawnGroup<ValueType> {
+    /// Cancels all running task in the spawn group
+    pub fn cancel_all(&mut self) {
+        self.inner.borrow_mut().tasks.clear();
+        self.is_cancelled = true;
+    }
+
+    /// Cancels a single child task, leaving the other child tasks running
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: the id the child task was spawned with
+    ///
+    /// # Returns
+    /// Whether the child task was cancelled, which is false if it already finished
+    pub fn cancel_task(&mut self, id: TaskId) -> bool {
+        let mut inner = self.inner.borrow_mut();
+        let Some(index) = inner
+            .tasks
+            .iter()
+            .position(|task| TaskId::new(task.sequence) == id)
+        else {
+            return false;
+        };
+        inner.tasks.remove(index);
+        true
+    }
+
+    /// A Boolean value that indicates whether the group has any remaining tasks.
+    pub fn is_empty(&self) -> bool {
+        self.inner.borrow().tasks.is_empty()
+    }
+
+    /// Returns the number of child tasks that haven't finished yet
+    pub fn pending_count(&self) -> usize {
+        self.inner.borrow().tasks.len()
+    }
+
+    /// Waits for all remaining child tasks for finish.
+    ///
+    /// Their results stay in the spawn group for its ``Stream`` to yield.
+    pub async fn wait_for_all(&mut self) {
+        futures_lite::future::poll_fn(|cx| self.poll_tasks(cx)).await;
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their results
+    /// in the order they finished.
+    ///
+    /// Results already popped off the spawn group's stream are not returned again.
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks, which is empty if no child task was spawned
+    pub async fn wait_for_all_items(&mut self) -> Vec<ValueType> {
+        self.collect().await
+    }
+
+    /// Polls every child task once, storing the results of those that finished,
+    /// and returns whether no child task is left running
+    fn poll_tasks(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut inner = self.inner.borrow_mut();
+        let LocalTasks { tasks, results, .. } = &mut *inner;
+        tasks.retain_mut(|task| match task.future.as_mut().poll(cx) {
+            Poll::Ready(result) => {
+                results.push_back(result);
+                false
+            }
+            Poll::Pending => true,
+        });
+        match tasks.is_empty() {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    }
+}
+
+/// Yields the results of the child tasks as they finish, and ends once every child task finished
+/// and all their results were yielded
+impl<ValueType> Stream for LocalSpawnGroup<ValueType> {
+    type Item = ValueType;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let finished: bool = self.poll_tasks(cx).is_ready();
+        if let Some(result) = self.inner.borrow_mut().results.pop_front() {
+            return Poll::Ready(Some(result));
+        }
+        match finished {
+            true => Poll::Ready(None),
+            false => Poll::Pending,
+        }
+    }
+}
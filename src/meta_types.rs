@@ -17,8 +17,6 @@
 /// 
 /// assert_eq!(string_result, String::from("32"));
 /// ```
-/// 
-
 use std::marker::PhantomData;
 
 /// `GetType` trait implements asssociated constant for every type and this associated constant provides a metatype value that's a type's type value
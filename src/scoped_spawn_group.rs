@@ -0,0 +1,114 @@
+use crate::shared::{initializible::Initializible, priority::Priority, runtime::RuntimeEngine};
+use std::{future::Future, marker::PhantomData, pin::Pin};
+
+type BoxedFuture<'env, ItemType> = Pin<Box<dyn Future<Output = ItemType> + Send + 'env>>;
+
+/// Scoped Spawn Group
+///
+/// A kind of a spawn group whose child tasks may borrow data from outside of it, such as a ``&Config`` on the caller's stack,
+/// rather than capturing only ``'static`` data. Only ``with_scoped_spawn_group()`` creates it.
+///
+/// # Safety invariants
+///
+/// The child tasks are spawned onto the same backend as the other spawn groups, which only accepts ``'static`` futures,
+/// so the lifetime of their borrows gets erased when spawning them. That is only sound because no child task
+/// outlives the data it borrows, which holds since:
+///
+/// * child tasks can only borrow data that lives for ``'env``, which outlives the call to ``with_scoped_spawn_group()``
+///   since it is a lifetime parameter of it, so data created inside the body can't be borrowed
+/// * ``with_scoped_spawn_group()`` is a blocking function, like ``std::thread::scope``, so it can't be forgotten halfway
+///   the way a future could with ``std::mem::forget``, and it only returns, or unwinds, once every child task stopped
+/// * when the body returns, every child task is waited for, and when it panics, every child task gets cancelled and
+///   whoever was polling it gets waited for, so its future, borrows and all, is dropped before the panic leaves the scope
+/// * the spawn group is only handed to the body by reference, and can't be created any other way,
+///   so it can't be moved out of its scope, and it is invariant over ``'env`` so a shorter lifetime can't be swapped in
+///
+/// The results themselves outlive the scope, so they must still be ``'static``.
+pub struct ScopedSpawnGroup<'env, ValueType: Send + 'static> {
+    runtime: RuntimeEngine<ValueType>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'env, ValueType: Send + 'static> ScopedSpawnGroup<'env, ValueType> {
+    /// Spawns a new task into the spawn group
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``, which may borrow data that lives for ``'env``
+    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F)
+    where
+        F: Future<Output = ValueType> + Send + 'env,
+    {
+        let task: BoxedFuture<'env, ValueType> = Box::pin(closure);
+        // SAFETY: the child task stops before anything it borrows goes away, as laid out on ``ScopedSpawnGroup``
+        let task: BoxedFuture<'static, ValueType> = unsafe { std::mem::transmute(task) };
+        self.runtime.write_task(priority, task);
+    }
+
+    /// Spawn a new task only if the group is not cancelled yet,
+    /// otherwise does nothing
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``, which may borrow data that lives for ``'env``
+    ///
+    /// # Returns
+    /// Whether the child task was spawned
+    pub fn spawn_task_unless_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
+    where
+        F: Future<Output = ValueType> + Send + 'env,
+    {
+        if self.is_cancelled() {
+            return false;
+        }
+        self.spawn_task(priority, closure);
+        true
+    }
+
+    /// Cancels all running task in the spawn group, and blocks until every one of them actually stopped,
+    /// since a child task being polled on another thread may still be using what it borrows
+    pub fn cancel_all(&mut self) {
+        crate::block_on(self.runtime.cancel_and_wait());
+    }
+
+    /// Returns whether the spawn group was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.runtime.was_cancelled()
+    }
+
+    /// Returns the number of child tasks that haven't finished yet
+    pub fn pending_count(&self) -> usize {
+        self.runtime.stream().task_count()
+    }
+
+    /// Blocks until all remaining child tasks finished and returns their results
+    /// in the order they finished.
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks, which is empty if no child task was spawned
+    pub fn wait_for_all_items(&mut self) -> Vec<ValueType> {
+        crate::block_on(self.runtime.wait_for_all())
+    }
+}
+
+impl<ValueType: Send + 'static> ScopedSpawnGroup<'_, ValueType> {
+    pub(crate) fn new() -> Self {
+        Self {
+            runtime: RuntimeEngine::init(),
+            env: PhantomData,
+        }
+    }
+}
+
+impl<ValueType: Send + 'static> Drop for ScopedSpawnGroup<'_, ValueType> {
+    /// Makes sure no child task outlives the scope, even when its body panics
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            crate::block_on(self.runtime.cancel_and_wait());
+        } else {
+            self.runtime.wait_for_all_tasks();
+        }
+    }
+}
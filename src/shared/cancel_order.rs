@@ -0,0 +1,15 @@
+/// Cancel Order
+///
+/// Decides in which order a spawn group cancels its still running child tasks when it gets cancelled,
+/// which matters when dropping them has side effects, such as closing connections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CancelOrder {
+    /// Cancels the child tasks in the order they were spawned, which is the default
+    #[default]
+    Spawned,
+    /// Cancels the child tasks of the lowest priority first,
+    /// which leaves the most important ones the most time to wind down
+    LowestPriorityFirst,
+    /// Cancels the child tasks of the highest priority first
+    HighestPriorityFirst,
+}
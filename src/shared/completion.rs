@@ -0,0 +1,10 @@
+/// Completion
+///
+/// Tells whether the results collected from a spawn group are all of its child tasks' results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Completion {
+    /// Every child task ran to the end
+    Finished,
+    /// The spawn group was cancelled, so the results may be partial
+    Cancelled,
+}
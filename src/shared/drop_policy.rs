@@ -0,0 +1,73 @@
+/// Drop Policy
+///
+/// Decides what a spawn group does with its still running child tasks when it gets dropped or closed.
+///
+/// Closing a spawn group with ``close()`` is the recommended teardown in asynchronous code,
+/// dropping it is the safety net for when it wasn't closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DropPolicy {
+    /// Blocks until every child task finishes, which is the default.
+    ///
+    /// Child tasks are cancelled instead when the spawn group gets dropped while its thread panics
+    #[default]
+    WaitAll,
+    /// Cancels every child task that is still running
+    Cancel,
+    /// Leaves every child task running in the background and discards their results as they come in,
+    /// then lets the spawn group's threads exit once every child task finished
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{spawn_group::SpawnGroupBuilder, DropPolicy, Priority};
+    /// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// struct Discarded(Arc<AtomicUsize>);
+    ///
+    /// impl Drop for Discarded {
+    ///     fn drop(&mut self) {
+    ///         self.0.fetch_add(1, Ordering::SeqCst);
+    ///     }
+    /// }
+    ///
+    /// let discarded = Arc::new(AtomicUsize::new(0));
+    /// let mut group = SpawnGroupBuilder::new()
+    ///     .threads(2)
+    ///     .thread_name("detached")
+    ///     .max_buffered(1)
+    ///     .drop_policy(DropPolicy::Detach)
+    ///     .build();
+    /// for _ in 0..5 {
+    ///     let discarded = discarded.clone();
+    ///     group.spawn_task(Priority::default(), async move { Discarded(discarded) });
+    /// }
+    /// drop(group);
+    ///
+    /// // the buffer only holds a single result, so the others are only stored once that one was discarded
+    /// let now = Instant::now();
+    /// while discarded.load(Ordering::SeqCst) < 5 {
+    ///     assert!(now.elapsed() < Duration::from_secs(5));
+    ///     std::thread::sleep(Duration::from_millis(10));
+    /// }
+    ///
+    /// # #[cfg(target_os = "linux")]
+    /// # {
+    /// let detached_threads = || {
+    ///     std::fs::read_dir("/proc/self/task")
+    ///         .unwrap()
+    ///         .filter_map(Result::ok)
+    ///         .filter(|thread| {
+    ///             let name = std::fs::read_to_string(thread.path().join("comm")).unwrap_or_default();
+    ///             name.starts_with("detached")
+    ///         })
+    ///         .count()
+    /// };
+    /// while detached_threads() > 0 {
+    ///     assert!(now.elapsed() < Duration::from_secs(5));
+    ///     std::thread::sleep(Duration::from_millis(10));
+    /// }
+    /// # }
+    /// ```
+    Detach,
+}
@@ -0,0 +1,65 @@
+/// Generates an enum whose variants wrap the result types of differently typed child tasks,
+/// so they can share a single spawn group.
+///
+/// Every variant wraps exactly one type, and the enum implements ``From`` for each of them,
+/// which lets ``SpawnGroup::spawn_task_into`` spawn a child task returning any of those types
+/// without wrapping its result by hand. The wrapped types must therefore be distinct.
+///
+/// Attributes such as derives are passed on to the enum.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{spawn_group_enum, with_spawn_group, Priority};
+/// use futures_lite::StreamExt;
+///
+/// spawn_group_enum! {
+///     #[derive(Debug, PartialEq)]
+///     pub enum Either3 {
+///         A(u32),
+///         B(String),
+///         C(bool),
+///     }
+/// }
+///
+/// # spawn_groups::block_on(async move {
+/// let results = with_spawn_group(|mut group| async move {
+///     group.spawn_task_into(Priority::HIGH, async { 1_u32 });
+///     group.spawn_task_into(Priority::MEDIUM, async { "two".to_string() });
+///     group.spawn_task_into(Priority::LOW, async { true });
+///
+///     let mut results: Vec<Either3> = vec![];
+///     while let Some(result) = group.next().await {
+///         results.push(result);
+///     }
+///     results
+/// }).await;
+///
+/// assert_eq!(results.len(), 3);
+/// assert!(results.contains(&Either3::A(1)));
+/// assert!(results.contains(&Either3::B("two".to_string())));
+/// assert!(results.contains(&Either3::C(true)));
+/// # });
+/// ```
+#[macro_export]
+macro_rules! spawn_group_enum {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $($variant:ident($ty:ty)),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $($variant($ty)),+
+        }
+
+        $(
+            impl ::std::convert::From<$ty> for $name {
+                fn from(value: $ty) -> Self {
+                    $name::$variant(value)
+                }
+            }
+        )+
+    };
+}
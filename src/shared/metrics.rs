@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+/// Metrics Recorder
+///
+/// Receives events about the child tasks of the spawn group it's registered on with ``set_metrics_recorder()``,
+/// so they can be forwarded to a metrics library without this crate depending on one.
+///
+/// Every method does nothing unless overridden, and is called on whichever thread the event happens on.
+pub trait MetricsRecorder: Send + Sync {
+    /// Called whenever a child task is spawned
+    fn on_spawn(&self) {}
+
+    /// Called whenever a child task returns, with how long ago it was spawned
+    fn on_complete(&self, duration: Duration) {
+        _ = duration;
+    }
+
+    /// Called whenever a child task is cancelled before it returned
+    fn on_cancel(&self) {}
+
+    /// Called whenever a child task panics
+    fn on_panic(&self) {}
+}
+
+/// The recorder spawn groups start with, which ignores every event
+pub(crate) struct NoopRecorder;
+
+impl MetricsRecorder for NoopRecorder {}
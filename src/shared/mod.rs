@@ -1,5 +1,25 @@
+pub(crate) mod cancel_order;
+pub(crate) mod completion;
+pub(crate) mod drop_policy;
+mod group_enum;
 pub(crate) mod initializible;
+pub(crate) mod metrics;
+pub(crate) mod panic_policy;
 pub(crate) mod priority;
+pub(crate) mod queue_snapshot;
+pub(crate) mod rate_limiter;
 pub(crate) mod runtime;
+pub(crate) mod saturation_policy;
+pub(crate) mod scheduling_policy;
+pub(crate) mod scope;
+pub(crate) mod semaphore;
 pub(crate) mod sharedfuncs;
+pub(crate) mod spawner;
+pub(crate) mod spawn_error;
+#[cfg(feature = "stats")]
+pub(crate) mod stats;
+pub(crate) mod task_id;
+pub(crate) mod task_info;
+pub(crate) mod task_result;
+pub(crate) mod task_status;
 pub(crate) mod wait;
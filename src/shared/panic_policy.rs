@@ -0,0 +1,17 @@
+/// Panic Policy
+///
+/// Decides what a spawn group does when one of its child tasks panics.
+///
+/// Either way, the panicking child task stores no result and the thread it ran on survives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PanicPolicy {
+    /// Swallows the panic and keeps the other child tasks running, which is the default
+    #[default]
+    Isolate,
+    /// Keeps the other child tasks running and resumes the earliest panic
+    /// once the spawn group waits for all its child tasks
+    Propagate,
+    /// Cancels the spawn group as soon as a child task panics, aborting every other child task unless it already finished,
+    /// so the spawn group fails fast. The grace period is skipped, since the panicking child task is still winding down
+    CancelAll,
+}
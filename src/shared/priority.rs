@@ -1,13 +1,80 @@
+use std::cmp::Ordering;
+
 /// Task Priority
 ///
 /// Spawn groups uses it to rank the importance of their spawned tasks and order of returned values only when waited for.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+///
+/// Spawn groups wait for child tasks of a higher priority first,
+/// and for child tasks of the same priority in the order they were spawned.
+///
+/// The named priorities rank from `BACKGROUND` as 0 up to `USERINITIATED` as 5.
+/// A `Custom` priority ranks by its value against them, just above a named priority of the same value.
+///
+/// More priorities may be added later, so a `match` on a priority needs a wildcard arm.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::Priority;
+///
+/// assert!(Priority::Custom(-1) < Priority::BACKGROUND);
+/// assert!(Priority::MEDIUM < Priority::Custom(3));
+/// assert!(Priority::Custom(3) < Priority::HIGH);
+/// assert!(Priority::USERINITIATED < Priority::Custom(1_000));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
 pub enum Priority {
-    BACKGROUND = 0,
+    BACKGROUND,
     LOW,
     UTILITY,
     #[default]
     MEDIUM,
     HIGH,
     USERINITIATED,
+    Custom(i32),
+}
+
+impl Priority {
+    fn rank(&self) -> (i32, bool) {
+        match self {
+            Priority::BACKGROUND => (0, false),
+            Priority::LOW => (1, false),
+            Priority::UTILITY => (2, false),
+            Priority::MEDIUM => (3, false),
+            Priority::HIGH => (4, false),
+            Priority::USERINITIATED => (5, false),
+            Priority::Custom(value) => (*value, true),
+        }
+    }
+}
+
+impl Priority {
+    /// The priority ``levels`` ranks above this one, which stays a named priority while there is one that high
+    pub(crate) fn boosted(&self, levels: i32) -> Priority {
+        match self.rank() {
+            (rank, false) => match rank.saturating_add(levels) {
+                0 => Priority::BACKGROUND,
+                1 => Priority::LOW,
+                2 => Priority::UTILITY,
+                3 => Priority::MEDIUM,
+                4 => Priority::HIGH,
+                5 => Priority::USERINITIATED,
+                rank => Priority::Custom(rank),
+            },
+            (rank, true) => Priority::Custom(rank.saturating_add(levels)),
+        }
+    }
+}
+
+impl PartialOrd for Priority {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Priority {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rank().cmp(&other.rank())
+    }
 }
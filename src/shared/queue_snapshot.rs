@@ -0,0 +1,62 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// Queue Snapshot
+///
+/// How deep the queues of a spawn group were at one point in time,
+/// as reported to the callback given to its ``sample_queue_depth()`` method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct QueueSnapshot {
+    /// The number of child tasks that haven't finished yet
+    pub pending_tasks: usize,
+    /// The number of child tasks waiting for one of the spawn group's threads, which is always zero with the ``tokio`` feature
+    pub pool_queued: usize,
+    /// The number of threads of the spawn group running a child task
+    pub in_flight: usize,
+}
+
+/// A background thread calling a callback with a snapshot at a fixed interval until stopped
+pub(crate) struct Sampler {
+    stopped: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl Sampler {
+    pub(crate) fn start<F>(interval: Duration, sample: F) -> Self
+    where
+        F: Fn() + Send + 'static,
+    {
+        let stopped: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let stopped_flag: Arc<AtomicBool> = stopped.clone();
+        let thread: JoinHandle<()> = std::thread::spawn(move || {
+            while !stopped_flag.load(Ordering::Acquire) {
+                sample();
+                // parking may wake up early, either spuriously or because the sampler was stopped
+                let next: Instant = Instant::now() + interval;
+                while !stopped_flag.load(Ordering::Acquire) {
+                    let Some(remaining) = next.checked_duration_since(Instant::now()) else {
+                        break;
+                    };
+                    std::thread::park_timeout(remaining);
+                }
+            }
+        });
+        Self { stopped, thread }
+    }
+
+    /// Stops the sampler and waits for a callback in progress to return,
+    /// unless it's the callback itself stopping the sampler
+    pub(crate) fn stop(self) {
+        self.stopped.store(true, Ordering::Release);
+        self.thread.thread().unpark();
+        if self.thread.thread().id() != std::thread::current().id() {
+            _ = self.thread.join();
+        }
+    }
+}
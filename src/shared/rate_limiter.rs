@@ -0,0 +1,40 @@
+use crate::sleep;
+use parking_lot::Mutex;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Paces the starts of child tasks to an even rate by handing each of them its own start slot,
+/// so a burst of child tasks gets spread out instead of starting all at once
+#[derive(Clone)]
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    next_slot: Arc<Mutex<Option<Instant>>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(per_second: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / per_second,
+            next_slot: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Waits until the next free start slot, which is at least one interval after the previously handed out one
+    pub(crate) async fn wait_for_slot(&self) {
+        let now: Instant = Instant::now();
+        let slot: Instant = {
+            let mut next_slot = self.next_slot.lock();
+            // an idle limiter doesn't save up slots, so starts never burst after a pause
+            let slot: Instant = next_slot.map_or(now, |next_slot| next_slot.max(now));
+            *next_slot = Some(slot + self.interval);
+            slot
+        };
+        if slot > now {
+            sleep(slot - now).await;
+        }
+    }
+}
@@ -1,75 +1,977 @@
+#[cfg(feature = "stats")]
+use crate::shared::stats::{Stats, TaskStats};
 use crate::{
-    async_runtime::{executor::Executor, task::Task},
-    async_stream::AsyncStream,
-    executors::block_task,
-    shared::{initializible::Initializible, priority::Priority},
+    async_stream::{AsyncStream, Buffered},
+    cancellation_token::CancellationToken,
+    executors::{DefaultSpawner, Spawner},
+    shared::{
+        cancel_order::CancelOrder,
+        completion::Completion,
+        initializible::Initializible,
+        metrics::{MetricsRecorder, NoopRecorder},
+        panic_policy::PanicPolicy,
+        priority::Priority,
+        queue_snapshot::{QueueSnapshot, Sampler},
+        rate_limiter::RateLimiter,
+        saturation_policy::SaturationPolicy,
+        scheduling_policy::SchedulingPolicy,
+        semaphore::{Permit, Semaphore},
+        spawn_error::SpawnError,
+        task_id::TaskId,
+        task_info::TaskInfo,
+        task_result::{self, ResultReceiver, ResultSender, TaskResult},
+        task_status::TaskStatus,
+    },
+    sleep,
 };
+use futures_lite::{future, FutureExt, Stream, StreamExt};
 use parking_lot::Mutex;
 use std::{
+    any::Any,
+    cmp::Reverse,
+    collections::HashMap,
     future::Future,
+    panic::AssertUnwindSafe,
+    pin::pin,
     sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Weak,
     },
+    task::Poll,
+    time::{Duration, Instant},
 };
 
-type TaskQueue = Arc<Mutex<Vec<(Priority, Task)>>>;
+type TaskQueue<Handle> = Arc<Mutex<Vec<TrackedTask<Handle>>>>;
+type PanicQueue = Arc<Mutex<Vec<Box<dyn Any + Send>>>>;
+type Observer<ItemType> = Arc<dyn Fn(&ItemType) + Send + Sync>;
+type Observers<ItemType> = Arc<Mutex<Vec<Observer<ItemType>>>>;
+type StartObserver = Arc<dyn Fn(TaskId) + Send + Sync>;
+type StartObservers = Arc<Mutex<Vec<StartObserver>>>;
+type Recorder = Arc<Mutex<Arc<dyn MetricsRecorder>>>;
+/// The status of every child task still tracked, or which stopped since the child tasks were last taken,
+/// by its sequence number
+type Statuses = Arc<Mutex<HashMap<u64, TaskStatus>>>;
+/// Where the results of child tasks go instead of the buffer, by their sequence number
+#[cfg(feature = "futures")]
+type Routes<ItemType> = Arc<Mutex<HashMap<u64, ResultSender<ItemType>>>>;
+
+/// A spawned child task along with what it was spawned with
+struct TrackedTask<Handle> {
+    priority: Priority,
+    sequence: u64,
+    handle: Handle,
+    name: Option<String>,
+    spawned_at: Instant,
+    finished: Arc<AtomicBool>,
+    settled: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+}
+
+/// Stores the result of a child task, and marks it as stopped once its future is dropped, however it ended,
+/// and wakes the waiters
+struct StopGuard<ItemType> {
+    sequence: u64,
+    stopped: Arc<AtomicBool>,
+    stream: AsyncStream<ItemType>,
+    #[cfg(feature = "futures")]
+    routes: Routes<ItemType>,
+}
+
+impl<ItemType> StopGuard<ItemType> {
+    /// Stores the child task's result in the buffer, or sends it down the route ``route_results`` set up for the child task
+    async fn store(&self, priority: Priority, item: ItemType, latency: Duration) {
+        #[cfg(feature = "futures")]
+        let item: ItemType = match self.take_route() {
+            Some(sender) => {
+                sender.send(item);
+                // the result counts as consumed once it was sent
+                self.stream.decrement_count();
+                return;
+            }
+            None => item,
+        };
+        self.stream
+            .insert_item(self.sequence, priority, item, latency)
+            .await;
+        // a route set up while the result was on its way into the buffer didn't find it there yet
+        #[cfg(feature = "futures")]
+        if let Some(sender) = self.take_route() {
+            let taken: Vec<(u64, ItemType)> = self
+                .stream
+                .take_items(|sequence| sequence == self.sequence)
+                .await;
+            if let Some((_, item)) = taken.into_iter().next() {
+                sender.send(item);
+            }
+        }
+    }
+
+    /// Removes the child task's route, without holding the routes any longer than that
+    #[cfg(feature = "futures")]
+    fn take_route(&self) -> Option<ResultSender<ItemType>> {
+        self.routes.lock().remove(&self.sequence)
+    }
+}
+
+impl<ItemType> Drop for StopGuard<ItemType> {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Release);
+        // marked as stopped first, so a route added afterwards never waits for this child task,
+        // while a route left over means the child task stored no result through ``store``, like a stream child task
+        #[cfg(feature = "futures")]
+        if let Some(sender) = self.take_route() {
+            // a consumer holding the buffer right now leaves the result buffered rather than blocking a backend thread
+            let taken: Vec<(u64, ItemType)> = self
+                .stream
+                .try_take_items(|sequence| sequence == self.sequence)
+                .unwrap_or_default();
+            if let Some((_, item)) = taken.into_iter().next() {
+                sender.send(item);
+            }
+        }
+        self.stream.wake_consumers();
+    }
+}
 
-pub struct RuntimeEngine<ItemType> {
-    tasks: TaskQueue,
-    runtime: Executor,
+pub struct RuntimeEngine<ItemType, S: Spawner = DefaultSpawner> {
+    tasks: TaskQueue<S::JoinHandle>,
+    runtime: S,
     stream: AsyncStream<ItemType>,
     wait_flag: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+    closed: Arc<AtomicBool>,
+    semaphore: Arc<Mutex<Option<Semaphore>>>,
+    concurrency_limits: Arc<Mutex<HashMap<Priority, Semaphore>>>,
+    aging: Arc<Mutex<Duration>>,
+    scheduling_policy: Arc<Mutex<SchedulingPolicy>>,
+    rate_limiter: Arc<Mutex<Option<RateLimiter>>>,
+    saturation_policy: Arc<Mutex<SaturationPolicy>>,
+    token: CancellationToken,
+    grace_period: Arc<Mutex<Duration>>,
+    cancel_order: Arc<Mutex<CancelOrder>>,
+    panics: PanicQueue,
+    panicked: Arc<AtomicUsize>,
+    panic_policy: Arc<Mutex<PanicPolicy>>,
+    observers: Observers<ItemType>,
+    start_observers: StartObservers,
+    recorder: Recorder,
+    samplers: Arc<Mutex<Vec<Sampler>>>,
+    statuses: Statuses,
+    keep_cancelled_results: Arc<AtomicBool>,
+    #[cfg(feature = "futures")]
+    routes: Routes<ItemType>,
+    #[cfg(feature = "stats")]
+    stats: Stats,
 }
 
-impl<ItemType> Initializible for RuntimeEngine<ItemType> {
+impl<ItemType, S: Spawner> Initializible for RuntimeEngine<ItemType, S> {
     fn init() -> Self {
+        Self::init_with_threads(0, None)
+    }
+}
+
+impl<ItemType, S: Spawner> RuntimeEngine<ItemType, S> {
+    pub(crate) fn init_with_threads(thread_count: usize, thread_name: Option<&str>) -> Self {
         Self {
             tasks: Arc::new(Mutex::new(vec![])),
             stream: AsyncStream::new(),
-            runtime: Executor::default(),
+            runtime: S::with_threads(thread_count, thread_name),
             wait_flag: Arc::new(AtomicBool::new(false)),
+            cancelled: Arc::new(AtomicBool::new(false)),
+            closed: Arc::new(AtomicBool::new(false)),
+            semaphore: Arc::new(Mutex::new(None)),
+            concurrency_limits: Arc::new(Mutex::new(HashMap::new())),
+            aging: Arc::new(Mutex::new(Duration::ZERO)),
+            scheduling_policy: Arc::new(Mutex::new(SchedulingPolicy::default())),
+            rate_limiter: Arc::new(Mutex::new(None)),
+            saturation_policy: Arc::new(Mutex::new(SaturationPolicy::default())),
+            token: CancellationToken::current()
+                .map_or_else(CancellationToken::new, |parent| parent.child_token()),
+            grace_period: Arc::new(Mutex::new(Duration::ZERO)),
+            cancel_order: Arc::new(Mutex::new(CancelOrder::default())),
+            panics: Arc::new(Mutex::new(vec![])),
+            panicked: Arc::new(AtomicUsize::new(0)),
+            panic_policy: Arc::new(Mutex::new(PanicPolicy::default())),
+            observers: Arc::new(Mutex::new(vec![])),
+            start_observers: Arc::new(Mutex::new(vec![])),
+            recorder: Arc::new(Mutex::new(Arc::new(NoopRecorder))),
+            samplers: Arc::new(Mutex::new(vec![])),
+            statuses: Arc::new(Mutex::new(HashMap::new())),
+            keep_cancelled_results: Arc::new(AtomicBool::new(false)),
+            #[cfg(feature = "futures")]
+            routes: Arc::new(Mutex::new(HashMap::new())),
+            #[cfg(feature = "stats")]
+            stats: Stats::default(),
         }
     }
 }
 
-impl<ItemType> RuntimeEngine<ItemType> {
+impl<ItemType, S: Spawner> Clone for RuntimeEngine<ItemType, S> {
+    /// Clones share the same backend, child tasks, stream and configuration with the original,
+    /// so waiting on, cancelling or configuring any of them affects child tasks spawned through all of them
+    fn clone(&self) -> Self {
+        Self {
+            tasks: self.tasks.clone(),
+            runtime: self.runtime.clone(),
+            stream: self.stream.clone(),
+            wait_flag: self.wait_flag.clone(),
+            cancelled: self.cancelled.clone(),
+            closed: self.closed.clone(),
+            semaphore: self.semaphore.clone(),
+            concurrency_limits: self.concurrency_limits.clone(),
+            aging: self.aging.clone(),
+            scheduling_policy: self.scheduling_policy.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            saturation_policy: self.saturation_policy.clone(),
+            token: self.token.clone(),
+            grace_period: self.grace_period.clone(),
+            cancel_order: self.cancel_order.clone(),
+            panics: self.panics.clone(),
+            panicked: self.panicked.clone(),
+            panic_policy: self.panic_policy.clone(),
+            observers: self.observers.clone(),
+            start_observers: self.start_observers.clone(),
+            recorder: self.recorder.clone(),
+            samplers: self.samplers.clone(),
+            statuses: self.statuses.clone(),
+            keep_cancelled_results: self.keep_cancelled_results.clone(),
+            #[cfg(feature = "futures")]
+            routes: self.routes.clone(),
+            #[cfg(feature = "stats")]
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl<ItemType, S: Spawner> RuntimeEngine<ItemType, S> {
+    /// Cancels the child tasks, blocking the caller's thread for the grace period
+    /// while the child tasks observing the cancellation token finish on their own
     pub(crate) fn cancel(&mut self) {
-        self.store(true);
-        self.runtime.cancel();
-        self.tasks.lock().clear();
+        self.signal_cancellation();
+        self.wait_for_grace_period();
+        self.cancel_remaining_tasks();
+    }
+
+    /// Cancels like ``cancel`` does, but waits for the grace period on the caller's executor
+    pub(crate) async fn cancel_async(&mut self) {
+        self.signal_cancellation();
+        self.wait_for_grace_period_async().await;
+        self.cancel_remaining_tasks();
+    }
+
+    /// Tells the backend to stop the child tasks still running once the grace period is over
+    fn cancel_remaining_tasks(&mut self) {
+        let handles: Vec<S::JoinHandle> = self
+            .take_cancelled_tasks()
+            .into_iter()
+            .map(|(handle, _)| handle)
+            .collect();
+        self.runtime.cancel(handles);
         self.stream.cancel_tasks();
         self.poll();
     }
+
+    /// Cancels like ``cancel`` does, but waits for the grace period on the caller's executor, and until every cancelled child task
+    /// actually stopped, rather than leaving the backend to stop them afterwards.
+    ///
+    /// Each child task is only cancelled once the one before it in the cancel order stopped,
+    /// so their futures are dropped in that order
+    pub(crate) async fn cancel_and_wait(&mut self) {
+        self.signal_cancellation();
+        self.wait_for_grace_period_async().await;
+        let mut handles: Vec<S::JoinHandle> = vec![];
+        for (handle, stopped) in self.take_cancelled_tasks() {
+            self.runtime.abort(&handle);
+            // a child task being polled right now only stops once that poll returns
+            self.stream
+                .wait_until(|| stopped.load(Ordering::Acquire))
+                .await;
+            handles.push(handle);
+        }
+        self.runtime.cancel(handles);
+        self.stream.cancel_tasks();
+        self.poll();
+    }
+
+    /// Marks the engine as cancelled and cancels its token, which starts the grace period
+    fn signal_cancellation(&self) {
+        self.cancelled.store(true, Ordering::Release);
+        self.token.cancel();
+    }
+
+    /// Stops tracking the child tasks once the grace period is over,
+    /// returning their handles along with their stopped flags in the cancel order
+    fn take_cancelled_tasks(&mut self) -> Vec<(S::JoinHandle, Arc<AtomicBool>)> {
+        self.store(true);
+        let recorder: Arc<dyn MetricsRecorder> = self.recorder.lock().clone();
+        let mut tasks: Vec<TrackedTask<S::JoinHandle>> = std::mem::take(&mut *self.tasks.lock());
+        self.sort_in_cancel_order(&mut tasks);
+        tasks
+            .into_iter()
+            .map(|task| {
+                if !task.finished.swap(true, Ordering::AcqRel) {
+                    recorder.on_cancel();
+                    set_status(&self.statuses, task.sequence, TaskStatus::Cancelled);
+                }
+                (task.handle, task.stopped)
+            })
+            .collect()
+    }
+
+    fn sort_in_cancel_order(&self, tasks: &mut [TrackedTask<S::JoinHandle>]) {
+        sort_in_cancel_order(*self.cancel_order.lock(), tasks);
+    }
+
+    pub(crate) fn set_metrics_recorder(&self, recorder: Arc<dyn MetricsRecorder>) {
+        *self.recorder.lock() = recorder;
+    }
+
+    #[cfg(feature = "stats")]
+    pub(crate) fn stats(&self) -> TaskStats {
+        self.stats.snapshot()
+    }
 }
 
-impl<ItemType> RuntimeEngine<ItemType> {
+impl<ItemType, S: Spawner> RuntimeEngine<ItemType, S> {
     pub(crate) fn stream(&self) -> AsyncStream<ItemType> {
         self.stream.clone()
     }
 
-    pub(crate) fn end(&mut self) {
-        self.runtime.cancel();
-        self.tasks.lock().clear();
+    pub(crate) fn set_max_buffered(&self, max_buffered: usize) {
+        self.stream.set_max_buffered(max_buffered);
+    }
+
+    #[cfg(feature = "deterministic")]
+    pub(crate) fn set_drain_seed(&self, seed: u64) {
+        self.stream.set_drain_seed(seed);
+    }
+
+    pub(crate) fn set_max_concurrency(&mut self, max_concurrency: usize) {
+        let aging: Duration = *self.aging.lock();
+        let scheduling_policy = self.scheduling_policy.lock();
+        *self.semaphore.lock() = (max_concurrency > 0)
+            .then(|| Semaphore::new(max_concurrency, aging, &scheduling_policy));
+    }
+
+    /// Limits the child tasks of each priority in ``limits`` on their own, where zero means no limit,
+    /// replacing the limits set before for the child tasks spawned afterwards
+    pub(crate) fn set_concurrency_limits(&mut self, limits: HashMap<Priority, usize>) {
+        *self.concurrency_limits.lock() = limits
+            .into_iter()
+            .filter(|(_, limit)| *limit > 0)
+            .map(|(priority, limit)| {
+                // every waiter has the same priority, so neither aging nor fairness changes anything
+                let semaphore: Semaphore =
+                    Semaphore::new(limit, Duration::ZERO, &SchedulingPolicy::Strict);
+                (priority, semaphore)
+            })
+            .collect();
+    }
+
+    /// Picks which child task waiting for the concurrency limit runs next by the scheduling policy
+    pub(crate) fn set_scheduling_policy(&mut self, scheduling_policy: SchedulingPolicy) {
+        if let Some(semaphore) = &*self.semaphore.lock() {
+            semaphore.set_scheduling_policy(&scheduling_policy);
+        }
+        *self.scheduling_policy.lock() = scheduling_policy;
+    }
+
+    /// Raises the priority of child tasks waiting for the concurrency limit a level for every ``rate`` they waited,
+    /// where zero turns it off
+    pub(crate) fn set_aging(&mut self, rate: Duration) {
+        *self.aging.lock() = rate;
+        if let Some(semaphore) = &*self.semaphore.lock() {
+            semaphore.set_aging(rate);
+        }
+    }
+
+    /// Takes a slot of the concurrency limit for the caller, queued after the child tasks of the same priority
+    /// spawned so far, and right away if there is no concurrency limit
+    pub(crate) async fn acquire_permit(&self, priority: Priority) -> Permit {
+        let semaphore: Option<Semaphore> = self.semaphore.lock().clone();
+        match semaphore {
+            Some(semaphore) => {
+                let latest: u64 = self.stream.next_sequence().saturating_sub(1);
+                semaphore.acquire_outside(priority, latest).await
+            }
+            None => Permit::unlimited(),
+        }
+    }
+
+    pub(crate) fn set_rate_limit(&mut self, per_second: u32) {
+        *self.rate_limiter.lock() = (per_second > 0).then(|| RateLimiter::new(per_second));
+    }
+
+    pub(crate) fn set_saturation_policy(&mut self, saturation_policy: SaturationPolicy) {
+        *self.saturation_policy.lock() = saturation_policy;
+    }
+
+    /// Whether child tasks are already waiting for one of the backend's threads
+    fn is_saturated(&self) -> bool {
+        self.runtime.queued_count() > 0
+    }
+
+    /// Applies the saturation policy to a new child task, and returns whether it may be spawned
+    fn wait_for_capacity(&self) -> bool {
+        let saturation_policy: SaturationPolicy = *self.saturation_policy.lock();
+        match saturation_policy {
+            SaturationPolicy::Queue => true,
+            SaturationPolicy::Reject => !self.is_saturated(),
+            SaturationPolicy::Block => {
+                crate::block_on(self.wait_for_free_thread());
+                true
+            }
+        }
+    }
+
+    /// Applies the saturation policy like ``wait_for_capacity`` does,
+    /// but waits on the caller's executor rather than blocking its thread under ``SaturationPolicy::Block``
+    async fn wait_for_capacity_async(&self) -> bool {
+        if *self.saturation_policy.lock() != SaturationPolicy::Block {
+            return self.wait_for_capacity();
+        }
+        self.wait_for_free_thread().await;
+        true
+    }
+
+    /// Waits until no child task waits for one of the backend's threads, woken by the backend whenever one of its threads
+    /// takes a waiting child task, or until the engine was closed, which is noticed on the next of those wakeups
+    async fn wait_for_free_thread(&self) {
+        future::poll_fn(|cx| match self.is_closed() {
+            true => Poll::Ready(()),
+            false => self.runtime.poll_capacity(cx),
+        })
+        .await
+    }
+
+    pub(crate) fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    pub(crate) fn set_cancel_order(&mut self, cancel_order: CancelOrder) {
+        *self.cancel_order.lock() = cancel_order;
+    }
+
+    pub(crate) fn set_grace_period(&mut self, grace_period: Duration) {
+        *self.grace_period.lock() = grace_period;
+    }
+
+    /// Gives child tasks that observe the cancellation token the grace period to finish on their own,
+    /// blocking the caller's thread meanwhile, which child tasks wake as they finish
+    fn wait_for_grace_period(&self) {
+        let grace_period: Duration = *self.grace_period.lock();
+        if grace_period.is_zero() {
+            return;
+        }
+        crate::executors::block_on_until(
+            self.stream.wait_until(|| self.stream.task_count() == 0),
+            Instant::now() + grace_period,
+        );
+    }
+
+    /// Gives child tasks the grace period like ``wait_for_grace_period`` does, but waits on the caller's executor
+    async fn wait_for_grace_period_async(&self) {
+        let grace_period: Duration = *self.grace_period.lock();
+        if grace_period.is_zero() {
+            return;
+        }
+        future::or(
+            self.stream.wait_until(|| self.stream.task_count() == 0),
+            sleep(grace_period),
+        )
+        .await
+    }
+
+    pub(crate) fn was_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Rejects every child task spawned afterwards, leaving the already spawned ones running
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn completion(&self) -> Completion {
+        match self.was_cancelled() {
+            true => Completion::Cancelled,
+            false => Completion::Finished,
+        }
+    }
+
+    /// Allocates room for ``additional`` more child tasks and their results,
+    /// so spawning them doesn't reallocate while holding the locks
+    pub(crate) fn reserve(&self, additional: usize) {
+        self.tasks.lock().reserve(additional);
+        self.statuses.lock().reserve(additional);
+        // only an optimization, so it never waits on a consumer holding the buffer from an async context
+        self.stream.try_reserve(additional);
+    }
+
+    pub(crate) fn set_panic_policy(&mut self, panic_policy: PanicPolicy) {
+        *self.panic_policy.lock() = panic_policy;
+    }
+
+    pub(crate) fn panicked_count(&self) -> usize {
+        self.panicked.load(Ordering::Acquire)
+    }
+
+    /// Resumes the earliest recorded panic when the panic policy asks for it
+    fn propagate_panic(&self) {
+        if *self.panic_policy.lock() != PanicPolicy::Propagate || std::thread::panicking() {
+            return;
+        }
+        if let Some(payload) = self.take_panic() {
+            std::panic::resume_unwind(payload);
+        }
+    }
+
+    /// Removes the earliest recorded panic
+    fn take_panic(&self) -> Option<Box<dyn Any + Send>> {
+        let mut panics = self.panics.lock();
+        match panics.is_empty() {
+            true => None,
+            false => Some(panics.remove(0)),
+        }
+    }
+
+    /// Stops tracking the child tasks, and forgets the statuses of those which already stopped,
+    /// so the statuses don't pile up over the rounds of a long lived engine
+    fn take_tasks(&self) -> Vec<S::JoinHandle> {
+        self.take_tracked_tasks()
+            .into_iter()
+            .map(|task| task.handle)
+            .collect()
+    }
+
+    /// Stops tracking the child tasks like ``take_tasks`` does, returning everything they were tracked with
+    fn take_tracked_tasks(&self) -> Vec<TrackedTask<S::JoinHandle>> {
+        let tasks: Vec<TrackedTask<S::JoinHandle>> = std::mem::take(&mut *self.tasks.lock());
+        self.statuses
+            .lock()
+            .retain(|_, status| matches!(status, TaskStatus::Queued | TaskStatus::Running));
+        tasks
+    }
+
+    /// Orders the child tasks by descending priority, then by the ascending order they were spawned in
+    fn sort_tasks(&self) {
+        self.tasks.lock().sort_by(|lhs, rhs| {
+            rhs.priority
+                .cmp(&lhs.priority)
+                .then(lhs.sequence.cmp(&rhs.sequence))
+        });
+    }
+
+    pub(crate) fn debug_snapshot(&self) -> Vec<TaskInfo> {
+        self.sort_tasks();
+        self.tasks
+            .lock()
+            .iter()
+            .filter(|task| !task.finished.load(Ordering::Acquire))
+            .map(|task| TaskInfo {
+                name: task.name.clone(),
+                priority: task.priority,
+                age: task.spawned_at.elapsed(),
+            })
+            .collect()
+    }
+
+    pub(crate) fn set_thread_count(&self, thread_count: usize) -> bool {
+        self.runtime.set_thread_count(thread_count)
+    }
+
+    pub(crate) fn pool_active(&self) -> usize {
+        self.runtime.active_count()
+    }
+
+    pub(crate) fn pool_queued(&self) -> usize {
+        self.runtime.queued_count()
+    }
+
+    /// Stops every sampler started by ``sample_queue_depth``
+    pub(crate) fn stop_sampling(&self) {
+        let samplers: Vec<Sampler> = std::mem::take(&mut *self.samplers.lock());
+        for sampler in samplers {
+            sampler.stop();
+        }
+    }
+}
+
+impl<ItemType: Send + 'static, S: Spawner> RuntimeEngine<ItemType, S> {
+    /// Starts a sampler reporting the depth of the queues to ``report`` every ``interval``
+    /// until ``stop_sampling`` is called
+    pub(crate) fn sample_queue_depth<F>(&self, interval: Duration, report: F)
+    where
+        F: Fn(QueueSnapshot) + Send + 'static,
+    {
+        // holds onto the counts only, so the sampler doesn't keep the engine itself alive
+        let (stream, runtime) = (self.stream.clone(), self.runtime.clone());
+        let sampler: Sampler = Sampler::start(interval, move || {
+            report(QueueSnapshot {
+                pending_tasks: stream.task_count(),
+                pool_queued: runtime.queued_count(),
+                in_flight: runtime.active_count(),
+            })
+        });
+        self.samplers.lock().push(sampler);
+    }
+}
+
+impl<ValueType: Send + 'static, ErrorType: Send + 'static, S: Spawner>
+    RuntimeEngine<Result<ValueType, ErrorType>, S>
+{
+    /// Spawns a child task which calls ``factory`` again after each error, up to ``retries`` more times,
+    /// sleeping for ``backoff`` in between, and only stores the result of its last attempt
+    pub(crate) fn write_retrying_task<F, Fut>(
+        &self,
+        priority: Priority,
+        retries: usize,
+        backoff: Duration,
+        factory: F,
+    ) -> TaskId
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<ValueType, ErrorType>> + Send + 'static,
+    {
+        self.write_task(priority, async move {
+            let mut result: Result<ValueType, ErrorType> = factory().await;
+            for _ in 0..retries {
+                if result.is_ok() {
+                    break;
+                }
+                if !backoff.is_zero() {
+                    sleep(backoff).await;
+                }
+                result = factory().await;
+            }
+            result
+        })
     }
 }
 
-impl<ValueType: Send + 'static> RuntimeEngine<ValueType> {
+impl<ValueType: Send + 'static, S: Spawner> RuntimeEngine<ValueType, S> {
+    /// Blocks until every child task finished, including the ones spawned while it waits
     pub(crate) fn wait_for_all_tasks(&self) {
-        self.poll();
-        self.runtime.cancel();
-        self.tasks.lock().sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+        // nothing drains the buffer while this thread blocks, so lift its limit in the meantime
+        let max_buffered: usize = self.stream.max_buffered();
+        self.stream.set_max_buffered(0);
+        let mut restart: bool = false;
+        loop {
+            self.poll();
+            self.sort_tasks();
+            self.store(true);
+            let handles: Vec<S::JoinHandle> = self.take_tasks();
+            if handles.is_empty() {
+                if self.stream.task_count() == 0 {
+                    break;
+                }
+                // a child task that was just spawned hasn't been tracked yet
+                std::thread::yield_now();
+                continue;
+            }
+            if restart {
+                self.runtime.restart();
+            }
+            self.runtime.block_on(handles);
+            restart = true;
+        }
+        self.stream.set_max_buffered(max_buffered);
+        self.propagate_panic();
+    }
+
+    /// Stops tracking the child tasks while leaving them running, and hands them over to a background thread
+    /// which keeps the backend alive until they finish, discarding their results as they're stored,
+    /// then stops the backend's run loop so its threads exit once the engine is gone
+    pub(crate) fn detach_to_background(&mut self) {
+        // nothing consumes the results anymore, so child tasks must never wait on a full buffer
+        self.stream.set_max_buffered(0);
         self.store(true);
-        while let Some((_, handle)) = self.tasks.lock().pop() {
-            self.runtime.submit(move || {
-                block_task(handle);
-            });
+        let (handles, stopped): (Vec<S::JoinHandle>, Vec<Arc<AtomicBool>>) = self
+            .take_tracked_tasks()
+            .into_iter()
+            .map(|task| (task.handle, task.stopped))
+            .unzip();
+        if handles.is_empty() {
+            self.runtime.block_on(handles);
+            self.stream.cancel_tasks();
+            return;
+        }
+        let reaper: RuntimeEngine<ValueType, S> = self.clone();
+        std::thread::spawn(move || {
+            // the task count is zeroed below, so the stopped flags tell when the results stop coming
+            crate::block_on(reaper.stream.wait_until(|| {
+                // checked before draining, since child tasks store their result before they stop
+                let stopped: bool = stopped.iter().all(|flag| flag.load(Ordering::Acquire));
+                drop(crate::block_on(reaper.stream.drain_buffered_items()));
+                stopped
+            }));
+            reaper.runtime.block_on(handles);
+        });
+        self.stream.cancel_tasks();
+    }
+
+    /// Shares the condition ``wait_for_all_tasks_async`` waits for, on top of the buffer being drained
+    pub(crate) fn is_finished(&self) -> bool {
+        self.stream.task_count() == 0 && self.stream.try_is_drained()
+    }
+
+    /// Yields to the caller's executor until the child tasks finished, so the waiting composes with other futures
+    pub(crate) async fn wait_for_all_tasks_async(&self) {
+        self.stream
+            .wait_until(|| self.stream.task_count() == 0)
+            .await;
+        self.wait_for_all_tasks();
+    }
+
+    /// Waits until at least ``count`` child tasks completed, without consuming their results,
+    /// and returns false if no child task was left running before that
+    pub(crate) async fn wait_until_completed(&self, count: usize) -> bool {
+        future::poll_fn(|cx| self.stream.poll_completed(count, cx)).await
+    }
+
+    /// Drains the results until the child tasks finished or the timeout elapsed,
+    /// and returns them along with whether the child tasks finished
+    pub(crate) async fn drain_until(&self, timeout: Duration) -> (Vec<ValueType>, bool) {
+        let mut items: Vec<ValueType> = vec![];
+        let finished: bool = future::or(
+            self.drain_while_waiting(|drained| {
+                items.extend(drained.into_iter().map(|(_, _, item, _)| item));
+                true
+            }),
+            async {
+                sleep(timeout).await;
+                false
+            },
+        )
+        .await;
+        (items, finished)
+    }
+
+    /// Hands the results to ``drained`` in batches as they're stored until every child task finished,
+    /// and returns true then, or false once ``drained`` stops the waiting early by returning false
+    async fn drain_while_waiting(
+        &self,
+        mut drained: impl FnMut(Vec<Buffered<ValueType>>) -> bool,
+    ) -> bool {
+        future::poll_fn(|cx| {
+            let (items, finished) = self.stream.poll_drain(cx);
+            match (drained(items), finished) {
+                (false, _) => Poll::Ready(false),
+                (true, true) => Poll::Ready(true),
+                (true, false) => Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Forwards the results into the sink as they finish, only pulling the next one once the sink is ready for it,
+    /// then flushes the sink once every child task finished
+    #[cfg(feature = "sink")]
+    pub(crate) async fn drain_into<Si>(&self, mut sink: Si) -> Result<(), Si::Error>
+    where
+        Si: futures_sink::Sink<ValueType> + Unpin,
+    {
+        use std::pin::Pin;
+
+        let mut stream: AsyncStream<ValueType> = self.stream();
+        loop {
+            future::poll_fn(|cx| Pin::new(&mut sink).poll_ready(cx)).await?;
+            let Some(item) = stream.next().await else {
+                break;
+            };
+            Pin::new(&mut sink).start_send(item)?;
+        }
+        self.wait_for_all_tasks_async().await;
+        future::poll_fn(|cx| Pin::new(&mut sink).poll_flush(cx)).await
+    }
+
+    /// Sends the results down the channel as they finish, waiting for room in it, until every child task finished
+    /// and returns true, or until the receiver was dropped and returns false
+    #[cfg(feature = "channel")]
+    pub(crate) async fn forward_to(
+        &self,
+        mut tx: futures_channel::mpsc::Sender<ValueType>,
+    ) -> bool {
+        let mut stream: AsyncStream<ValueType> = self.stream();
+        loop {
+            if future::poll_fn(|cx| tx.poll_ready(cx)).await.is_err() {
+                return false;
+            }
+            // raced against the receiver going away, so a long running child task doesn't keep the group waiting for nobody
+            let next: Option<Option<ValueType>> =
+                future::or(async { Some(stream.next().await) }, async {
+                    // the channel only wakes senders waiting for room once the receiver is dropped, so it's checked periodically
+                    while !tx.is_closed() {
+                        sleep(Duration::from_millis(10)).await;
+                    }
+                    None
+                })
+                .await;
+            let Some(next) = next else {
+                return false;
+            };
+            let Some(item) = next else {
+                break;
+            };
+            if tx.start_send(item).is_err() {
+                return false;
+            }
+        }
+        self.wait_for_all_tasks_async().await;
+        true
+    }
+
+    /// Drains the results while waiting, so child tasks never wait on a full buffer
+    pub(crate) async fn wait_for_all(&self) -> Vec<ValueType> {
+        let items: Vec<ValueType> = self.stream().collect().await;
+        self.wait_for_all_tasks_async().await;
+        items
+    }
+
+    pub(crate) async fn wait_for_all_ordered(&self) -> Vec<ValueType> {
+        let mut items: Vec<(u64, ValueType)> = vec![];
+        self.drain_while_waiting(|drained| {
+            items.extend(
+                drained
+                    .into_iter()
+                    .map(|(sequence, _, item, _)| (sequence, item)),
+            );
+            true
+        })
+        .await;
+        self.wait_for_all_tasks_async().await;
+        items.sort_by_key(|(sequence, _)| *sequence);
+        items.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// Cancels the child tasks and waits until every one of them stopped like ``cancel_and_wait`` does,
+    /// then takes every result stored before that in the order they finished,
+    /// including those of child tasks which returned while being cancelled
+    pub(crate) async fn cancel_and_collect(&mut self) -> Vec<ValueType> {
+        self.keep_cancelled_results.store(true, Ordering::Release);
+        // a child task storing its result only stops once stored, so it is in the buffer once this returns
+        self.cancel_and_wait().await;
+        self.keep_cancelled_results.store(false, Ordering::Release);
+        self.stream
+            .drain_sequenced_items()
+            .await
+            .into_iter()
+            .map(|(_, item)| item)
+            .collect()
+    }
+
+    /// Drains the results while waiting like ``wait_for_all`` does, pairing each result with its child task's latency
+    pub(crate) async fn wait_for_all_timed(&self) -> Vec<(ValueType, Duration)> {
+        let mut items: Vec<(ValueType, Duration)> = vec![];
+        self.drain_while_waiting(|drained| {
+            items.extend(
+                drained
+                    .into_iter()
+                    .map(|(_, _, item, latency)| (item, latency)),
+            );
+            true
+        })
+        .await;
+        self.wait_for_all_tasks_async().await;
+        items
+    }
+
+    /// Waits for the child tasks behind ``ids`` to finish, leaving the other child tasks running,
+    /// and takes their results out of the buffer in the order of ``ids``.
+    ///
+    /// Ids of child tasks which were cancelled, stored no result or whose result was already consumed are skipped
+    pub(crate) async fn join(&self, ids: &[TaskId]) -> Vec<ValueType> {
+        // child tasks settle once their result is stored, while cancelled ones stop being tracked
+        self.stream
+            .wait_until(|| {
+                !self.tasks.lock().iter().any(|task| {
+                    ids.contains(&TaskId::new(task.sequence))
+                        && !task.settled.load(Ordering::Acquire)
+                })
+            })
+            .await;
+        let mut items: Vec<(u64, ValueType)> = self
+            .stream
+            .take_items(|sequence| ids.contains(&TaskId::new(sequence)))
+            .await;
+        items.sort_by_key(|(sequence, _)| ids.iter().position(|id| *id == TaskId::new(*sequence)));
+        items.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// Splits the child tasks into a ``TaskResult`` for each one still running, which its result is sent to
+    /// once it stops instead of being stored, and the results already stored, which are taken out of the buffer,
+    /// so every result ends up on exactly one side
+    #[cfg(feature = "futures")]
+    pub(crate) fn route_results(&self) -> (Vec<TaskResult<ValueType>>, Vec<ValueType>) {
+        // held until the buffer was taken, so a child task stopping in the meantime finds its route either way
+        let mut routes = self.routes.lock();
+        let mut results: Vec<TaskResult<ValueType>> = vec![];
+        for task in self.tasks.lock().iter() {
+            if !task.stopped.load(Ordering::Acquire) {
+                let (sender, receiver): (ResultSender<ValueType>, ResultReceiver<ValueType>) =
+                    task_result::channel();
+                routes.insert(task.sequence, sender);
+                results.push(TaskResult::new(TaskId::new(task.sequence), receiver));
+            }
+        }
+        let mut items: Vec<ValueType> = vec![];
+        // results stored before their child task stopped are still in the buffer
+        for (sequence, item) in crate::block_on(self.stream.take_items(|_| true)) {
+            match routes.remove(&sequence) {
+                Some(sender) => sender.send(item),
+                None => items.push(item),
+            }
+        }
+        (results, items)
+    }
+
+    pub(crate) async fn fold<Acc, F>(&self, init: Acc, f: F) -> Acc
+    where
+        F: FnMut(Acc, ValueType) -> Acc,
+    {
+        let acc: Acc = self.stream().fold(init, f).await;
+        self.wait_for_all_tasks_async().await;
+        acc
+    }
+
+    pub(crate) fn try_drain(&self) -> Vec<ValueType> {
+        crate::block_on(self.stream.drain_sequenced_items())
+            .into_iter()
+            .map(|(_, item)| item)
+            .collect()
+    }
+
+    pub(crate) async fn wait_for_first(&self, count: usize) -> Vec<ValueType> {
+        self.stream().take(count).collect().await
+    }
+
+    /// Collects the results until a child task panics, then cancels the other child tasks and returns its panic
+    pub(crate) async fn wait_for_all_checked(&mut self) -> Result<Vec<ValueType>, SpawnError> {
+        let mut items: Vec<ValueType> = vec![];
+        let mut panic: Option<Box<dyn Any + Send>> = None;
+        // child tasks store their panic before decrementing the task count too
+        self.drain_while_waiting(|drained| {
+            items.extend(drained.into_iter().map(|(_, _, item, _)| item));
+            panic = self.take_panic();
+            panic.is_none()
+        })
+        .await;
+        if let Some(payload) = panic {
+            self.cancel_async().await;
+            return Err(SpawnError::Panicked(payload));
+        }
+        self.wait_for_all_tasks_async().await;
+        match self.was_cancelled() {
+            true => Err(SpawnError::Cancelled),
+            false => Ok(items),
         }
-        self.poll();
     }
 }
 
-impl<ItemType> RuntimeEngine<ItemType> {
+impl<ItemType, S: Spawner> RuntimeEngine<ItemType, S> {
     pub(crate) fn load(&self) -> bool {
         self.wait_flag.load(Ordering::Acquire)
     }
@@ -79,33 +981,598 @@ impl<ItemType> RuntimeEngine<ItemType> {
     }
 }
 
-impl<ItemType: Send + 'static> RuntimeEngine<ItemType> {
-    pub(crate) fn write_task<F>(&self, priority: Priority, task: F)
+impl<ItemType: Send + 'static, S: Spawner> RuntimeEngine<ItemType, S> {
+    pub(crate) fn on_complete<F>(&self, observer: F)
+    where
+        F: Fn(&ItemType) + Send + Sync + 'static,
+    {
+        self.observers.lock().push(Arc::new(observer));
+    }
+
+    pub(crate) fn on_start<F>(&self, observer: F)
+    where
+        F: Fn(TaskId) + Send + Sync + 'static,
+    {
+        self.start_observers.lock().push(Arc::new(observer));
+    }
+
+    pub(crate) fn write_task<F>(&self, priority: Priority, task: F) -> TaskId
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        self.write_optional_task(priority, None, async move { Some(task.await) })
+    }
+
+    /// Spawns a child task whose result also resolves the returned ``TaskResult``, besides being stored
+    pub(crate) fn write_task_with_result<F>(
+        &self,
+        priority: Priority,
+        task: F,
+    ) -> TaskResult<ItemType>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+        ItemType: Clone,
+    {
+        let (sender, receiver): (ResultSender<ItemType>, ResultReceiver<ItemType>) =
+            task_result::channel();
+        let id: TaskId = self.write_task(priority, async move {
+            let item: ItemType = task.await;
+            sender.send(item.clone());
+            item
+        });
+        TaskResult::new(id, receiver)
+    }
+
+    pub(crate) fn write_blocking_task<F>(&self, priority: Priority, task: F) -> TaskId
+    where
+        F: FnOnce() -> ItemType + Send + 'static,
+    {
+        let Some(task) =
+            self.spawn_optional_task(priority, None, true, async move { Some(task()) })
+        else {
+            return TaskId::rejected();
+        };
+        let id: TaskId = TaskId::new(task.sequence);
+        self.tasks.lock().push(task);
+        id
+    }
+
+    pub(crate) fn write_named_task<F>(&self, priority: Priority, name: &str, task: F) -> TaskId
     where
         F: Future<Output = ItemType> + Send + 'static,
     {
+        self.write_optional_task(priority, Some(name), async move { Some(task.await) })
+    }
+
+    pub(crate) fn write_task_with_timeout<F>(
+        &self,
+        priority: Priority,
+        timeout: Duration,
+        task: F,
+    ) -> TaskId
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        self.write_optional_task(priority, None, async move {
+            future::or(async move { Some(task.await) }, async move {
+                sleep(timeout).await;
+                None
+            })
+            .await
+        })
+    }
+
+    /// Spawns the child task unless the engine was cancelled or closed, and returns whether it did.
+    ///
+    /// The tasks lock is held from the check until the child task is tracked,
+    /// so a concurrent cancellation either cancels the child task or prevents it from spawning
+    pub(crate) fn write_task_unless_cancelled<F>(&self, priority: Priority, task: F) -> bool
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        self.try_write_task(priority, task).is_ok()
+    }
+
+    /// Spawns a child task unless the engine was cancelled, and tells why it wasn't spawned otherwise
+    pub(crate) fn try_write_task<F>(
+        &self,
+        priority: Priority,
+        task: F,
+    ) -> Result<TaskId, SpawnError>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        // waited for before taking the child tasks, so a blocked spawn never holds up whoever needs them meanwhile
+        let admitted: bool = self.wait_for_capacity();
+        self.write_admitted_task(admitted, priority, task)
+    }
+
+    /// Spawns a child task like ``try_write_task`` does, but waits for capacity on the caller's executor
+    pub(crate) async fn try_write_task_async<F>(
+        &self,
+        priority: Priority,
+        task: F,
+    ) -> Result<TaskId, SpawnError>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        let admitted: bool = self.wait_for_capacity_async().await;
+        self.write_admitted_task(admitted, priority, task)
+    }
+
+    /// Spawns a child task the saturation policy already ``admitted``, unless the engine was cancelled,
+    /// holding the child tasks throughout so a cancellation can't miss it
+    fn write_admitted_task<F>(
+        &self,
+        admitted: bool,
+        priority: Priority,
+        task: F,
+    ) -> Result<TaskId, SpawnError>
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        let mut tasks = self.tasks.lock();
+        if self.was_cancelled() {
+            return Err(SpawnError::Cancelled);
+        }
+        let task = async move { Some(task.await) };
+        let Some(task) = admitted
+            .then(|| self.spawn_admitted_task(priority, None, false, vec![], move |_| task))
+            .flatten()
+        else {
+            return match self.is_closed() {
+                true => Err(SpawnError::Closed),
+                false => Err(SpawnError::Saturated),
+            };
+        };
+        let id: TaskId = TaskId::new(task.sequence);
+        tasks.push(task);
+        Ok(id)
+    }
+
+    fn write_optional_task<F>(&self, priority: Priority, name: Option<&str>, task: F) -> TaskId
+    where
+        F: Future<Output = Option<ItemType>> + Send + 'static,
+    {
+        let Some(task) = self.spawn_optional_task(priority, name, false, task) else {
+            return TaskId::rejected();
+        };
+        let id: TaskId = TaskId::new(task.sequence);
+        self.tasks.lock().push(task);
+        id
+    }
+
+    /// Spawns a child task which only starts once the child tasks of the ids in ``after`` finished,
+    /// where ids of child tasks that aren't tracked anymore, or never were, count as finished
+    pub(crate) fn write_task_after<F>(
+        &self,
+        priority: Priority,
+        after: &[TaskId],
+        task: F,
+    ) -> TaskId
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        let prerequisites: Vec<Arc<AtomicBool>> = self
+            .tasks
+            .lock()
+            .iter()
+            .filter(|task| after.contains(&TaskId::new(task.sequence)))
+            .map(|task| task.finished.clone())
+            .collect();
+        let task = async move { Some(task.await) };
+        let Some(task) =
+            self.spawn_sequenced_task(priority, None, false, prerequisites, move |_| task)
+        else {
+            return TaskId::rejected();
+        };
+        let id: TaskId = TaskId::new(task.sequence);
+        self.tasks.lock().push(task);
+        id
+    }
+
+    /// Spawns a child task which stores every item the stream yields as a result of its own,
+    /// and finishes once the stream is exhausted
+    pub(crate) fn write_stream_task<St>(&self, priority: Priority, items: St) -> TaskId
+    where
+        St: Stream<Item = ItemType> + Send + 'static,
+    {
+        let stream: AsyncStream<ItemType> = self.stream();
+        let observers: Observers<ItemType> = self.observers.clone();
+        let spawned_at: Instant = Instant::now();
+        let task = move |sequence: u64| async move {
+            let mut items = pin!(items);
+            while let Some(item) = items.next().await {
+                notify_observers(&observers, &item);
+                // the child task was only counted for a single result
+                stream.increment_count();
+                stream
+                    .insert_item(sequence, priority, item, spawned_at.elapsed())
+                    .await;
+            }
+            None
+        };
+        let Some(task) = self.spawn_sequenced_task(priority, None, false, vec![], task) else {
+            return TaskId::rejected();
+        };
+        let id: TaskId = TaskId::new(task.sequence);
+        self.tasks.lock().push(task);
+        id
+    }
+
+    /// Cancels the child task unless it already finished, and returns whether it did
+    pub(crate) fn cancel_task(&self, id: TaskId) -> bool {
+        let mut tasks = self.tasks.lock();
+        let Some(index) = tasks
+            .iter()
+            .position(|task| TaskId::new(task.sequence) == id)
+        else {
+            return false;
+        };
+        // whoever sets the flag first adjusts the counts, so they can't be adjusted twice
+        if tasks[index].finished.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+        let task: TrackedTask<S::JoinHandle> = tasks.remove(index);
+        drop(tasks);
+        self.abort_task(task);
+        true
+    }
+
+    /// Cancels every child task of a lower priority than ``priority`` unless it already finished,
+    /// and returns how many it cancelled
+    pub(crate) fn cancel_below(&self, priority: Priority) -> usize {
+        let mut tasks = self.tasks.lock();
+        let mut cancelled: Vec<TrackedTask<S::JoinHandle>> = vec![];
+        let mut remaining: Vec<TrackedTask<S::JoinHandle>> = vec![];
+        for task in tasks.drain(..) {
+            // finished child tasks stay tracked, since they may still be storing their result
+            if task.priority < priority && !task.finished.swap(true, Ordering::AcqRel) {
+                cancelled.push(task);
+            } else {
+                remaining.push(task);
+            }
+        }
+        *tasks = remaining;
+        drop(tasks);
+        let count: usize = cancelled.len();
+        for task in cancelled {
+            self.abort_task(task);
+        }
+        count
+    }
+
+    /// Aborts a child task whose finished flag was just set, and adjusts the counts for it
+    fn abort_task(&self, task: TrackedTask<S::JoinHandle>) {
+        abort_task(
+            &self.runtime,
+            &self.recorder,
+            &self.statuses,
+            &self.stream,
+            task,
+        );
+    }
+
+    /// Hands a child task what it needs to cancel the engine once it panicked under ``PanicPolicy::CancelAll``
+    fn panic_canceller(&self) -> PanicCanceller<ItemType, S> {
+        PanicCanceller {
+            tasks: Arc::downgrade(&self.tasks),
+            runtime: self.runtime.clone(),
+            stream: self.stream(),
+            cancelled: self.cancelled.clone(),
+            token: self.token.clone(),
+            cancel_order: self.cancel_order.clone(),
+            recorder: self.recorder.clone(),
+            statuses: self.statuses.clone(),
+        }
+    }
+
+    /// Spawns a child task which only stores its result when it returns one,
+    /// onto the backend's threads for blocking child tasks when ``blocking`` is set,
+    /// unless the engine was closed or the saturation policy rejects it
+    fn spawn_optional_task<F>(
+        &self,
+        priority: Priority,
+        name: Option<&str>,
+        blocking: bool,
+        task: F,
+    ) -> Option<TrackedTask<S::JoinHandle>>
+    where
+        F: Future<Output = Option<ItemType>> + Send + 'static,
+    {
+        self.spawn_sequenced_task(priority, name, blocking, vec![], move |_| task)
+    }
+
+    /// Spawns a child task like ``spawn_optional_task`` does, out of the future ``task`` creates
+    /// from the child task's sequence number, which only starts once every flag in ``prerequisites`` is set
+    fn spawn_sequenced_task<F, Fut>(
+        &self,
+        priority: Priority,
+        name: Option<&str>,
+        blocking: bool,
+        prerequisites: Vec<Arc<AtomicBool>>,
+        task: F,
+    ) -> Option<TrackedTask<S::JoinHandle>>
+    where
+        F: FnOnce(u64) -> Fut + Send + 'static,
+        Fut: Future<Output = Option<ItemType>> + Send + 'static,
+    {
+        if !self.wait_for_capacity() {
+            return None;
+        }
+        self.spawn_admitted_task(priority, name, blocking, prerequisites, task)
+    }
+
+    /// Spawns a child task like ``spawn_sequenced_task`` does, once the saturation policy admitted it
+    fn spawn_admitted_task<F, Fut>(
+        &self,
+        priority: Priority,
+        name: Option<&str>,
+        blocking: bool,
+        prerequisites: Vec<Arc<AtomicBool>>,
+        task: F,
+    ) -> Option<TrackedTask<S::JoinHandle>>
+    where
+        F: FnOnce(u64) -> Fut + Send + 'static,
+        Fut: Future<Output = Option<ItemType>> + Send + 'static,
+    {
+        if self.is_closed() {
+            return None;
+        }
         if self.load() {
-            self.runtime.start();
+            self.runtime.restart();
             self.store(false);
         }
-        self.stream.increment();
-        let mut stream: AsyncStream<ItemType> = self.stream();
-        let runtime = self.runtime.clone();
-        let tasks: Arc<Mutex<Vec<(Priority, Task)>>> = self.tasks.clone();
-        self.runtime.submit(move || {
-            tasks.lock().push((
-                priority,
-                runtime.spawn(async move {
-                    stream.insert_item(task.await).await;
-                    stream.decrement_task_count();
-                }),
-            ));
-        });
+        let sequence: u64 = self.stream.increment();
+        self.statuses.lock().insert(sequence, TaskStatus::Queued);
+        let stream: AsyncStream<ItemType> = self.stream();
+        let statuses: Statuses = self.statuses.clone();
+        let panic_policy: Arc<Mutex<PanicPolicy>> = self.panic_policy.clone();
+        let keep_cancelled_results: Arc<AtomicBool> = self.keep_cancelled_results.clone();
+        let panic_canceller: PanicCanceller<ItemType, S> = self.panic_canceller();
+        let semaphore: Option<Semaphore> = self.semaphore.lock().clone();
+        let class_semaphore: Option<Semaphore> =
+            self.concurrency_limits.lock().get(&priority).cloned();
+        let rate_limiter: Option<RateLimiter> = self.rate_limiter.lock().clone();
+        let (panics, panicked) = (self.panics.clone(), self.panicked.clone());
+        let observers: Observers<ItemType> = self.observers.clone();
+        let start_observers: StartObservers = self.start_observers.clone();
+        let token: CancellationToken = self.token.clone();
+        let finished: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let finished_flag: Arc<AtomicBool> = finished.clone();
+        let settled: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let settled_flag: Arc<AtomicBool> = settled.clone();
+        let stopped: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let stop_guard: StopGuard<ItemType> = StopGuard {
+            sequence,
+            stopped: stopped.clone(),
+            stream: self.stream(),
+            #[cfg(feature = "futures")]
+            routes: self.routes.clone(),
+        };
+        let recorder: Arc<dyn MetricsRecorder> = self.recorder.lock().clone();
+        #[cfg(feature = "stats")]
+        let stats: Stats = self.stats.clone();
+        let spawned_at: Instant = Instant::now();
+        // created while spawning, so the span of the code that spawned the child task becomes its parent
+        #[cfg(feature = "tracing")]
+        let span: tracing::Span = tracing::info_span!(
+            "child_task",
+            id = sequence,
+            priority = ?priority,
+            name = name.unwrap_or_default(),
+        );
+        recorder.on_spawn();
+        let wrapper = async move {
+            // held for the whole child task, so it's dropped along with its future even if that never got polled
+            let stop_guard: StopGuard<ItemType> = stop_guard;
+            // waited for before the permit, so a child task waiting for its prerequisites never holds one up
+            stream
+                .wait_until(|| {
+                    prerequisites
+                        .iter()
+                        .all(|finished| finished.load(Ordering::Acquire))
+                })
+                .await;
+            // taken before the overall limit, so a child task of a saturated priority never holds up the other priorities
+            let _class_permit: Option<Permit> = match class_semaphore {
+                Some(semaphore) => Some(semaphore.acquire(priority, sequence).await),
+                None => None,
+            };
+            let _permit: Option<Permit> = match semaphore {
+                Some(semaphore) => Some(semaphore.acquire(priority, sequence).await),
+                None => None,
+            };
+            // child tasks take their start slot once they may run, so a slot is never spent waiting for a permit
+            if let Some(rate_limiter) = rate_limiter {
+                rate_limiter.wait_for_slot().await;
+            }
+            let running: Statuses = statuses.clone();
+            let task = async move {
+                // a child task cancelled before it got aborted may still be polled once
+                if let Some(status @ TaskStatus::Queued) = running.lock().get_mut(&sequence) {
+                    *status = TaskStatus::Running;
+                }
+                notify_start_observers(&start_observers, TaskId::new(sequence));
+                // spawn groups created by the child task get a child token of this spawn group's token
+                let item: Option<ItemType> = token.as_current(task(sequence)).await;
+                if let Some(item) = &item {
+                    notify_observers(&observers, item);
+                }
+                item
+            };
+            // the span is only entered once the child task runs, and closes once it's dropped however it ended
+            #[cfg(feature = "tracing")]
+            let task = tracing::Instrument::instrument(task, span);
+            let outcome = AssertUnwindSafe(task).catch_unwind().await;
+            // a child task cancelled on its own already had its counts adjusted
+            if finished_flag.swap(true, Ordering::AcqRel) {
+                // cancelled right after returning, which ``cancel_and_collect`` still wants the result of
+                if let Ok(Some(item)) = outcome {
+                    if keep_cancelled_results.load(Ordering::Acquire) {
+                        set_status(&statuses, sequence, TaskStatus::Completed);
+                        let latency: Duration = spawned_at.elapsed();
+                        stop_guard.store(priority, item, latency).await;
+                    }
+                }
+                return;
+            }
+            let latency: Duration = spawned_at.elapsed();
+            match &outcome {
+                Ok(_) => {
+                    #[cfg(feature = "stats")]
+                    stats.record(latency);
+                    recorder.on_complete(latency);
+                    set_status(&statuses, sequence, TaskStatus::Completed);
+                }
+                Err(_) => {
+                    recorder.on_panic();
+                    set_status(&statuses, sequence, TaskStatus::Panicked);
+                }
+            }
+            match outcome {
+                Ok(Some(item)) => stop_guard.store(priority, item, latency).await,
+                Ok(None) => stream.decrement_count(),
+                Err(payload) => {
+                    panics.lock().push(payload);
+                    panicked.fetch_add(1, Ordering::Release);
+                    stream.decrement_count();
+                    if *panic_policy.lock() == PanicPolicy::CancelAll {
+                        panic_canceller.cancel_all();
+                    }
+                }
+            }
+            settled_flag.store(true, Ordering::Release);
+            stream.decrement_task_count();
+        };
+        let handle: S::JoinHandle = match blocking {
+            true => self.runtime.spawn_blocking(wrapper),
+            false => self.runtime.spawn(wrapper),
+        };
+        Some(TrackedTask {
+            priority,
+            sequence,
+            handle,
+            name: name.map(str::to_string),
+            spawned_at,
+            finished,
+            settled,
+            stopped,
+        })
+    }
+}
+
+/// Cancels the other child tasks from inside a child task which panicked under ``PanicPolicy::CancelAll``.
+///
+/// It holds the child tasks weakly, since each of them holds onto the future of its child task,
+/// which holds onto this in turn
+struct PanicCanceller<ItemType, S: Spawner> {
+    tasks: Weak<Mutex<Vec<TrackedTask<S::JoinHandle>>>>,
+    runtime: S,
+    stream: AsyncStream<ItemType>,
+    cancelled: Arc<AtomicBool>,
+    token: CancellationToken,
+    cancel_order: Arc<Mutex<CancelOrder>>,
+    recorder: Recorder,
+    statuses: Statuses,
+}
+
+impl<ItemType, S: Spawner> PanicCanceller<ItemType, S> {
+    /// Cancels the engine, aborting every other child task in the cancel order unless it already finished
+    fn cancel_all(&self) {
+        self.cancelled.store(true, Ordering::Release);
+        self.token.cancel();
+        // the engine is already gone, along with every child task it tracked
+        let Some(tasks) = self.tasks.upgrade() else {
+            return;
+        };
+        let mut cancelled: Vec<TrackedTask<S::JoinHandle>> = vec![];
+        let mut tasks = tasks.lock();
+        for task in std::mem::take(&mut *tasks) {
+            // the panicking child task already set its own flag, so it is never aborted
+            match task.finished.swap(true, Ordering::AcqRel) {
+                true => tasks.push(task),
+                false => cancelled.push(task),
+            }
+        }
+        drop(tasks);
+        sort_in_cancel_order(*self.cancel_order.lock(), &mut cancelled);
+        for task in cancelled {
+            abort_task(
+                &self.runtime,
+                &self.recorder,
+                &self.statuses,
+                &self.stream,
+                task,
+            );
+        }
+    }
+}
+
+fn sort_in_cancel_order<Handle>(cancel_order: CancelOrder, tasks: &mut [TrackedTask<Handle>]) {
+    match cancel_order {
+        CancelOrder::Spawned => tasks.sort_by_key(|task| task.sequence),
+        CancelOrder::LowestPriorityFirst => {
+            tasks.sort_by_key(|task| (task.priority, task.sequence))
+        }
+        CancelOrder::HighestPriorityFirst => {
+            tasks.sort_by_key(|task| (Reverse(task.priority), task.sequence))
+        }
+    }
+}
+
+/// Aborts a child task whose finished flag was just set, and adjusts the counts for it
+fn abort_task<ItemType, S: Spawner>(
+    runtime: &S,
+    recorder: &Recorder,
+    statuses: &Statuses,
+    stream: &AsyncStream<ItemType>,
+    task: TrackedTask<S::JoinHandle>,
+) {
+    runtime.abort(&task.handle);
+    recorder.lock().on_cancel();
+    set_status(statuses, task.sequence, TaskStatus::Cancelled);
+    stream.decrement_count();
+    stream.decrement_cancelled_task_count();
+}
+
+/// Calls the observers with a child task's result
+fn notify_observers<ItemType>(observers: &Observers<ItemType>, item: &ItemType) {
+    // observers may register other observers, so they are called without holding the lock
+    let observers: Vec<Observer<ItemType>> = observers.lock().clone();
+    for observer in observers {
+        observer(item);
     }
 }
 
-impl<ItemType> RuntimeEngine<ItemType> {
+/// Records how a child task ended
+fn set_status(statuses: &Statuses, sequence: u64, status: TaskStatus) {
+    if let Some(slot) = statuses.lock().get_mut(&sequence) {
+        *slot = status;
+    }
+}
+
+/// Calls the start observers with the id of a child task which is about to run
+fn notify_start_observers(observers: &StartObservers, id: TaskId) {
+    let observers: Vec<StartObserver> = observers.lock().clone();
+    for observer in observers {
+        observer(id);
+    }
+}
+
+impl<ItemType, S: Spawner> RuntimeEngine<ItemType, S> {
     pub(crate) fn poll(&self) {
-        self.runtime.poll_all();
+        self.runtime.poll();
+    }
+
+    /// Returns the status of the child task behind ``id``, or ``None`` if this engine never spawned it
+    pub(crate) fn task_status(&self, id: TaskId) -> Option<TaskStatus> {
+        self.statuses.lock().get(&id.sequence()).copied()
+    }
+}
+
+impl<ItemType: Clone, S: Spawner> RuntimeEngine<ItemType, S> {
+    pub(crate) fn results_snapshot(&self) -> Vec<ItemType> {
+        crate::block_on(self.stream.snapshot_items())
     }
 }
@@ -0,0 +1,22 @@
+/// Saturation Policy
+///
+/// Decides what a spawn group does with a new child task while its backend's threads are saturated,
+/// which is while child tasks are already waiting for one of its threads.
+///
+/// With the ``tokio`` feature, the spawn group can't tell how many of its child tasks wait for a thread,
+/// so it never counts as saturated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SaturationPolicy {
+    /// Queues the new child task up behind the waiting ones, which is the default
+    #[default]
+    Queue,
+    /// Rejects the new child task, the way a closed spawn group does
+    Reject,
+    /// Blocks the spawning thread until no child task waits for a thread anymore, then spawns the new child task.
+    ///
+    /// The spawning thread sleeps until one of the spawn group's threads takes a waiting child task.
+    /// It must not be used from inside a child task of the same spawn group, which would block the very thread
+    /// the waiting child tasks need, and wait forever on a single threaded spawn group.
+    /// ``try_spawn_task_async`` waits on the caller's executor instead of blocking its thread
+    Block,
+}
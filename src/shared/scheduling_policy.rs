@@ -0,0 +1,30 @@
+use crate::shared::priority::Priority;
+
+/// Scheduling Policy
+///
+/// Decides which of the child tasks waiting for the concurrency limit set by ``set_max_concurrency`` runs next
+/// whenever a running child task finishes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SchedulingPolicy {
+    /// Runs the waiting child task of the highest priority next, which is the default.
+    ///
+    /// Child tasks of a low priority may wait forever while child tasks of a higher priority keep getting spawned,
+    /// unless the spawn group lets them age with ``set_aging``
+    #[default]
+    Strict,
+    /// Shares the runs between the priorities of the waiting child tasks in proportion to their weights,
+    /// so every priority keeps making progress.
+    ///
+    /// The weights pair priorities with how many runs they get for every run of a priority of weight one.
+    /// Priorities left out, as well as those of weight zero, weigh one.
+    ///
+    /// It uses smooth weighted round-robin: every priority with a waiting child task has a credit, which starts at zero.
+    /// Whenever a child task may run, every such priority gains its weight in credit, and the one with the most credit,
+    /// the higher one on a tie, runs its earliest spawned child task and loses the sum of the weights just handed out.
+    /// Priorities without waiting child tasks lose their credit. With a weight of 3 for ``Priority::HIGH``
+    /// and 1 for ``Priority::LOW``, that runs their child tasks in the order high, high, low, high, over and over,
+    /// for as long as both have child tasks waiting.
+    ///
+    /// Aging set with ``set_aging`` has no effect under this policy.
+    WeightedFair(Vec<(Priority, u32)>),
+}
@@ -0,0 +1,41 @@
+use crate::shared::runtime::RuntimeEngine;
+use std::{
+    future::Future,
+    pin::{pin, Pin},
+};
+
+/// Ties the child tasks of a spawn group to the scope it was created for
+///
+/// Once the scope's body completes, every child task spawned in it is waited for, even when the spawn group
+/// itself was moved out of the body. If the body panics or is dropped before it completes, they get cancelled instead.
+pub(crate) struct Scope<ItemType: Send + 'static> {
+    runtime: RuntimeEngine<ItemType>,
+    finished: bool,
+}
+
+impl<ItemType: Send + 'static> Scope<ItemType> {
+    pub(crate) fn new(runtime: RuntimeEngine<ItemType>) -> Self {
+        Self {
+            runtime,
+            finished: false,
+        }
+    }
+
+    pub(crate) async fn run<Fut: Future>(self, body: Fut) -> Fut::Output {
+        // locals drop in reverse order, so the scope cancels the child tasks before the body drops the spawn group
+        let mut body: Pin<&mut Fut> = pin!(body);
+        let mut scope: Scope<ItemType> = self;
+        let output: Fut::Output = body.as_mut().await;
+        scope.runtime.wait_for_all_tasks_async().await;
+        scope.finished = true;
+        output
+    }
+}
+
+impl<ItemType: Send + 'static> Drop for Scope<ItemType> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.runtime.cancel();
+        }
+    }
+}
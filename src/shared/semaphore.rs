@@ -0,0 +1,291 @@
+use crate::shared::{priority::Priority, scheduling_policy::SchedulingPolicy};
+use parking_lot::Mutex;
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+/// Identifies a waiter, ordered so that the highest priority and then the earliest spawned comes first,
+/// where waiters from outside of the spawn group get a unique nonzero last field, and child tasks zero
+type Ticket = (Reverse<Priority>, u64, usize);
+
+type Waiters = BTreeMap<Ticket, Waiter>;
+
+/// When a waiter started waiting, and how to wake it once it may be next in line
+struct Waiter {
+    enqueued: Instant,
+    waker: Waker,
+}
+
+/// An asynchronous counting semaphore which hands its permits to the waiters with the highest priority first
+///
+/// With aging, a waiter's priority rises a level for every ``aging`` it waited, so none of them waits forever.
+/// With weighted fairness, the permits are shared between the priorities of the waiters instead.
+///
+/// Waiters never wake themselves: whoever frees a permit or gives up waiting wakes the waiter next in line
+#[derive(Clone)]
+pub(crate) struct Semaphore {
+    permits: Arc<AtomicUsize>,
+    waiters: Arc<Mutex<Waiters>>,
+    outsiders: Arc<AtomicUsize>,
+    aging: Arc<Mutex<Duration>>,
+    fairness: Arc<Mutex<Option<Fairness>>>,
+}
+
+impl Semaphore {
+    pub(crate) fn new(
+        permits: usize,
+        aging: Duration,
+        scheduling_policy: &SchedulingPolicy,
+    ) -> Self {
+        Self {
+            permits: Arc::new(AtomicUsize::new(permits)),
+            waiters: Arc::new(Mutex::new(BTreeMap::new())),
+            outsiders: Arc::new(AtomicUsize::new(0)),
+            aging: Arc::new(Mutex::new(aging)),
+            fairness: Arc::new(Mutex::new(Fairness::for_policy(scheduling_policy))),
+        }
+    }
+
+    /// Raises the priority of the waiters a level for every ``aging`` they waited, where zero turns it off
+    pub(crate) fn set_aging(&self, aging: Duration) {
+        *self.aging.lock() = aging;
+        self.wake_head(&self.waiters.lock());
+    }
+
+    /// Picks the waiters next in line by the scheduling policy, starting over from zero credits
+    pub(crate) fn set_scheduling_policy(&self, scheduling_policy: &SchedulingPolicy) {
+        *self.fairness.lock() = Fairness::for_policy(scheduling_policy);
+        self.wake_head(&self.waiters.lock());
+    }
+
+    /// The waiter next in line, which without aging or weighted fairness is simply the first one
+    fn head(&self, waiters: &Waiters) -> Option<Ticket> {
+        if let Some(fairness) = &*self.fairness.lock() {
+            return fairness.head(waiters);
+        }
+        let aging: Duration = *self.aging.lock();
+        if aging.is_zero() {
+            return waiters.keys().next().copied();
+        }
+        let now: Instant = Instant::now();
+        let aged = |(ticket, waiter): (&Ticket, &Waiter)| {
+            let (Reverse(priority), sequence, outsider) = *ticket;
+            let levels: u128 = now.duration_since(waiter.enqueued).as_nanos() / aging.as_nanos();
+            let levels: i32 = i32::try_from(levels).unwrap_or(i32::MAX);
+            (Reverse(priority.boosted(levels)), sequence, outsider)
+        };
+        waiters
+            .iter()
+            .min_by_key(|waiter| aged(*waiter))
+            .map(|(ticket, _)| *ticket)
+    }
+
+    /// Hands the credits out for the permit the waiter of ``priority`` just took, while it's still among the waiters
+    fn dispatched(&self, waiters: &Waiters, priority: Priority) {
+        if let Some(fairness) = &mut *self.fairness.lock() {
+            fairness.dispatched(waiters, priority);
+        }
+    }
+
+    /// Wakes the waiter next in line when a permit is free for it
+    fn wake_head(&self, waiters: &Waiters) {
+        if self.permits.load(Ordering::Acquire) == 0 {
+            return;
+        }
+        if let Some(waiter) = self.head(waiters).and_then(|ticket| waiters.get(&ticket)) {
+            waiter.waker.wake_by_ref();
+        }
+    }
+}
+
+/// The credits of the smooth weighted round-robin between the priorities of the waiters,
+/// as laid out on ``SchedulingPolicy::WeightedFair``
+struct Fairness {
+    weights: Vec<(Priority, u32)>,
+    credits: BTreeMap<Priority, i64>,
+}
+
+impl Fairness {
+    fn for_policy(scheduling_policy: &SchedulingPolicy) -> Option<Self> {
+        match scheduling_policy {
+            SchedulingPolicy::Strict => None,
+            SchedulingPolicy::WeightedFair(weights) => Some(Self {
+                weights: weights.clone(),
+                credits: BTreeMap::new(),
+            }),
+        }
+    }
+
+    fn weight(&self, priority: Priority) -> i64 {
+        self.weights
+            .iter()
+            .find(|(weighted, _)| *weighted == priority)
+            .map_or(1, |(_, weight)| i64::from(*weight).max(1))
+    }
+
+    /// The priorities with waiters, from the highest one down
+    fn priorities(waiters: &Waiters) -> Vec<Priority> {
+        let mut priorities: Vec<Priority> = waiters
+            .keys()
+            .map(|(Reverse(priority), _, _)| *priority)
+            .collect();
+        priorities.dedup();
+        priorities
+    }
+
+    /// The earliest waiter of the priority with the most credit once it gains its weight
+    fn head(&self, waiters: &Waiters) -> Option<Ticket> {
+        let next: Priority = Self::priorities(waiters)
+            .into_iter()
+            .max_by_key(|priority| {
+                let credit: i64 = self.credits.get(priority).copied().unwrap_or_default();
+                (credit + self.weight(*priority), *priority)
+            })?;
+        waiters
+            .keys()
+            .find(|(Reverse(priority), _, _)| *priority == next)
+            .copied()
+    }
+
+    fn dispatched(&mut self, waiters: &Waiters, next: Priority) {
+        let priorities: Vec<Priority> = Self::priorities(waiters);
+        self.credits
+            .retain(|priority, _| priorities.contains(priority));
+        let mut total: i64 = 0;
+        for priority in priorities {
+            let weight: i64 = self.weight(priority);
+            *self.credits.entry(priority).or_default() += weight;
+            total += weight;
+        }
+        *self.credits.entry(next).or_default() -= total;
+    }
+}
+
+impl Semaphore {
+    /// Waits until a permit is available and no waiter of a higher priority, or of the same priority
+    /// but with a lower sequence number, is left waiting for one, then takes it
+    pub(crate) fn acquire(&self, priority: Priority, sequence: u64) -> Acquire {
+        Acquire {
+            semaphore: self.clone(),
+            ticket: (Reverse(priority), sequence, 0),
+            waiting: false,
+        }
+    }
+
+    /// Waits like ``acquire`` does, for a waiter which isn't a child task,
+    /// queued after the child tasks of the same priority up to the sequence number ``sequence``
+    pub(crate) fn acquire_outside(&self, priority: Priority, sequence: u64) -> Acquire {
+        let outsider: usize = self.outsiders.fetch_add(1, Ordering::AcqRel) + 1;
+        Acquire {
+            semaphore: self.clone(),
+            ticket: (Reverse(priority), sequence, outsider),
+            waiting: false,
+        }
+    }
+
+    fn try_acquire(&self) -> Option<Permit> {
+        self.permits
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |permits| {
+                permits.checked_sub(1)
+            })
+            .ok()?;
+        Some(Permit {
+            semaphore: Some(self.clone()),
+        })
+    }
+}
+
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub(crate) struct Acquire {
+    semaphore: Semaphore,
+    ticket: Ticket,
+    waiting: bool,
+}
+
+impl Future for Acquire {
+    type Output = Permit;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let semaphore: Semaphore = self.semaphore.clone();
+        let mut waiters = semaphore.waiters.lock();
+        match waiters.get_mut(&self.ticket) {
+            Some(waiter) => {
+                if !waiter.waker.will_wake(cx.waker()) {
+                    waiter.waker = cx.waker().clone();
+                }
+            }
+            None => {
+                let waiter = Waiter {
+                    enqueued: Instant::now(),
+                    waker: cx.waker().clone(),
+                };
+                waiters.insert(self.ticket, waiter);
+            }
+        }
+        self.waiting = true;
+        // freeing a permit wakes the waiter next in line, so there is nothing to check until then
+        if semaphore.permits.load(Ordering::Acquire) == 0 {
+            return Poll::Pending;
+        }
+        if semaphore.head(&waiters) == Some(self.ticket) {
+            if let Some(permit) = semaphore.try_acquire() {
+                semaphore.dispatched(&waiters, self.ticket.0 .0);
+                waiters.remove(&self.ticket);
+                self.waiting = false;
+                // several permits may have been freed at once
+                semaphore.wake_head(&waiters);
+                return Poll::Ready(permit);
+            }
+        }
+        // aging may have moved another waiter to the head since this one was woken
+        semaphore.wake_head(&waiters);
+        Poll::Pending
+    }
+}
+
+impl Drop for Acquire {
+    /// A cancelled child task stops holding back the waiters behind it
+    fn drop(&mut self) {
+        if self.waiting {
+            let mut waiters = self.semaphore.waiters.lock();
+            waiters.remove(&self.ticket);
+            // this waiter may have been woken for a permit it will never take
+            self.semaphore.wake_head(&waiters);
+        }
+    }
+}
+
+/// Permit
+///
+/// A guard holding one of the slots of a spawn group's concurrency limit, as returned by its ``acquire_permit()`` method,
+/// which gives the slot back when dropped.
+///
+/// While it is held, one less child task of the spawn group can run at the same time.
+pub struct Permit {
+    semaphore: Option<Semaphore>,
+}
+
+impl Permit {
+    /// A permit of a spawn group without a concurrency limit, which holds no slot
+    pub(crate) fn unlimited() -> Self {
+        Self { semaphore: None }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some(semaphore) = &self.semaphore {
+            semaphore.permits.fetch_add(1, Ordering::AcqRel);
+            semaphore.wake_head(&semaphore.waiters.lock());
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::{any::Any, error::Error, fmt};
+
+/// Spawn Error
+///
+/// Tells why a child task could not be spawned, or why the results of a spawn group's child tasks could not all be collected
+#[derive(Debug)]
+pub enum SpawnError {
+    /// A child task panicked with this payload, so the other child tasks were cancelled
+    Panicked(Box<dyn Any + Send>),
+    /// The spawn group was cancelled, so some child tasks never returned a result,
+    /// or a child task got spawned afterwards
+    Cancelled,
+    /// The child task was rejected by ``SaturationPolicy::Reject`` since every thread was busy
+    Saturated,
+    /// The child task was rejected since the spawn group was closed
+    Closed,
+}
+
+impl SpawnError {
+    /// The panic message of the child task, if it panicked with a string message
+    pub fn panic_message(&self) -> Option<&str> {
+        match self {
+            SpawnError::Panicked(payload) => payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str)),
+            SpawnError::Cancelled | SpawnError::Saturated | SpawnError::Closed => None,
+        }
+    }
+}
+
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self, self.panic_message()) {
+            (SpawnError::Panicked(_), Some(message)) => {
+                write!(f, "a child task panicked: {}", message)
+            }
+            (SpawnError::Panicked(_), None) => write!(f, "a child task panicked"),
+            (SpawnError::Cancelled, _) => write!(f, "the spawn group was cancelled"),
+            (SpawnError::Saturated, _) => write!(f, "every thread of the spawn group was busy"),
+            (SpawnError::Closed, _) => write!(f, "the spawn group was closed"),
+        }
+    }
+}
+
+impl Error for SpawnError {}
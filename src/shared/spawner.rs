@@ -0,0 +1,88 @@
+use crate::shared::{priority::Priority, runtime::RuntimeEngine, task_id::TaskId};
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// Spawner
+///
+/// A cheaply cloneable handle to a spawn group, as returned by its ``spawner()`` method,
+/// which spawns child tasks into that spawn group through a shared reference.
+///
+/// Child tasks can capture a clone of it to spawn more child tasks into their own spawn group while they run,
+/// and waiting for the spawn group also waits for the child tasks spawned that way.
+pub struct Spawner<ItemType: Send + 'static> {
+    runtime: RuntimeEngine<ItemType>,
+    count: Option<Arc<AtomicUsize>>,
+}
+
+impl<ItemType: Send + 'static> Spawner<ItemType> {
+    pub(crate) fn new(runtime: RuntimeEngine<ItemType>, count: Option<Arc<AtomicUsize>>) -> Self {
+        Self { runtime, count }
+    }
+
+    fn increment_count(&self) {
+        if let Some(count) = &self.count {
+            count.fetch_add(1, Ordering::Acquire);
+        }
+    }
+}
+
+impl<ItemType: Send + 'static> Clone for Spawner<ItemType> {
+    fn clone(&self) -> Self {
+        Self {
+            runtime: self.runtime.clone(),
+            count: self.count.clone(),
+        }
+    }
+}
+
+impl<ItemType: Send + 'static> Spawner<ItemType> {
+    /// Spawns a new child task into the spawn group
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `task`: an async closure that returns a value of the spawn group's item type
+    ///
+    /// # Returns
+    ///
+    /// The identifier of the new child task, for the spawn group's ``cancel_task()``
+    pub fn spawn<F>(&self, priority: Priority, task: F) -> TaskId
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        self.increment_count();
+        self.runtime.write_task(priority, task)
+    }
+
+    /// Spawns a new child task into the spawn group only if the spawn group is neither cancelled nor closed yet,
+    /// otherwise does nothing
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `task`: an async closure that returns a value of the spawn group's item type
+    ///
+    /// # Returns
+    ///
+    /// Whether the child task was spawned
+    pub fn spawn_unless_cancelled<F>(&self, priority: Priority, task: F) -> bool
+    where
+        F: Future<Output = ItemType> + Send + 'static,
+    {
+        if !self.runtime.write_task_unless_cancelled(priority, task) {
+            return false;
+        }
+        self.increment_count();
+        true
+    }
+
+    /// Returns whether the spawn group was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.runtime.was_cancelled()
+    }
+}
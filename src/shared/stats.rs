@@ -0,0 +1,87 @@
+use parking_lot::Mutex;
+use std::{sync::Arc, time::Duration};
+
+/// The number of latencies kept for estimating the percentiles
+const RESERVOIR_SIZE: usize = 1024;
+
+/// Task Stats
+///
+/// A snapshot of how long the child tasks of a spawn group took from being spawned to returning,
+/// as returned by its ``stats()`` method.
+///
+/// Child tasks that were cancelled or panicked are left out.
+/// The percentile is estimated from a uniform sample of at most 1024 latencies, so it stays cheap however many child tasks ran,
+/// while the other fields are exact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TaskStats {
+    /// The number of child tasks that returned
+    pub count: usize,
+    /// The average latency of the child tasks
+    pub mean_latency: Duration,
+    /// The longest latency of a child task
+    pub max_latency: Duration,
+    /// The latency 99 percent of the child tasks returned within
+    pub p99: Duration,
+}
+
+/// Records the latencies of the child tasks, keeping a reservoir sample of them for the percentiles
+#[derive(Clone, Default)]
+pub(crate) struct Stats {
+    inner: Arc<Mutex<Latencies>>,
+}
+
+#[derive(Default)]
+struct Latencies {
+    count: usize,
+    total: Duration,
+    max: Duration,
+    reservoir: Vec<Duration>,
+    state: u64,
+}
+
+impl Stats {
+    pub(crate) fn record(&self, latency: Duration) {
+        let mut latencies = self.inner.lock();
+        latencies.count += 1;
+        latencies.total += latency;
+        latencies.max = latencies.max.max(latency);
+        if latencies.reservoir.len() < RESERVOIR_SIZE {
+            latencies.reservoir.push(latency);
+            return;
+        }
+        // every latency recorded so far is equally likely to be in the reservoir
+        let index: usize = (latencies.next_random() % latencies.count as u64) as usize;
+        if index < RESERVOIR_SIZE {
+            latencies.reservoir[index] = latency;
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> TaskStats {
+        let latencies = self.inner.lock();
+        if latencies.count == 0 {
+            return TaskStats::default();
+        }
+        let mut sample: Vec<Duration> = latencies.reservoir.clone();
+        sample.sort();
+        let rank: usize = (sample.len() * 99).div_ceil(100);
+        TaskStats {
+            count: latencies.count,
+            mean_latency: Duration::from_nanos(
+                (latencies.total.as_nanos() / latencies.count as u128) as u64,
+            ),
+            max_latency: latencies.max,
+            p99: sample[rank.saturating_sub(1)],
+        }
+    }
+}
+
+impl Latencies {
+    /// SplitMix64, which is plenty for picking reservoir slots
+    fn next_random(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut value: u64 = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^ (value >> 31)
+    }
+}
@@ -0,0 +1,21 @@
+/// Task Id
+///
+/// Identifies a child task within its spawn group, as returned when spawning it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TaskId(u64);
+
+impl TaskId {
+    pub(crate) fn new(sequence: u64) -> Self {
+        TaskId(sequence)
+    }
+
+    /// The id of a child task that was never spawned, which matches no child task
+    pub(crate) fn rejected() -> Self {
+        TaskId(u64::MAX)
+    }
+
+    /// The sequence number of the child task
+    pub(crate) fn sequence(&self) -> u64 {
+        self.0
+    }
+}
@@ -0,0 +1,15 @@
+use crate::shared::priority::Priority;
+use std::time::Duration;
+
+/// Task Info
+///
+/// Describes a child task that has not finished yet, as returned by ``debug_snapshot()``
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskInfo {
+    /// The name the child task was spawned with, if any
+    pub name: Option<String>,
+    /// The priority the child task was spawned with
+    pub priority: Priority,
+    /// How long ago the child task was spawned
+    pub age: Duration,
+}
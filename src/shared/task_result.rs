@@ -0,0 +1,96 @@
+use crate::shared::task_id::TaskId;
+use parking_lot::Mutex;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+};
+
+/// Where a child task leaves its result for its ``TaskResult``
+struct Slot<ItemType> {
+    item: Option<ItemType>,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+type SharedSlot<ItemType> = Arc<Mutex<Slot<ItemType>>>;
+
+/// Task Result
+///
+/// A future that resolves to the result of a single child task,
+/// as returned when spawning it with ``spawn_task_with_result()``
+///
+/// It resolves to ``None`` when the child task finished without a result, because it was cancelled or panicked.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct TaskResult<ItemType> {
+    id: TaskId,
+    slot: SharedSlot<ItemType>,
+}
+
+impl<ItemType> TaskResult<ItemType> {
+    pub(crate) fn new(id: TaskId, receiver: ResultReceiver<ItemType>) -> Self {
+        Self {
+            id,
+            slot: receiver.slot,
+        }
+    }
+
+    /// The id of the child task, which ``cancel_task`` takes to cancel it on its own
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+}
+
+impl<ItemType> Future for TaskResult<ItemType> {
+    type Output = Option<ItemType>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut slot = self.slot.lock();
+        if let Some(item) = slot.item.take() {
+            return Poll::Ready(Some(item));
+        }
+        if slot.closed {
+            return Poll::Ready(None);
+        }
+        slot.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+/// The child task's end of a ``TaskResult``, which closes it once dropped,
+/// so the ``TaskResult`` also resolves when the child task never gets to send its result
+pub(crate) struct ResultSender<ItemType> {
+    slot: SharedSlot<ItemType>,
+}
+
+/// The end of a ``TaskResult`` kept until the child task it belongs to was spawned and has an id
+pub(crate) struct ResultReceiver<ItemType> {
+    slot: SharedSlot<ItemType>,
+}
+
+/// Creates both ends of a ``TaskResult``
+pub(crate) fn channel<ItemType>() -> (ResultSender<ItemType>, ResultReceiver<ItemType>) {
+    let slot: SharedSlot<ItemType> = Arc::new(Mutex::new(Slot {
+        item: None,
+        closed: false,
+        waker: None,
+    }));
+    (ResultSender { slot: slot.clone() }, ResultReceiver { slot })
+}
+
+impl<ItemType> ResultSender<ItemType> {
+    pub(crate) fn send(self, item: ItemType) {
+        self.slot.lock().item = Some(item);
+    }
+}
+
+impl<ItemType> Drop for ResultSender<ItemType> {
+    fn drop(&mut self) {
+        let mut slot = self.slot.lock();
+        slot.closed = true;
+        if let Some(waker) = slot.waker.take() {
+            waker.wake();
+        }
+    }
+}
@@ -0,0 +1,16 @@
+/// Task Status
+///
+/// Where a child task is in its life, as returned by the ``task_status()`` method of its spawn group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskStatus {
+    /// Spawned but not started yet, such as while waiting for the concurrency limit or for a thread
+    Queued,
+    /// Started running and not finished yet
+    Running,
+    /// Returned, whether or not its result was consumed yet
+    Completed,
+    /// Cancelled before it returned
+    Cancelled,
+    /// Panicked before it returned
+    Panicked,
+}
@@ -1,21 +1,34 @@
+use crate::async_stream::Buffered;
+use crate::cancellation_token::CancellationToken;
+use crate::discarding_spawn_group::DiscardingSpawnGroup;
+use crate::err_spawn_group::ErrSpawnGroup;
+#[cfg(feature = "stats")]
+use crate::shared::stats::TaskStats;
 use crate::shared::{
-    initializible::Initializible, priority::Priority, runtime::RuntimeEngine, sharedfuncs::Shared,
+    cancel_order::CancelOrder, completion::Completion, drop_policy::DropPolicy,
+    initializible::Initializible, metrics::MetricsRecorder, panic_policy::PanicPolicy,
+    priority::Priority, queue_snapshot::QueueSnapshot, runtime::RuntimeEngine,
+    saturation_policy::SaturationPolicy, scheduling_policy::SchedulingPolicy, scope::Scope,
+    semaphore::Permit, sharedfuncs::Shared, spawn_error::SpawnError, spawner::Spawner,
+    task_id::TaskId, task_info::TaskInfo, task_result::TaskResult, task_status::TaskStatus,
     wait::Waitable,
 };
 use async_trait::async_trait;
 use futures_lite::{Stream, StreamExt};
+use std::collections::HashMap;
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
     Arc,
 };
 use std::task::{Context, Poll};
+use std::time::Duration;
 use std::{future::Future, pin::Pin};
 
 /// Spawn Group
 ///
 /// A kind of a spawn group that spawns asynchronous child tasks that returns a value of ValueType,
 /// that implicitly wait for the spawned tasks to return before being dropped unless by
-/// explicitly calling ``dont_wait_at_drop()``
+/// explicitly calling ``dont_wait_at_drop()`` or ``set_drop_policy()``
 ///
 /// Child tasks are spawned by calling either ``spawn_task()`` or ``spawn_task_unless_cancelled()`` methods.
 ///
@@ -26,11 +39,94 @@ use std::{future::Future, pin::Pin};
 ///
 /// It dereferences into a ``futures`` crate ``Stream`` type where the results of each finished child task is stored and it pops out the result in First-In First-Out
 /// FIFO order whenever it is being used
-
+///
+/// Once its child tasks were waited for, it can spawn the next batch of child tasks.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{with_spawn_group, Priority};
+///
+/// # spawn_groups::block_on(async move {
+/// with_spawn_group(|mut group| async move {
+///     for batch in [0, 10] {
+///         for i in 0..3 {
+///             group.spawn_task(Priority::default(), async move { batch + i });
+///         }
+///         let mut results = group.wait_for_all_items().await;
+///         results.sort();
+///         assert_eq!(results, vec![batch, batch + 1, batch + 2]);
+///         assert!(group.is_finished());
+///     }
+/// }).await;
+/// # });
+/// ```
+///
+/// # Empty spawn groups
+///
+/// A spawn group that no child task was spawned into, such as one built conditionally, is already finished,
+/// so every way of waiting for it returns right away: the ones collecting results return nothing,
+/// the ones waiting for a single result return ``None``, and the ones waiting for a count of results
+/// only return true when that count is zero.
+///
+/// ```rust
+/// use futures_lite::StreamExt;
+/// use spawn_groups::{spawn_group::{SpawnGroup, SpawnGroupBuilder}, with_spawn_group};
+/// use std::collections::HashSet;
+/// use std::time::Duration;
+///
+/// # spawn_groups::block_on(async move {
+/// with_spawn_group(|mut group: SpawnGroup<u64>| async move {
+///     assert!(group.is_empty());
+///     assert!(group.is_finished());
+///     group.wait_for_all().await;
+///     assert_eq!(group.wait_for_all_items().await, vec![]);
+///     assert_eq!(group.wait_for_all_ordered().await, vec![]);
+///     assert_eq!(group.wait_for_all_timed().await, vec![]);
+///     assert_eq!(group.wait_for_all_checked().await.unwrap(), vec![]);
+///     assert_eq!(group.wait_for_all_within(Duration::from_secs(10)).await, (vec![], true));
+///     assert_eq!(group.wait_for_all_until(Duration::from_secs(10)).await, vec![]);
+///     assert_eq!(group.wait_for_all_items_with_completion().await.0, vec![]);
+///     assert_eq!(group.wait_for_first(0).await, vec![]);
+///     assert_eq!(group.wait_for_first(3).await, vec![]);
+///     assert_eq!(group.take_and_cancel(0).await, vec![]);
+///     assert_eq!(group.first().await, None);
+///     assert_eq!(group.first_and_cancel().await, None);
+///     assert_eq!(group.wait_until(|_| true).await, None);
+///     assert_eq!(group.next_completed().await, None);
+///     assert_eq!(group.join(&[]).await, vec![]);
+///     assert_eq!(group.fold_items(0, |sum, i| sum + i).await, 0);
+///     assert!(group.wait_until_completed(0).await);
+///     assert!(!group.wait_until_completed(1).await);
+///     assert_eq!(group.try_drain(), vec![]);
+///     assert_eq!(group.next().await, None);
+///     assert_eq!(group.cancel_and_collect().await, vec![]);
+/// }).await;
+///
+/// let group = SpawnGroupBuilder::new().build::<u64>();
+/// assert_eq!(group.collect_into::<HashSet<u64>>().await, HashSet::new());
+///
+/// let group = SpawnGroupBuilder::new().build::<u64>();
+/// assert_eq!(group.into_stream_ordered().collect::<Vec<u64>>().await, vec![]);
+///
+/// let (first, second) = (SpawnGroupBuilder::new().build::<u64>(), SpawnGroupBuilder::new().build());
+/// assert_eq!(first.merge(second).collect::<Vec<u64>>().await, vec![]);
+/// # });
+/// ```
+///
+/// The blocking ways of waiting return right away too
+///
+/// ```rust
+/// use spawn_groups::spawn_group::SpawnGroupBuilder;
+///
+/// let mut group = SpawnGroupBuilder::new().build::<u64>();
+/// assert_eq!(group.wait_for_all_blocking(), vec![]);
+/// assert_eq!(group.collect_blocking::<Vec<u64>>(), vec![]);
+/// ```
 pub struct SpawnGroup<ValueType: Send + 'static> {
     /// A field that indicates if the spawn group had been cancelled
     pub is_cancelled: bool,
-    wait_at_drop: bool,
+    drop_policy: DropPolicy,
     count: Arc<AtomicUsize>,
     runtime: RuntimeEngine<ValueType>,
 }
@@ -39,97 +135,3476 @@ impl<ValueType: Send> SpawnGroup<ValueType> {
     pub(crate) fn new() -> Self {
         Self::init()
     }
-}
 
-impl<ValueType: Send> SpawnGroup<ValueType> {
-    /// Don't implicity wait for spawned child tasks to finish before being dropped
-    pub fn dont_wait_at_drop(&mut self) {
-        self.wait_at_drop = false;
+    pub(crate) fn with_threads(thread_count: usize) -> Self {
+        SpawnGroupBuilder::new().threads(thread_count).build()
+    }
+}
+
+/// Spawn Group Builder
+///
+/// Configures the threads, the concurrency and buffer limits and the drop policy of a ``SpawnGroup`` before creating it,
+/// or of an ``ErrSpawnGroup`` or a ``DiscardingSpawnGroup``.
+///
+/// Its defaults create the same spawn group as ``with_spawn_group()`` does.
+/// Naming the threads of each spawn group tells them apart in thread dumps and profilers.
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{spawn_group::SpawnGroupBuilder, DropPolicy, Priority};
+///
+/// let mut group = SpawnGroupBuilder::new()
+///     .threads(2)
+///     .max_concurrency(1)
+///     .thread_name("worker")
+///     .drop_policy(DropPolicy::WaitAll)
+///     .build();
+///
+/// for _ in 0..3 {
+///     group.spawn_task(Priority::default(), async {
+///         std::thread::current().name().unwrap_or_default().to_string()
+///     });
+/// }
+///
+/// let names: Vec<String> = spawn_groups::block_on(group.wait_for_all_items());
+/// assert_eq!(names.len(), 3);
+/// assert!(names.iter().all(|name| name.starts_with("worker")));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SpawnGroupBuilder {
+    threads: usize,
+    max_concurrency: usize,
+    max_buffered: usize,
+    thread_name: Option<String>,
+    drop_policy: DropPolicy,
+}
+
+impl SpawnGroupBuilder {
+    /// Creates a builder with the default configuration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of threads driving the child tasks,
+    /// where zero, the default, means as many threads as the available parallelism
+    pub fn threads(mut self, thread_count: usize) -> Self {
+        self.threads = thread_count;
+        self
+    }
+
+    /// Sets the maximum number of child tasks running at once,
+    /// where zero, the default, means no limit
+    pub fn max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
+    /// Sets the maximum number of results waiting to be consumed,
+    /// where zero, the default, means no limit
+    pub fn max_buffered(mut self, max_buffered: usize) -> Self {
+        self.max_buffered = max_buffered;
+        self
+    }
+
+    /// Sets the name of the threads driving the child tasks
+    pub fn thread_name(mut self, thread_name: &str) -> Self {
+        self.thread_name = Some(thread_name.to_string());
+        self
+    }
+
+    /// Sets what happens to the remaining child tasks when the spawn group is dropped
+    pub fn drop_policy(mut self, drop_policy: DropPolicy) -> Self {
+        self.drop_policy = drop_policy;
+        self
+    }
+
+    /// Creates the spawn group
+    pub fn build<ValueType: Send + 'static>(self) -> SpawnGroup<ValueType> {
+        SpawnGroup {
+            runtime: self.runtime(),
+            is_cancelled: false,
+            count: Arc::new(AtomicUsize::new(0)),
+            drop_policy: self.drop_policy,
+        }
+    }
+
+    /// Creates an err spawn group instead
+    pub fn build_err<ValueType: Send + 'static, ErrorType: Send + 'static>(
+        self,
+    ) -> ErrSpawnGroup<ValueType, ErrorType> {
+        ErrSpawnGroup::from_parts(self.runtime(), self.drop_policy)
+    }
+
+    /// Creates a discarding spawn group instead
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{spawn_group::SpawnGroupBuilder, Priority};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let names = Arc::new(Mutex::new(vec![]));
+    /// let mut group = SpawnGroupBuilder::new().threads(2).thread_name("flusher").build_discarding();
+    /// for _ in 0..3 {
+    ///     let names = names.clone();
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         let name = std::thread::current().name().unwrap_or_default().to_string();
+    ///         names.lock().unwrap().push(name);
+    ///     });
+    /// }
+    /// drop(group);
+    ///
+    /// let names = names.lock().unwrap();
+    /// assert_eq!(names.len(), 3);
+    /// assert!(names.iter().all(|name| name.starts_with("flusher")));
+    /// ```
+    pub fn build_discarding(self) -> DiscardingSpawnGroup {
+        DiscardingSpawnGroup::from_parts(self.runtime(), self.drop_policy)
+    }
+
+    fn runtime<ItemType: Send + 'static>(&self) -> RuntimeEngine<ItemType> {
+        let mut runtime: RuntimeEngine<ItemType> =
+            RuntimeEngine::init_with_threads(self.threads, self.thread_name.as_deref());
+        runtime.set_max_concurrency(self.max_concurrency);
+        runtime.set_max_buffered(self.max_buffered);
+        runtime
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// Don't implicity wait for spawned child tasks to finish before being dropped,
+    /// cancel them instead
+    ///
+    /// This is the same as setting the drop policy to ``DropPolicy::Cancel``
+    pub fn dont_wait_at_drop(&mut self) {
+        self.drop_policy = DropPolicy::Cancel;
+    }
+
+    /// Sets what happens to still running child tasks when the spawn group gets dropped.
+    ///
+    /// The default is ``DropPolicy::WaitAll``
+    ///
+    /// # Parameters
+    ///
+    /// * `drop_policy`: the drop policy to use
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, DropPolicy, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     group.set_drop_policy(DropPolicy::Cancel);
+    ///     group.spawn_task(Priority::default(), std::future::pending::<()>());
+    ///     // dropping the group here cancels the child task instead of waiting for it forever
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn set_drop_policy(&mut self, drop_policy: DropPolicy) {
+        self.drop_policy = drop_policy;
+    }
+
+    /// Waits for the still running child tasks, or cancels or detaches them as the drop policy says,
+    /// without blocking the caller's thread, then consumes the spawn group.
+    ///
+    /// This is the recommended way to tear down a spawn group in asynchronous code,
+    /// since dropping it blocks the thread it gets dropped on, which only serves as a safety net.
+    /// Nothing is left for the drop to do once it returns.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{spawn_group::SpawnGroupBuilder, Priority};
+    /// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let finished = Arc::new(AtomicUsize::new(0));
+    /// let mut group = SpawnGroupBuilder::new().build::<()>();
+    /// for _ in 0..3 {
+    ///     let finished = finished.clone();
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         finished.fetch_add(1, Ordering::SeqCst);
+    ///     });
+    /// }
+    /// group.close().await;
+    /// assert_eq!(finished.load(Ordering::SeqCst), 3);
+    /// # });
+    /// ```
+    pub async fn close(mut self) {
+        match self.drop_policy {
+            DropPolicy::WaitAll => self.runtime.wait_for_all_tasks_async().await,
+            DropPolicy::Cancel => self.cancel_and_wait().await,
+            DropPolicy::Detach => self.runtime.detach_to_background(),
+        }
+        // there is nothing left to wait for or cancel, so the drop only releases the child tasks' bookkeeping
+        self.drop_policy = DropPolicy::Detach;
+    }
+
+    /// Leaves the still running child tasks running in the background and consumes the spawn group
+    /// without blocking the caller's thread, whatever the drop policy says.
+    ///
+    /// Just like under ``DropPolicy::Detach``, the child tasks are handed over to a background thread
+    /// which keeps the spawn group's threads alive until they finish and discards their results as they come in,
+    /// so their results don't pile up when nobody consumes them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, spawn_group::SpawnGroupBuilder, Priority};
+    /// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let flushed = Arc::new(AtomicUsize::new(0));
+    /// let mut group = SpawnGroupBuilder::new().build::<()>();
+    /// for _ in 0..3 {
+    ///     let flushed = flushed.clone();
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         sleep(Duration::from_millis(50)).await;
+    ///         flushed.fetch_add(1, Ordering::SeqCst);
+    ///     });
+    /// }
+    ///
+    /// let now = Instant::now();
+    /// group.detach();
+    /// assert!(now.elapsed() < Duration::from_millis(50));
+    ///
+    /// while flushed.load(Ordering::SeqCst) < 3 {
+    ///     std::thread::sleep(Duration::from_millis(10));
+    /// }
+    /// ```
+    pub fn detach(mut self) {
+        self.runtime.detach_to_background();
+        self.drop_policy = DropPolicy::Detach;
+    }
+
+    /// Limits how many of the child tasks spawned afterwards run at the same time,
+    /// the rest wait until a running child task finishes.
+    ///
+    /// Whenever a running child task finishes, the waiting child task of the highest priority starts next,
+    /// and among the ones of the same priority, the earliest spawned.
+    ///
+    /// Zero removes the limit, which is the default.
+    ///
+    /// # Parameters
+    ///
+    /// * `max_concurrency`: the maximum number of child tasks running at the same time
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let running = Arc::new(AtomicUsize::new(0));
+    /// let most_running = Arc::new(AtomicUsize::new(0));
+    /// let (running_count, most_running_count) = (running.clone(), most_running.clone());
+    /// with_spawn_group(|mut group| async move {
+    ///     group.set_max_concurrency(2);
+    ///     for _ in 0..10 {
+    ///         let (running, most_running) = (running_count.clone(), most_running_count.clone());
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+    ///             most_running.fetch_max(now_running, Ordering::SeqCst);
+    ///             sleep(Duration::from_millis(5)).await;
+    ///             running.fetch_sub(1, Ordering::SeqCst);
+    ///         });
+    ///     }
+    ///     group.wait_for_all().await;
+    /// }).await;
+    ///
+    /// assert!(most_running.load(Ordering::SeqCst) <= 2);
+    /// # });
+    /// ```
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::sync::{Arc, Mutex};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let started = Arc::new(Mutex::new(vec![]));
+    /// let order = started.clone();
+    /// with_spawn_group(|mut group| async move {
+    ///     group.set_max_concurrency(1);
+    ///     group.spawn_task(Priority::default(), async move { sleep(Duration::from_millis(100)).await });
+    ///     // lets the first child task take the only slot before the others wait for it
+    ///     sleep(Duration::from_millis(20)).await;
+    ///     for (name, priority) in [("low", Priority::LOW), ("high", Priority::HIGH), ("utility", Priority::UTILITY)] {
+    ///         let started = order.clone();
+    ///         group.spawn_task(priority, async move { started.lock().unwrap().push(name) });
+    ///     }
+    ///     group.wait_for_all().await;
+    /// }).await;
+    ///
+    /// assert_eq!(*started.lock().unwrap(), vec!["high", "utility", "low"]);
+    /// # });
+    /// ```
+    pub fn set_max_concurrency(&mut self, max_concurrency: usize) {
+        self.runtime.set_max_concurrency(max_concurrency);
+    }
+
+    /// Limits how many of the child tasks of each priority in ``limits``, spawned afterwards, run at the same time,
+    /// so child tasks of a saturated priority wait while those of the other priorities keep starting.
+    ///
+    /// Priorities missing from ``limits``, or mapped to zero, have no limit of their own.
+    /// Child tasks also wait for the limit set by ``set_max_concurrency``, but only once their priority's limit let them through,
+    /// so they never take a slot of it while waiting.
+    ///
+    /// # Parameters
+    ///
+    /// * `limits`: the maximum number of child tasks of each priority running at the same time
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::collections::HashMap;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let peaks = with_spawn_group(|mut group| async move {
+    ///     group.set_concurrency_limits(HashMap::from([(Priority::HIGH, 2), (Priority::LOW, 3)]));
+    ///     let running = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0)]);
+    ///     let peaks = Arc::new([AtomicUsize::new(0), AtomicUsize::new(0)]);
+    ///     for (class, priority) in [(0, Priority::HIGH), (1, Priority::LOW)] {
+    ///         for _ in 0..8 {
+    ///             let (running, peaks) = (running.clone(), peaks.clone());
+    ///             group.spawn_task(priority, async move {
+    ///                 let now = running[class].fetch_add(1, Ordering::SeqCst) + 1;
+    ///                 peaks[class].fetch_max(now, Ordering::SeqCst);
+    ///                 sleep(Duration::from_millis(10)).await;
+    ///                 running[class].fetch_sub(1, Ordering::SeqCst);
+    ///             });
+    ///         }
+    ///     }
+    ///     group.wait_for_all().await;
+    ///     peaks
+    /// }).await;
+    ///
+    /// assert!(peaks[0].load(Ordering::SeqCst) <= 2);
+    /// assert!(peaks[1].load(Ordering::SeqCst) <= 3);
+    /// # });
+    /// ```
+    pub fn set_concurrency_limits(&mut self, limits: HashMap<Priority, usize>) {
+        self.runtime.set_concurrency_limits(limits);
+    }
+
+    /// Lets child tasks waiting for the concurrency limit set by ``set_max_concurrency`` age,
+    /// so their priority rises a level for every ``rate`` they waited, and child tasks of a low priority
+    /// eventually run even while child tasks of a higher priority keep getting spawned.
+    ///
+    /// Zero turns aging off, which is the default.
+    ///
+    /// # Parameters
+    ///
+    /// * `rate`: how long a child task waits for each level its priority rises
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::sync::{Arc, Mutex};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let started = Arc::new(Mutex::new(vec![]));
+    /// let order = started.clone();
+    /// with_spawn_group(|mut group| async move {
+    ///     group.set_max_concurrency(1);
+    ///     group.set_aging(Duration::from_millis(5));
+    ///     group.spawn_task(Priority::default(), async move { sleep(Duration::from_millis(100)).await });
+    ///     sleep(Duration::from_millis(20)).await;
+    ///     let low = order.clone();
+    ///     group.spawn_task(Priority::LOW, async move { low.lock().unwrap().push("low") });
+    ///     sleep(Duration::from_millis(70)).await;
+    ///     let high = order.clone();
+    ///     group.spawn_task(Priority::HIGH, async move { high.lock().unwrap().push("high") });
+    ///     group.wait_for_all().await;
+    /// }).await;
+    ///
+    /// // the low priority child task waited long enough to overtake the high priority one
+    /// assert_eq!(*started.lock().unwrap(), vec!["low", "high"]);
+    /// # });
+    /// ```
+    pub fn set_aging(&mut self, rate: Duration) {
+        self.runtime.set_aging(rate);
+    }
+
+    /// Sets how the child tasks waiting for the concurrency limit set by ``set_max_concurrency`` take turns,
+    /// such as ``SchedulingPolicy::WeightedFair`` to share the runs between their priorities rather than
+    /// always running the highest priority first, so child tasks of a low priority are never starved.
+    ///
+    /// The default is ``SchedulingPolicy::Strict``
+    ///
+    /// # Parameters
+    ///
+    /// * `scheduling_policy`: the scheduling policy to use
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority, SchedulingPolicy};
+    /// use std::sync::{Arc, Mutex};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let started = Arc::new(Mutex::new(vec![]));
+    /// let order = started.clone();
+    /// with_spawn_group(|mut group| async move {
+    ///     group.set_max_concurrency(1);
+    ///     group.set_scheduling_policy(SchedulingPolicy::WeightedFair(vec![
+    ///         (Priority::HIGH, 3),
+    ///         (Priority::LOW, 1),
+    ///     ]));
+    ///     // holds the only slot until every child task is waiting for it
+    ///     let permit = group.acquire_permit().await;
+    ///     for _ in 0..40 {
+    ///         for priority in [Priority::HIGH, Priority::LOW] {
+    ///             let order = order.clone();
+    ///             group.spawn_task(priority, async move { order.lock().unwrap().push(priority) });
+    ///         }
+    ///     }
+    ///     sleep(Duration::from_millis(200)).await;
+    ///     drop(permit);
+    ///     group.wait_for_all().await;
+    /// }).await;
+    ///
+    /// // paid jobs get about three runs for every run of free ones, which still make progress
+    /// let started = started.lock().unwrap();
+    /// let high = started[..40].iter().filter(|priority| **priority == Priority::HIGH).count();
+    /// assert!((27..=33).contains(&high), "{high} of the first 40 runs were of a high priority");
+    /// # });
+    /// ```
+    pub fn set_scheduling_policy(&mut self, scheduling_policy: SchedulingPolicy) {
+        self.runtime.set_scheduling_policy(scheduling_policy);
+    }
+
+    /// Waits for a slot of the concurrency limit set by ``set_max_concurrency``, for work outside of the spawn group
+    /// which should count against the same limit, and returns a guard which gives the slot back when dropped.
+    ///
+    /// The caller waits in line with the child tasks: behind the ones of a higher priority, and behind the ones of
+    /// the default priority spawned so far, while child tasks spawned afterwards wait behind it.
+    /// While the guard is held, one less child task runs at the same time, so holding it across waiting for the spawn group
+    /// with a limit of one never finishes.
+    ///
+    /// Without a concurrency limit, it returns right away with a guard which holds nothing.
+    /// Changing the limit afterwards doesn't affect guards already held.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let started = Arc::new(AtomicBool::new(false));
+    /// let running = started.clone();
+    /// with_spawn_group(|mut group| async move {
+    ///     group.set_max_concurrency(1);
+    ///     // a pre-flight step taking the only slot
+    ///     let permit = group.acquire_permit().await;
+    ///     group.spawn_task(Priority::default(), async move { running.store(true, Ordering::SeqCst) });
+    ///     sleep(Duration::from_millis(50)).await;
+    ///     assert!(!started.load(Ordering::SeqCst));
+    ///
+    ///     drop(permit);
+    ///     group.wait_for_all().await;
+    ///     assert!(started.load(Ordering::SeqCst));
+    /// }).await;
+    /// # });
+    /// ```
+    pub async fn acquire_permit(&self) -> Permit {
+        self.runtime.acquire_permit(Priority::default()).await
+    }
+
+    /// Paces the starts of the child tasks spawned afterwards to at most ``per_second`` a second,
+    /// spreading a burst of them out evenly rather than starting them all at once.
+    ///
+    /// The rate limits how often child tasks start, while ``set_max_concurrency`` limits how many run at the same time.
+    /// With both, a child task first waits until it may run, then until its start is due.
+    ///
+    /// Zero removes the limit, which is the default.
+    ///
+    /// # Parameters
+    ///
+    /// * `per_second`: the highest number of child tasks starting a second
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let mut starts: Vec<Instant> = with_spawn_group(|mut group| async move {
+    ///     group.set_rate_limit(20);
+    ///     for _ in 0..5 {
+    ///         group.spawn_task(Priority::default(), async { Instant::now() });
+    ///     }
+    ///     group.wait_for_all_items().await
+    /// }).await;
+    ///
+    /// starts.sort();
+    /// // five starts at 20 a second take at least four intervals of 50 milliseconds
+    /// assert!(starts[4] - starts[0] >= Duration::from_millis(190));
+    /// # });
+    /// ```
+    pub fn set_rate_limit(&mut self, per_second: u32) {
+        self.runtime.set_rate_limit(per_second);
+    }
+
+    /// Limits the number of finished child tasks' results waiting to be consumed,
+    /// so child tasks wait for the consumer before storing more results.
+    ///
+    /// Zero, the default, means no limit
+    ///
+    /// # Parameters
+    ///
+    /// * `max_buffered`: the highest number of results waiting to be consumed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let results = with_spawn_group(|mut group| async move {
+    ///     group.set_max_buffered(2);
+    ///     for i in 0..10 {
+    ///         group.spawn_task(Priority::default(), async move { vec![i; 1024] });
+    ///     }
+    ///     group.wait_for_all_items().await
+    /// }).await;
+    ///
+    /// assert_eq!(results.len(), 10);
+    /// # });
+    /// ```
+    pub fn set_max_buffered(&mut self, max_buffered: usize) {
+        self.runtime.set_max_buffered(max_buffered);
+    }
+
+    /// Allocates room for ``additional`` more child tasks and their results up front,
+    /// so spawning a large batch of child tasks doesn't keep reallocating while holding the spawn group's locks
+    ///
+    /// Room for the results is only reserved up to the limit set by ``set_max_buffered``,
+    /// and skipped rather than waited for while the results are being consumed at the same time.
+    ///
+    /// # Parameters
+    ///
+    /// * `additional`: the number of child tasks about to be spawned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let results = with_spawn_group(|mut group| async move {
+    ///     group.reserve(10_000);
+    ///     for i in 0..10_000 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     group.wait_for_all_items().await
+    /// }).await;
+    ///
+    /// assert_eq!(results.len(), 10_000);
+    /// # });
+    /// ```
+    pub fn reserve(&mut self, additional: usize) {
+        self.runtime.reserve(additional);
+    }
+
+    /// Fixes the order the results are yielded in to one which only depends on the seed
+    /// and the order the child tasks were spawned in, so tests can assert the exact output.
+    ///
+    /// Child tasks still run concurrently, but no result is yielded until every child task finished,
+    /// and the limit set by ``set_max_buffered`` no longer applies.
+    /// Only available with the ``deterministic`` feature, which is meant for tests.
+    ///
+    /// # Parameters
+    ///
+    /// * `seed`: the seed the order is derived from
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// async fn run(seed: u64) -> Vec<u64> {
+    ///     with_spawn_group(move |mut group| async move {
+    ///         group.set_deterministic_seed(seed);
+    ///         for i in 0..10 {
+    ///             group.spawn_task(Priority::default(), async move {
+    ///                 sleep(Duration::from_millis(10 - i)).await;
+    ///                 i
+    ///             });
+    ///         }
+    ///         group.wait_for_all_items().await
+    ///     }).await
+    /// }
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let first = run(42).await;
+    /// assert_eq!(first, run(42).await);
+    ///
+    /// let mut sorted = first.clone();
+    /// sorted.sort();
+    /// assert_eq!(sorted, (0..10).collect::<Vec<u64>>());
+    /// # });
+    /// ```
+    #[cfg(feature = "deterministic")]
+    pub fn set_deterministic_seed(&mut self, seed: u64) {
+        self.runtime.set_drain_seed(seed);
+    }
+
+    /// Registers a closure called with the result of each child task as soon as it finishes,
+    /// before the result is stored in the spawn group.
+    ///
+    /// Closures are called on the threads driving the child tasks, in the order they were registered.
+    ///
+    /// # Parameters
+    ///
+    /// * `observer`: the closure to call
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    /// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let finished = Arc::new(AtomicUsize::new(0));
+    /// let finished_count = finished.clone();
+    /// with_spawn_group(|mut group| async move {
+    ///     group.on_complete(move |_: &i32| {
+    ///         finished_count.fetch_add(1, Ordering::SeqCst);
+    ///     });
+    ///     for i in 0..5 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     group.wait_for_all().await;
+    /// }).await;
+    ///
+    /// assert_eq!(finished.load(Ordering::SeqCst), 5);
+    /// # });
+    /// ```
+    pub fn on_complete<F>(&mut self, observer: F)
+    where
+        F: Fn(&ValueType) + Send + Sync + 'static,
+    {
+        self.runtime.on_complete(observer);
+    }
+
+    /// Registers a closure called with the id of every child task right before it starts running,
+    /// which is after it waited for the concurrency limit and the rate limit, if any.
+    ///
+    /// Closures are called on the threads driving the child tasks, in the order they were registered.
+    ///
+    /// # Parameters
+    ///
+    /// * `observer`: the closure to call
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority, TaskId};
+    /// use std::sync::{Arc, Mutex};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let started: Arc<Mutex<Vec<TaskId>>> = Arc::new(Mutex::new(vec![]));
+    /// let observed = started.clone();
+    /// with_spawn_group(|mut group| async move {
+    ///     group.set_max_concurrency(1);
+    ///     group.on_start(move |id| observed.lock().unwrap().push(id));
+    ///     let first = group.spawn_task(Priority::default(), async { sleep(Duration::from_millis(200)).await });
+    ///     let second = group.spawn_task(Priority::default(), async {});
+    ///     while started.lock().unwrap().is_empty() {
+    ///         sleep(Duration::from_millis(1)).await;
+    ///     }
+    ///     // the second child task was submitted, but waits for the first one to finish before starting
+    ///     assert_eq!(*started.lock().unwrap(), vec![first]);
+    ///     group.wait_for_all().await;
+    ///     assert_eq!(*started.lock().unwrap(), vec![first, second]);
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn on_start<F>(&mut self, observer: F)
+    where
+        F: Fn(TaskId) + Send + Sync + 'static,
+    {
+        self.runtime.on_start(observer);
+    }
+
+    /// Returns the cancellation token of the spawn group.
+    ///
+    /// The token is cancelled as soon as the spawn group is cancelled, so child tasks holding a clone of it
+    /// can observe the cancellation and clean up within the grace period set by ``set_cancellation_grace_period``.
+    /// Once cancelled, the token stays cancelled.
+    ///
+    /// When the spawn group was created while a child task of another spawn group runs, its token is a child token
+    /// of that spawn group's token, so it gets cancelled along with the enclosing spawn group.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, yield_now, Priority};
+    /// use futures_lite::StreamExt;
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let result = with_spawn_group(|mut group| async move {
+    ///     group.set_cancellation_grace_period(Duration::from_secs(5));
+    ///     let token = group.cancellation_token();
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         while !token.is_cancelled() {
+    ///             yield_now().await;
+    ///         }
+    ///         "cleaned up"
+    ///     });
+    ///     group.cancel_all();
+    ///     group.next().await
+    /// }).await;
+    ///
+    /// assert_eq!(result, Some("cleaned up"));
+    /// # });
+    /// ```
+    ///
+    /// Cancelling a spawn group cancels the tokens of the spawn groups nested in its child tasks,
+    /// so their own child tasks wind down too:
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let stopped: usize = with_spawn_group(|mut crawl| async move {
+    ///     crawl.set_cancellation_grace_period(Duration::from_secs(5));
+    ///     for _ in 0..2 {
+    ///         crawl.spawn_task(Priority::default(), async move {
+    ///             with_spawn_group(|mut page| async move {
+    ///                 for _ in 0..3 {
+    ///                     let token = page.cancellation_token();
+    ///                     page.spawn_task(Priority::default(), async move {
+    ///                         token.cancelled().await;
+    ///                         1
+    ///                     });
+    ///                 }
+    ///                 page.wait_for_all_items().await.into_iter().sum::<usize>()
+    ///             })
+    ///             .await
+    ///         });
+    ///     }
+    ///     // every child task finishes once the tokens beneath the root one are cancelled
+    ///     crawl.cancel_all();
+    ///     crawl.wait_for_all_items().await.into_iter().sum()
+    /// }).await;
+    ///
+    /// assert_eq!(stopped, 6);
+    /// # });
+    /// ```
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.runtime.token()
+    }
+
+    /// Returns a handle which spawns child tasks into the spawn group through a shared reference,
+    /// so child tasks can capture a clone of it to spawn more child tasks while they run.
+    ///
+    /// Waiting for the spawn group also waits for the child tasks spawned through it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority, Spawner};
+    /// use std::{future::Future, pin::Pin};
+    ///
+    /// // counts the nodes of a binary tree of the given depth, one child task per node
+    /// fn visit(spawner: Spawner<u32>, depth: u32) -> Pin<Box<dyn Future<Output = u32> + Send>> {
+    ///     Box::pin(async move {
+    ///         if depth > 0 {
+    ///             for _ in 0..2 {
+    ///                 spawner.spawn(Priority::default(), visit(spawner.clone(), depth - 1));
+    ///             }
+    ///         }
+    ///         1
+    ///     })
+    /// }
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let nodes = with_spawn_group(|mut group| async move {
+    ///     let spawner = group.spawner();
+    ///     group.spawn_task(Priority::default(), visit(spawner, 3));
+    ///     group.wait_for_all_items().await.into_iter().sum::<u32>()
+    /// }).await;
+    ///
+    /// assert_eq!(nodes, 15);
+    /// # });
+    /// ```
+    pub fn spawner(&self) -> Spawner<ValueType> {
+        Spawner::new(self.runtime.clone(), Some(self.count.clone()))
+    }
+
+    /// Sets how long cancelling waits for child tasks to finish on their own after the cancellation token is cancelled,
+    /// before they get forcefully cancelled.
+    ///
+    /// The default is zero, which forcefully cancels child tasks right away.
+    ///
+    /// ``cancel_all`` and dropping the spawn group block the thread they're called on until the child tasks finished
+    /// or the grace period is over, while ``first_and_cancel``, ``wait_until``, ``take_and_cancel``, ``cancel_and_wait`` and ``close``
+    /// wait for it without blocking the caller's executor.
+    ///
+    /// # Parameters
+    ///
+    /// * `grace_period`: the longest time to wait for child tasks to finish on their own
+    ///
+    /// # Example
+    ///
+    /// The grace period also applies when the future returned by ``with_spawn_group()`` is dropped early,
+    /// since the scope shares the spawn group's configuration.
+    ///
+    /// ```rust
+    /// use futures_lite::future;
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+    /// use std::time::Duration;
+    ///
+    /// let cleaned_up = Arc::new(AtomicBool::new(false));
+    /// let flag = cleaned_up.clone();
+    /// spawn_groups::block_on(future::or(
+    ///     with_spawn_group(move |mut group| async move {
+    ///         group.set_cancellation_grace_period(Duration::from_secs(5));
+    ///         let token = group.cancellation_token();
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             token.cancelled().await;
+    ///             flag.store(true, Ordering::SeqCst);
+    ///         });
+    ///         std::future::pending::<()>().await
+    ///     }),
+    ///     sleep(Duration::from_millis(50)),
+    /// ));
+    /// assert!(cleaned_up.load(Ordering::SeqCst));
+    /// ```
+    ///
+    /// ``cancel_and_wait`` and ``cancel_and_collect`` wait for the grace period on the caller's executor:
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     group.set_cancellation_grace_period(Duration::from_secs(5));
+    ///     let token = group.cancellation_token();
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         token.cancelled().await;
+    ///         "cleaned up"
+    ///     });
+    ///     assert_eq!(group.cancel_and_collect().await, vec!["cleaned up"]);
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn set_cancellation_grace_period(&mut self, grace_period: Duration) {
+        self.runtime.set_grace_period(grace_period);
+    }
+
+    /// Registers a recorder which receives an event whenever a child task is spawned, returns, is cancelled or panics,
+    /// replacing the previous one.
+    ///
+    /// Only child tasks spawned afterwards report their events to it.
+    ///
+    /// # Parameters
+    ///
+    /// * `recorder`: the recorder to report to
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, MetricsRecorder, Priority};
+    /// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+    /// use std::time::Duration;
+    ///
+    /// #[derive(Default)]
+    /// struct Counters {
+    ///     spawned: AtomicUsize,
+    ///     completed: AtomicUsize,
+    ///     panicked: AtomicUsize,
+    /// }
+    ///
+    /// struct Recorder(Arc<Counters>);
+    ///
+    /// impl MetricsRecorder for Recorder {
+    ///     fn on_spawn(&self) {
+    ///         self.0.spawned.fetch_add(1, Ordering::SeqCst);
+    ///     }
+    ///
+    ///     fn on_complete(&self, _duration: Duration) {
+    ///         self.0.completed.fetch_add(1, Ordering::SeqCst);
+    ///     }
+    ///
+    ///     fn on_panic(&self) {
+    ///         self.0.panicked.fetch_add(1, Ordering::SeqCst);
+    ///     }
+    /// }
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let counters = Arc::new(Counters::default());
+    /// let recorder = Recorder(counters.clone());
+    /// with_spawn_group(|mut group| async move {
+    ///     group.set_metrics_recorder(recorder);
+    ///     for i in 0..4 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             if i == 3 {
+    ///                 panic!("child task {i} failed");
+    ///             }
+    ///             i
+    ///         });
+    ///     }
+    ///     group.wait_for_all().await;
+    /// }).await;
+    ///
+    /// assert_eq!(counters.spawned.load(Ordering::SeqCst), 4);
+    /// assert_eq!(counters.completed.load(Ordering::SeqCst), 3);
+    /// assert_eq!(counters.panicked.load(Ordering::SeqCst), 1);
+    /// # });
+    /// ```
+    pub fn set_metrics_recorder<R>(&mut self, recorder: R)
+    where
+        R: MetricsRecorder + 'static,
+    {
+        self.runtime.set_metrics_recorder(Arc::new(recorder));
+    }
+}
+
+impl<ValueType: Send + 'static> SpawnGroup<ValueType> {
+    /// Spawns a new task into the spawn group
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Returns
+    /// The id of the child task, which ``cancel_task`` takes to cancel it on its own
+    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F) -> TaskId
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        self.add_task(priority, closure)
+    }
+
+    /// Spawns a new task into the spawn group whose result gets converted into ``ValueType``,
+    /// which lets child tasks of different result types share a spawn group whose ``ValueType`` is an enum over them,
+    /// such as one generated by the ``spawn_group_enum!`` macro
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of a type convertible into ``ValueType``
+    ///
+    /// # Returns
+    /// The id of the child task, which ``cancel_task`` takes to cancel it on its own
+    pub fn spawn_task_into<F, T>(&mut self, priority: Priority, closure: F) -> TaskId
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Into<ValueType>,
+    {
+        self.add_task(priority, async move { closure.await.into() })
+    }
+
+    /// Spawns a new task into the spawn group whose result can also be awaited on its own,
+    /// through the returned ``TaskResult``, while it still gets stored like any other child task's result
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let (critical, mut all) = with_spawn_group(|mut group| async move {
+    ///     for i in 0..3 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     let critical = group.spawn_task_with_result(Priority::HIGH, async { 10 });
+    ///     (critical.await, group.wait_for_all_items().await)
+    /// }).await;
+    ///
+    /// all.sort();
+    /// assert_eq!(critical, Some(10));
+    /// assert_eq!(all, vec![0, 1, 2, 10]);
+    /// # });
+    /// ```
+    pub fn spawn_task_with_result<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+    ) -> TaskResult<ValueType>
+    where
+        F: Future<Output = ValueType> + Send + 'static,
+        ValueType: Clone,
+    {
+        self.increment_count();
+        self.runtime.write_task_with_result(priority, closure)
+    }
+
+    /// Spawns a new task into the spawn group which drives the stream to its end,
+    /// storing every item it yields as a result on its own, so a single child task can contribute many results
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `stream`: a stream that yields values of type ``ValueType``
+    ///
+    /// # Returns
+    /// The id of the child task, which ``cancel_task`` takes to cancel it on its own
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let mut lines = with_spawn_group(|mut group| async move {
+    ///     group.spawn_stream_task(Priority::default(), futures_lite::stream::iter(["first", "second"]));
+    ///     group.spawn_task(Priority::default(), async { "third" });
+    ///     group.wait_for_all_items().await
+    /// }).await;
+    ///
+    /// lines.sort();
+    /// assert_eq!(lines, vec!["first", "second", "third"]);
+    /// # });
+    /// ```
+    pub fn spawn_stream_task<St>(&mut self, priority: Priority, stream: St) -> TaskId
+    where
+        St: Stream<Item = ValueType> + Send + 'static,
+    {
+        self.increment_count();
+        self.runtime.write_stream_task(priority, stream)
+    }
+
+    /// Spawns a new task into the spawn group which gets cancelled if it doesn't finish in time,
+    /// in which case it stores no result
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `timeout`: the longest time the child task may run for
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let now = Instant::now();
+    /// let results = with_spawn_group(|mut group| async move {
+    ///     group.spawn_task_with_timeout(Priority::default(), Duration::from_millis(50), async {
+    ///         sleep(Duration::from_secs(10)).await;
+    ///         "slow"
+    ///     });
+    ///     group.spawn_task(Priority::default(), async { "fast" });
+    ///     group.wait_for_all_items().await
+    /// }).await;
+    ///
+    /// assert_eq!(results, vec!["fast"]);
+    /// assert!(now.elapsed() < Duration::from_secs(10));
+    /// # });
+    /// ```
+    pub fn spawn_task_with_timeout<F>(
+        &mut self,
+        priority: Priority,
+        timeout: Duration,
+        closure: F,
+    ) -> TaskId
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        self.increment_count();
+        self.runtime
+            .write_task_with_timeout(priority, timeout, closure)
+    }
+
+    /// Spawns a new child task which runs a blocking closure, such as a CPU-bound computation
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: a closure that return a value of type ``ValueType``
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let sum: u64 = with_spawn_group(|mut group| async move {
+    ///     for i in 0..4u64 {
+    ///         group.spawn_blocking_task(Priority::default(), move || (0..=i * 1000).sum::<u64>());
+    ///     }
+    ///     group.wait_for_all_items().await.into_iter().sum()
+    /// }).await;
+    ///
+    /// assert_eq!(sum, 500500 + 2001000 + 4501500);
+    /// # });
+    /// ```
+    pub fn spawn_blocking_task<F>(&mut self, priority: Priority, closure: F) -> TaskId
+    where
+        F: FnOnce() -> ValueType + Send + 'static,
+    {
+        self.increment_count();
+        self.runtime.write_blocking_task(priority, closure)
+    }
+
+    /// Spawns a new child task with a name that shows up in ``debug_snapshot()``
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `name`: the name of the child task
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    pub fn spawn_named_task<F>(&mut self, priority: Priority, name: &str, closure: F) -> TaskId
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        self.increment_count();
+        self.runtime.write_named_task(priority, name, closure)
+    }
+
+    /// Spawns a new child task which only starts once the child tasks of the given ids finished,
+    /// so child tasks can depend on each other the way build steps do
+    ///
+    /// A child task counts as finished once it returned, panicked or was cancelled,
+    /// and ids of child tasks that were already waited for, or that are unknown to the spawn group, count as finished too.
+    /// Nothing detects cycles, so child tasks that depend on each other never start.
+    ///
+    /// The child task waits for its prerequisites before acquiring its turn under ``set_max_concurrency``,
+    /// so it never holds one up while waiting.
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `after`: the ids of the child tasks to wait for
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::sync::{Arc, Mutex};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let steps = Arc::new(Mutex::new(vec![]));
+    /// let log = steps.clone();
+    /// with_spawn_group(|mut group| async move {
+    ///     let mut objects = vec![];
+    ///     for (file, delay) in [("a.o", 80), ("b.o", 20)] {
+    ///         let log = log.clone();
+    ///         objects.push(group.spawn_task(Priority::default(), async move {
+    ///             sleep(Duration::from_millis(delay)).await;
+    ///             log.lock().unwrap().push(file);
+    ///         }));
+    ///     }
+    ///     group.spawn_task_after(Priority::HIGH, &objects, async move {
+    ///         log.lock().unwrap().push("app");
+    ///     });
+    /// }).await;
+    ///
+    /// let steps = steps.lock().unwrap();
+    /// assert_eq!(steps.len(), 3);
+    /// assert_eq!(steps[2], "app");
+    /// # });
+    /// ```
+    pub fn spawn_task_after<F>(
+        &mut self,
+        priority: Priority,
+        after: &[TaskId],
+        closure: F,
+    ) -> TaskId
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        self.increment_count();
+        self.runtime.write_task_after(priority, after, closure)
+    }
+
+    /// Spawn a new task only if the group is neither cancelled nor closed yet,
+    /// otherwise does nothing
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Returns
+    /// Whether the child task was spawned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     assert!(group.spawn_task_unless_cancelled(Priority::default(), async { 1 }));
+    ///     group.cancel_all();
+    ///     assert!(!group.spawn_task_unless_cancelled(Priority::default(), async { 2 }));
+    ///     assert_eq!(group.pending_count(), 0);
+    ///     assert!(group.wait_for_all_items().await.len() <= 1);
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn spawn_task_unless_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        self.add_task_unlessed_cancelled(priority, closure)
+    }
+
+    /// Spawns a new task like ``spawn_task_unless_cancelled`` does, but tells why the child task wasn't spawned
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Returns
+    /// - Ok: the id of the child task, which ``cancel_task`` takes to cancel it on its own
+    /// - Err: ``SpawnError::Cancelled`` if the spawn group was cancelled, ``SpawnError::Closed`` if it was closed,
+    ///   or ``SpawnError::Saturated`` if ``SaturationPolicy::Reject`` rejected the child task
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority, SpawnError};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     assert!(group.try_spawn_task(Priority::default(), async { 1 }).is_ok());
+    ///     group.close_gracefully();
+    ///     let error = group.try_spawn_task(Priority::default(), async { 2 }).unwrap_err();
+    ///     assert!(matches!(error, SpawnError::Closed));
+    ///     assert_eq!(group.wait_for_all_items().await, vec![1]);
+    /// }).await;
+    ///
+    /// with_spawn_group(|mut group| async move {
+    ///     group.cancel_all();
+    ///     let error = group.try_spawn_task(Priority::default(), async { 1 }).unwrap_err();
+    ///     assert!(matches!(error, SpawnError::Cancelled));
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn try_spawn_task<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+    ) -> Result<TaskId, SpawnError>
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        let id: TaskId = self.runtime.try_write_task(priority, closure)?;
+        self.increment_count();
+        Ok(id)
+    }
+
+    /// Spawns a new task like ``try_spawn_task`` does, but while ``SaturationPolicy::Block`` holds it back,
+    /// it waits on the caller's executor instead of blocking its thread
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    ///
+    /// # Returns
+    /// The same as ``try_spawn_task`` does
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{spawn_group::SpawnGroupBuilder, Priority, SaturationPolicy};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let mut group = SpawnGroupBuilder::new().threads(1).build();
+    /// group.set_saturation_policy(SaturationPolicy::Block);
+    /// for i in 0..3 {
+    ///     let spawned = group.try_spawn_task_async(Priority::default(), async move {
+    ///         std::thread::sleep(Duration::from_millis(20));
+    ///         i
+    ///     });
+    ///     assert!(spawned.await.is_ok());
+    /// }
+    /// let mut results = group.wait_for_all_items().await;
+    /// results.sort();
+    /// assert_eq!(results, vec![0, 1, 2]);
+    /// # });
+    /// ```
+    pub async fn try_spawn_task_async<F>(
+        &mut self,
+        priority: Priority,
+        closure: F,
+    ) -> Result<TaskId, SpawnError>
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        let id: TaskId = self.runtime.try_write_task_async(priority, closure).await?;
+        self.increment_count();
+        Ok(id)
+    }
+
+    /// Spawn a new task only if the group is neither cancelled nor closed yet,
+    /// otherwise does nothing
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: priority to use
+    /// * `closure`: an async closure that return a value of type ``ValueType``
+    #[deprecated(since = "2.0.0", note = "Use spawn_task_unless_cancelled instead")]
+    pub fn spawn_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
+    where
+        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+    {
+        self.spawn_task_unless_cancelled(priority, closure);
+    }
+
+    /// Cancels all running task in the spawn group
+    pub fn cancel_all(&mut self) {
+        self.cancel_all_tasks();
+    }
+
+    /// Cancels all running task in the spawn group, and waits until every one of them actually stopped
+    ///
+    /// Unlike ``cancel_all``, which only requests the cancellation, a child task being polled on another thread
+    /// is done running once this returns.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, sleep, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     for i in 0..4 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             sleep(Duration::from_secs(10)).await;
+    ///             i
+    ///         });
+    ///     }
+    ///     group.cancel_and_wait().await;
+    ///     assert!(group.is_cancelled);
+    ///     assert_eq!(group.pending_count(), 0);
+    /// }).await;
+    /// # });
+    /// ```
+    pub async fn cancel_and_wait(&mut self) {
+        self.runtime.cancel_and_wait().await;
+        self.is_cancelled = true;
+        self.decrement_count_to_zero();
+    }
+
+    /// Cancels all running task in the spawn group, waits until every one of them actually stopped like ``cancel_and_wait`` does,
+    /// and returns every result stored before the abort, in the order they finished
+    ///
+    /// Child tasks that were cancelled right after returning still contribute their result,
+    /// while those aborted halfway, such as while waiting for room under ``set_max_buffered``, don't.
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks that returned, which is empty if none did
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority, TaskStatus};
+    /// use std::future::pending;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     let done = group.spawn_task(Priority::default(), async { 1 });
+    ///     while group.task_status(done) != Some(TaskStatus::Completed) {
+    ///         spawn_groups::yield_now().await;
+    ///     }
+    ///     for _ in 0..3 {
+    ///         group.spawn_task(Priority::default(), pending::<i32>());
+    ///     }
+    ///     assert_eq!(group.cancel_and_collect().await, vec![1]);
+    ///     assert!(group.is_cancelled);
+    ///     assert_eq!(group.pending_count(), 0);
+    /// }).await;
+    /// # });
+    /// ```
+    pub async fn cancel_and_collect(&mut self) -> Vec<ValueType> {
+        let results: Vec<ValueType> = self.runtime.cancel_and_collect().await;
+        self.is_cancelled = true;
+        self.decrement_count_to_zero();
+        results
+    }
+
+    /// Closes the spawn group gracefully, so it rejects every child task spawned afterwards
+    /// while the already spawned ones keep running until they finish.
+    ///
+    /// Unlike cancelling, nothing gets cancelled. ``spawn_task_unless_cancelled`` returns false for a rejected child task,
+    /// while the other spawn methods return an id which matches no child task.
+    /// Waiting for the spawn group afterwards waits for the already spawned child tasks only.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let results = with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async {
+    ///         sleep(Duration::from_millis(50)).await;
+    ///         "in flight"
+    ///     });
+    ///     group.close_gracefully();
+    ///     assert!(group.is_closed());
+    ///     assert!(!group.spawn_task_unless_cancelled(Priority::default(), async { "rejected" }));
+    ///     group.spawn_task(Priority::default(), async { "rejected" });
+    ///     group.wait_for_all_items().await
+    /// }).await;
+    ///
+    /// assert_eq!(results, vec!["in flight"]);
+    /// # });
+    /// ```
+    pub fn close_gracefully(&mut self) {
+        self.runtime.close();
+    }
+
+    /// Returns whether the spawn group was closed gracefully with ``close_gracefully``
+    pub fn is_closed(&self) -> bool {
+        self.runtime.is_closed()
+    }
+
+    /// Cancels a single child task, leaving the other child tasks running
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: the id the child task was spawned with
+    ///
+    /// # Returns
+    /// Whether the child task was cancelled, which is false if it already finished
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let results = with_spawn_group(|mut group| async move {
+    ///     let stuck = group.spawn_task(Priority::default(), std::future::pending());
+    ///     let quick = group.spawn_task(Priority::default(), async { "quick" });
+    ///     assert!(group.cancel_task(stuck));
+    ///     group.wait_for_all().await;
+    ///     assert!(!group.cancel_task(quick));
+    ///     group.wait_for_all_items().await
+    /// }).await;
+    ///
+    /// assert_eq!(results, vec!["quick"]);
+    /// # });
+    /// ```
+    ///
+    /// Cancelling child tasks while they finish on their own counts each of them as finished exactly once,
+    /// so waiting for the spawn group still returns
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     for round in 0..20 {
+    ///         let ids: Vec<_> = (0..10)
+    ///             .map(|i| group.spawn_task(Priority::default(), async move { round * 10 + i }))
+    ///             .collect();
+    ///         let cancelled = ids.into_iter().filter(|&id| group.cancel_task(id)).count();
+    ///         let results = group.wait_for_all_items().await;
+    ///         assert_eq!(results.len() + cancelled, 10);
+    ///         assert!(group.is_finished());
+    ///     }
+    ///     assert_eq!(group.pending_count(), 0);
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn cancel_task(&mut self, id: TaskId) -> bool {
+        let cancelled: bool = self.runtime.cancel_task(id);
+        if cancelled {
+            self.decrement_count();
+        }
+        cancelled
+    }
+
+    /// Cancels every child task of a priority strictly lower than ``priority``,
+    /// leaving the child tasks of the same or a higher priority running
+    ///
+    /// # Parameters
+    ///
+    /// * `priority`: the lowest priority of the child tasks to keep
+    ///
+    /// # Returns
+    /// The number of child tasks cancelled, which leaves out those that already finished
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let mut results = with_spawn_group(|mut group| async move {
+    ///     for priority in [Priority::BACKGROUND, Priority::LOW, Priority::MEDIUM, Priority::HIGH] {
+    ///         group.spawn_task(priority, async move {
+    ///             // background jobs never finish unless cancelled
+    ///             if priority < Priority::MEDIUM {
+    ///                 std::future::pending::<()>().await;
+    ///             }
+    ///             priority
+    ///         });
+    ///     }
+    ///     assert_eq!(group.cancel_below(Priority::MEDIUM), 2);
+    ///     group.wait_for_all_items().await
+    /// }).await;
+    ///
+    /// results.sort();
+    /// assert_eq!(results, vec![Priority::MEDIUM, Priority::HIGH]);
+    /// # });
+    /// ```
+    pub fn cancel_below(&mut self, priority: Priority) -> usize {
+        let cancelled: usize = self.runtime.cancel_below(priority);
+        for _ in 0..cancelled {
+            self.decrement_count();
+        }
+        cancelled
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// Returns the first element of the stream, or None if it is empty.
+    pub async fn first(&self) -> Option<ValueType> {
+        self.runtime.stream().first().await
+    }
+
+    /// Waits for the earliest child task to finish, then cancels all the other child tasks.
+    ///
+    /// Results of other child tasks that finished before the cancellation stay in the stream.
+    ///
+    /// # Returns
+    /// The result of the earliest child task to finish, or None if no child task was spawned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let winner = with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), std::future::pending());
+    ///     group.spawn_task(Priority::default(), async { "fast" });
+    ///     // the group only gets dropped here because the pending child task is cancelled
+    ///     group.first_and_cancel().await
+    /// }).await;
+    ///
+    /// assert_eq!(winner, Some("fast"));
+    /// # });
+    /// ```
+    pub async fn first_and_cancel(&mut self) -> Option<ValueType> {
+        let result: Option<ValueType> = self.runtime.stream().first().await;
+        self.cancel_all_tasks_async().await;
+        result
+    }
+
+    /// Waits for the earliest child task whose result matches the predicate, then cancels all the other child tasks.
+    ///
+    /// Results that don't match are dropped on the way, while results of other child tasks
+    /// that finished before the cancellation stay in the stream.
+    ///
+    /// # Parameters
+    ///
+    /// * `predicate`: a closure telling whether a result is the one waited for
+    ///
+    /// # Returns
+    /// The earliest matching result, or None if every child task finished without a match
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let found = with_spawn_group(|mut group| async move {
+    ///     for i in 1..=10 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             sleep(Duration::from_millis(5 * i)).await;
+    ///             i
+    ///         });
+    ///     }
+    ///     group.wait_until(|x| *x > 5).await
+    /// }).await;
+    /// assert!(found.is_some_and(|x| x > 5));
+    ///
+    /// let missing = with_spawn_group(|mut group| async move {
+    ///     for i in 1..=10 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     group.wait_until(|x| *x > 10).await
+    /// }).await;
+    /// assert_eq!(missing, None);
+    /// # });
+    /// ```
+    pub async fn wait_until<F>(&mut self, predicate: F) -> Option<ValueType>
+    where
+        F: Fn(&ValueType) -> bool,
+    {
+        let result: Option<ValueType> = self.runtime.stream().find(|item| predicate(item)).await;
+        self.cancel_all_tasks_async().await;
+        result
+    }
+
+    /// Waits for the earliest child tasks to finish, then cancels all the other child tasks.
+    ///
+    /// Waiting for more child tasks than are left returns the results of all of them.
+    /// Results of other child tasks that finished before the cancellation stay in the stream.
+    ///
+    /// # Parameters
+    ///
+    /// * `count`: the number of child tasks to wait for
+    ///
+    /// # Returns
+    /// A vector of at most `count` results of the child tasks in the order they finished
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let matches = with_spawn_group(|mut group| async move {
+    ///     for i in 0..100 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             if i % 10 != 0 {
+    ///                 // a search that never turns up anything
+    ///                 std::future::pending::<()>().await;
+    ///             }
+    ///             i
+    ///         });
+    ///     }
+    ///     group.take_and_cancel(5).await
+    /// }).await;
+    ///
+    /// assert_eq!(matches.len(), 5);
+    /// assert!(matches.iter().all(|i| i % 10 == 0));
+    /// # });
+    /// ```
+    pub async fn take_and_cancel(&mut self, count: usize) -> Vec<ValueType> {
+        let results: Vec<ValueType> = self.runtime.wait_for_first(count).await;
+        self.cancel_all_tasks_async().await;
+        results
+    }
+
+    /// Waits for all remaining child tasks to finish and collects their results,
+    /// unless a child task panics, then all the other child tasks are cancelled.
+    ///
+    /// Unlike the panic policy, which either swallows panics or resumes them, this surfaces the panic as an error.
+    ///
+    /// # Returns
+    /// - Ok: the results of all the child tasks in the order they finished
+    /// - Err: ``SpawnError::Panicked`` with the payload of the earliest child task to panic,
+    ///   or ``SpawnError::Cancelled`` if the spawn group was cancelled
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority, SpawnError};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let result = with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async move { panic!("out of disk") });
+    ///     // never finishes unless cancelled
+    ///     group.spawn_task(Priority::default(), std::future::pending::<i32>());
+    ///     group.wait_for_all_checked().await
+    /// }).await;
+    ///
+    /// let error: SpawnError = result.unwrap_err();
+    /// assert_eq!(error.panic_message(), Some("out of disk"));
+    /// # });
+    /// ```
+    pub async fn wait_for_all_checked(&mut self) -> Result<Vec<ValueType>, SpawnError> {
+        let results: Result<Vec<ValueType>, SpawnError> = self.runtime.wait_for_all_checked().await;
+        self.decrement_count_to_zero();
+        results
+    }
+
+    /// Waits for the next child task to finish without cancelling the other child tasks,
+    /// so results can be inspected one at a time before deciding whether to keep going
+    ///
+    /// # Returns
+    /// The result of the next child task to finish along with how many results are still to come,
+    /// or None once every child task finished and all their results were consumed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let found = with_spawn_group(|mut group| async move {
+    ///     for i in 0..10 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     let mut seen = 0;
+    ///     while let Some((value, remaining)) = group.next_completed().await {
+    ///         seen += 1;
+    ///         assert_eq!(seen + remaining, 10);
+    ///         if value == 7 {
+    ///             group.cancel_all();
+    ///             return true;
+    ///         }
+    ///     }
+    ///     false
+    /// }).await;
+    ///
+    /// assert!(found);
+    /// # });
+    /// ```
+    pub async fn next_completed(&mut self) -> Option<(ValueType, usize)> {
+        let result: ValueType = self.runtime.stream().first().await?;
+        self.decrement_count();
+        Some((result, self.runtime.stream().item_count()))
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// Waits for all remaining child tasks for finish.
+    ///
+    /// The waiting yields to the caller's executor instead of blocking its thread,
+    /// so it can race against other futures.
+    ///
+    /// Spawning needs a mutable borrow of the spawn group, so no child task can be spawned while it waits,
+    /// but a spawn group shared between threads behind a lock can alternate between spawning and waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use futures_lite::future;
+    /// use spawn_groups::{sleep, with_type_spawn_group, GetType, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_type_spawn_group(i32::TYPE, |mut group| async move {
+    ///     group.spawn_task(Priority::default(), std::future::pending());
+    ///     let timed_out = future::or(
+    ///         async {
+    ///             group.wait_for_all().await;
+    ///             false
+    ///         },
+    ///         async {
+    ///             sleep(Duration::from_millis(10)).await;
+    ///             true
+    ///         },
+    ///     )
+    ///     .await;
+    ///     assert!(timed_out);
+    ///     group.cancel_all();
+    /// }).await;
+    /// # });
+    /// ```
+    ///
+    /// ```rust
+    /// use spawn_groups::{spawn_group::{SpawnGroup, SpawnGroupBuilder}, Priority};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let group: Arc<Mutex<SpawnGroup<usize>>> = Arc::new(Mutex::new(SpawnGroupBuilder::new().build()));
+    /// let threads: Vec<_> = (0..4)
+    ///     .map(|_| {
+    ///         let group = group.clone();
+    ///         std::thread::spawn(move || {
+    ///             for round in 0..25 {
+    ///                 let mut group = group.lock().unwrap();
+    ///                 for i in 0..3 {
+    ///                     group.spawn_task(Priority::default(), async move { round * 3 + i });
+    ///                 }
+    ///                 if round % 2 == 0 {
+    ///                     spawn_groups::block_on(group.wait_for_all());
+    ///                 }
+    ///             }
+    ///         })
+    ///     })
+    ///     .collect();
+    /// for thread in threads {
+    ///     thread.join().unwrap();
+    /// }
+    ///
+    /// let group = group.lock().unwrap();
+    /// let results: Vec<usize> = spawn_groups::block_on(group.wait_for_all_items());
+    /// assert_eq!(results.len(), 4 * 25 * 3);
+    /// ```
+    pub async fn wait_for_all(&self) {
+        self.wait().await;
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their results
+    /// in the order they finished.
+    ///
+    /// Results already popped off the spawn group's stream are not returned again.
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks, which is empty if no child task was spawned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, with_type_spawn_group, GetType, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let mut results = with_spawn_group(|mut group| async move {
+    ///     for i in 0..5 {
+    ///         group.spawn_task(Priority::default(), async move { i * 2 });
+    ///     }
+    ///     group.wait_for_all_items().await
+    /// }).await;
+    ///
+    /// results.sort();
+    /// assert_eq!(results, vec![0, 2, 4, 6, 8]);
+    ///
+    /// let results = with_type_spawn_group(i32::TYPE, |group| async move {
+    ///     group.wait_for_all_items().await
+    /// }).await;
+    /// assert!(results.is_empty());
+    /// # });
+    /// ```
+    pub async fn wait_for_all_items(&self) -> Vec<ValueType> {
+        let results: Vec<ValueType> = self.runtime.wait_for_all().await;
+        self.decrement_count_to_zero();
+        results
+    }
+
+    /// Blocks the current thread until all remaining child tasks finished, and returns their results
+    /// in the order they finished, for callers without any asynchronous code, such as a plain ``fn main()``
+    ///
+    /// The wait is driven by the crate itself while the spawn group's backend drives the child tasks,
+    /// so no executor has to be picked or imported.
+    ///
+    /// # Deadlocks
+    /// It must not be called from asynchronous code, such as a child task of a spawn group or a future driven by Tokio,
+    /// since it blocks the thread that code runs on, which the child tasks it waits for may need to make progress.
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks, which is empty if no child task was spawned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{spawn_group::SpawnGroupBuilder, Priority};
+    ///
+    /// let mut group = SpawnGroupBuilder::new().build();
+    /// for i in 0..5 {
+    ///     group.spawn_task(Priority::default(), async move { i * 2 });
+    /// }
+    ///
+    /// let mut results = group.wait_for_all_blocking();
+    /// results.sort();
+    /// assert_eq!(results, vec![0, 2, 4, 6, 8]);
+    /// ```
+    pub fn wait_for_all_blocking(&mut self) -> Vec<ValueType> {
+        crate::block_on(self.wait_for_all_items())
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their results in the order they finished,
+    /// along with whether they are all of the child tasks' results.
+    ///
+    /// Results already popped off the spawn group's stream are not returned again.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Completion, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let (results, completion) = with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async { 1 });
+    ///     group.wait_for_all_items_with_completion().await
+    /// }).await;
+    ///
+    /// assert_eq!(results, vec![1]);
+    /// assert_eq!(completion, Completion::Finished);
+    /// # });
+    /// ```
+    pub async fn wait_for_all_items_with_completion(&self) -> (Vec<ValueType>, Completion) {
+        let results: Vec<ValueType> = self.wait_for_all_items().await;
+        (results, self.runtime.completion())
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their results
+    /// in the order the child tasks were spawned.
+    ///
+    /// Results already popped off the spawn group's stream are not returned again.
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks, which is empty if no child task was spawned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let results = with_spawn_group(|mut group| async move {
+    ///     for i in 0..5 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             sleep(Duration::from_millis(5 * (5 - i))).await;
+    ///             i
+    ///         });
+    ///     }
+    ///     group.wait_for_all_ordered().await
+    /// }).await;
+    ///
+    /// assert_eq!(results, vec![0, 1, 2, 3, 4]);
+    /// # });
+    /// ```
+    pub async fn wait_for_all_ordered(&self) -> Vec<ValueType> {
+        let results: Vec<ValueType> = self.runtime.wait_for_all_ordered().await;
+        self.decrement_count_to_zero();
+        results
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their results in the order they finished,
+    /// each paired with how long its child task took from being spawned to returning.
+    ///
+    /// Results already popped off the spawn group's stream are not returned again,
+    /// and cancelled child tasks leave no result behind.
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks along with their latencies, which is empty if no child task was spawned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let results = with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async { 0 });
+    ///     group.spawn_task(Priority::default(), async {
+    ///         sleep(Duration::from_millis(50)).await;
+    ///         1
+    ///     });
+    ///     let cancelled = group.spawn_task(Priority::default(), async {
+    ///         sleep(Duration::from_secs(10)).await;
+    ///         2
+    ///     });
+    ///     group.cancel_task(cancelled);
+    ///     group.wait_for_all_timed().await
+    /// }).await;
+    ///
+    /// assert_eq!(results.len(), 2);
+    /// let (_, latency) = results.iter().find(|(result, _)| *result == 1).unwrap();
+    /// assert!(*latency >= Duration::from_millis(50));
+    /// # });
+    /// ```
+    pub async fn wait_for_all_timed(&self) -> Vec<(ValueType, Duration)> {
+        let results: Vec<(ValueType, Duration)> = self.runtime.wait_for_all_timed().await;
+        self.decrement_count_to_zero();
+        results
+    }
+
+    /// Returns a future that waits for all remaining child tasks to finish
+    /// and outputs their results in the order they finished.
+    ///
+    /// Dropping the future before it is ready leaves the child tasks and their results in the spawn group,
+    /// so it can be raced against other futures such as a shutdown signal.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::future;
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async { 1 });
+    ///     group.spawn_task(Priority::default(), async {
+    ///         sleep(Duration::from_millis(50)).await;
+    ///         2
+    ///     });
+    ///     let shutdown = async {
+    ///         sleep(Duration::from_millis(5)).await;
+    ///         vec![]
+    ///     };
+    ///     let _ = future::or(group.all(), shutdown).await;
+    ///
+    ///     let mut results = group.all().await;
+    ///     results.sort();
+    ///     assert_eq!(results, vec![1, 2]);
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn all(&self) -> All<'_, ValueType> {
+        All {
+            group: self,
+            items: vec![],
+            finished: false,
+        }
+    }
+
+    /// Waits for all remaining child tasks to finish within a timeout, then cancels the child tasks still running.
+    ///
+    /// Results are drained while waiting, so child tasks never wait on a full buffer,
+    /// and results stored before the cancellation are never lost.
+    ///
+    /// # Parameters
+    ///
+    /// * `timeout`: the longest time to wait for the child tasks
+    ///
+    /// # Returns
+    /// The results of the finished child tasks in the order they finished,
+    /// along with whether every child task finished before the timeout elapsed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async { "fast" });
+    ///     let (results, finished) = group.wait_for_all_within(Duration::from_secs(5)).await;
+    ///     assert_eq!((results, finished), (vec!["fast"], true));
+    ///
+    ///     group.spawn_task(Priority::default(), std::future::pending());
+    ///     group.spawn_task(Priority::default(), async { "quick" });
+    ///     let (results, finished) = group.wait_for_all_within(Duration::from_millis(100)).await;
+    ///     assert_eq!((results, finished), (vec!["quick"], false));
+    /// }).await;
+    /// # });
+    /// ```
+    pub async fn wait_for_all_within(&mut self, timeout: Duration) -> (Vec<ValueType>, bool) {
+        let (mut results, finished) = self.runtime.drain_until(timeout).await;
+        if !finished {
+            self.cancel_all_tasks();
+        }
+        results.extend(self.wait_for_all_items().await);
+        (results, finished)
+    }
+
+    /// Waits for all remaining child tasks to finish within a timeout, then cancels the child tasks still running
+    /// and returns the results of the finished ones in the order they finished,
+    /// like ``wait_for_all_within`` does without telling whether every child task finished.
+    ///
+    /// Results already popped off the spawn group's stream are not returned again.
+    ///
+    /// # Parameters
+    ///
+    /// * `timeout`: the longest time to wait for the child tasks
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), std::future::pending());
+    ///     group.spawn_task(Priority::default(), async { "fast" });
+    ///     let results = group.wait_for_all_until(Duration::from_millis(100)).await;
+    ///     assert_eq!(results, vec!["fast"]);
+    ///     assert!(group.is_cancelled);
+    /// }).await;
+    /// # });
+    /// ```
+    ///
+    /// A limited buffer doesn't hold up the child tasks, since their results are drained while waiting:
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     group.set_max_buffered(1);
+    ///     for i in 0..4 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     let mut results = group.wait_for_all_until(Duration::from_secs(5)).await;
+    ///     results.sort();
+    ///     assert_eq!(results, vec![0, 1, 2, 3]);
+    ///     assert!(!group.is_cancelled);
+    /// }).await;
+    /// # });
+    /// ```
+    pub async fn wait_for_all_until(&mut self, timeout: Duration) -> Vec<ValueType> {
+        self.wait_for_all_within(timeout).await.0
+    }
+
+    /// Waits for the earliest child tasks to finish and returns their results
+    /// in the order they finished, leaving the other child tasks running.
+    ///
+    /// Waiting for more child tasks than are left returns the results of all of them.
+    ///
+    /// # Parameters
+    ///
+    /// * `count`: the number of child tasks to wait for
+    ///
+    /// # Returns
+    /// A vector of at most `count` results of the child tasks
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     for i in 0..5 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     assert_eq!(group.wait_for_first(2).await.len(), 2);
+    ///     assert_eq!(group.wait_for_first(10).await.len(), 3);
+    /// }).await;
+    /// # });
+    /// ```
+    pub async fn wait_for_first(&self, count: usize) -> Vec<ValueType> {
+        self.runtime.wait_for_first(count).await
+    }
+
+    /// Waits for the child tasks behind ``ids`` to finish and returns their results in the order of ``ids``,
+    /// leaving the other child tasks running and their results in the spawn group.
+    ///
+    /// Ids of child tasks that were cancelled or whose result was already consumed are skipped,
+    /// while the result of a child task that finished earlier is still returned.
+    ///
+    /// # Parameters
+    ///
+    /// * `ids`: the ids the child tasks were spawned with
+    ///
+    /// # Returns
+    /// A vector of the results of the child tasks, at most one for each id unless it was spawned with ``spawn_stream_task``
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     let ids: Vec<_> = (0..6)
+    ///         .map(|i| group.spawn_task(Priority::default(), async move {
+    ///             sleep(Duration::from_millis(10 * i)).await;
+    ///             i
+    ///         }))
+    ///         .collect();
+    ///     // resolves the dependencies of the next step only
+    ///     assert_eq!(group.join(&[ids[4], ids[1], ids[2]]).await, vec![4, 1, 2]);
+    ///     assert_eq!(group.join(&[ids[1]]).await, vec![]);
+    ///
+    ///     let mut rest = group.wait_for_all_items().await;
+    ///     rest.sort();
+    ///     assert_eq!(rest, vec![0, 3, 5]);
+    /// }).await;
+    /// # });
+    /// ```
+    pub async fn join(&self, ids: &[TaskId]) -> Vec<ValueType> {
+        self.runtime.join(ids).await
+    }
+
+    /// Waits for the child task behind ``id`` to finish and returns its result,
+    /// leaving the other child tasks running and their results in the spawn group
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: the id the child task was spawned with
+    ///
+    /// # Returns
+    /// The result of the child task, or ``None`` if it was cancelled, panicked or its result was already consumed
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     let id = group.spawn_task(Priority::default(), async { 1 });
+    ///     group.spawn_task(Priority::default(), async { 2 });
+    ///     assert_eq!(group.await_task(id).await, Some(1));
+    ///     assert_eq!(group.await_task(id).await, None);
+    ///     assert_eq!(group.wait_for_all_items().await, vec![2]);
+    /// }).await;
+    /// # });
+    /// ```
+    pub async fn await_task(&self, id: TaskId) -> Option<ValueType> {
+        self.runtime.join(&[id]).await.into_iter().next()
+    }
+
+    /// Returns where the child task behind ``id`` is in its life, without waiting for it
+    ///
+    /// # Parameters
+    ///
+    /// * `id`: the id the child task was spawned with
+    ///
+    /// # Returns
+    /// The status of the child task, or ``None`` if the id wasn't issued by this spawn group,
+    /// such as the id of a child task the saturation policy rejected,
+    /// or if the child task already stopped when the spawn group was last waited for, which forgets the statuses of stopped child tasks
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority, TaskStatus};
+    /// use std::future::pending;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     let done = group.spawn_task(Priority::default(), async { 1 });
+    ///     let stuck = group.spawn_task(Priority::default(), pending::<i32>());
+    ///     assert_eq!(group.await_task(done).await, Some(1));
+    ///     assert_eq!(group.task_status(done), Some(TaskStatus::Completed));
+    ///     assert!(matches!(
+    ///         group.task_status(stuck),
+    ///         Some(TaskStatus::Queued | TaskStatus::Running)
+    ///     ));
+    ///
+    ///     group.cancel_task(stuck);
+    ///     assert_eq!(group.task_status(stuck), Some(TaskStatus::Cancelled));
+    ///
+    ///     // waiting forgets the statuses of the child tasks which stopped
+    ///     group.wait_for_all().await;
+    ///     assert_eq!(group.task_status(done), None);
+    ///
+    ///     let other = with_spawn_group(|mut other| async move {
+    ///         other.spawn_task(Priority::default(), async { 0 });
+    ///         other.spawn_task(Priority::default(), async { 0 });
+    ///         other.spawn_task(Priority::default(), async { 0 })
+    ///     }).await;
+    ///     assert_eq!(group.task_status(other), None);
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn task_status(&self, id: TaskId) -> Option<TaskStatus> {
+        self.runtime.task_status(id)
+    }
+
+    /// Pops the results of the child tasks that already finished, without waiting for the running ones
+    ///
+    /// # Returns
+    /// A vector of the results in the order their child tasks finished, which is empty if none finished yet
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async { 1 });
+    ///     let mut results = vec![];
+    ///     while results.is_empty() {
+    ///         results.extend(group.try_drain());
+    ///         sleep(Duration::from_millis(1)).await;
+    ///     }
+    ///     assert_eq!(results, vec![1]);
+    ///     assert!(group.try_drain().is_empty());
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn try_drain(&mut self) -> Vec<ValueType> {
+        self.runtime.try_drain()
+    }
+
+    /// Folds the results of the child tasks into an accumulator as they finish,
+    /// until all remaining child tasks are finished.
+    ///
+    /// Results already popped off the spawn group's stream are not folded again.
+    ///
+    /// # Parameters
+    ///
+    /// * `init`: the initial value of the accumulator
+    /// * `f`: the closure combining the accumulator with each result
+    ///
+    /// # Returns
+    /// The final accumulator, which is `init` if no child task was spawned
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, with_type_spawn_group, GetType, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let sum = with_spawn_group(|mut group| async move {
+    ///     for i in 1..=10 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     group.fold_items(0, |acc, x| acc + x).await
+    /// }).await;
+    /// assert_eq!(sum, 55);
+    ///
+    /// let sum = with_type_spawn_group(i32::TYPE, |group| async move {
+    ///     group.fold_items(7, |acc, x| acc + x).await
+    /// }).await;
+    /// assert_eq!(sum, 7);
+    /// # });
+    /// ```
+    pub async fn fold_items<Acc, F>(&self, init: Acc, f: F) -> Acc
+    where
+        F: FnMut(Acc, ValueType) -> Acc,
+    {
+        let acc: Acc = self.runtime.fold(init, f).await;
+        self.decrement_count_to_zero();
+        acc
+    }
+
+    /// Waits for all remaining child tasks to finish and gathers their results into any collection
+    /// implementing ``Default`` and ``Extend``, consuming the spawn group.
+    ///
+    /// Results are added in the order they finished, so when collecting into a map from child tasks
+    /// returning the same key, the result that finished last wins, as ``Extend`` does.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    /// use std::collections::{BTreeMap, HashSet};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let domains: HashSet<&str> = with_spawn_group(|mut group| async move {
+    ///     for email in ["a@x.org", "b@y.org", "c@x.org"] {
+    ///         group.spawn_task(Priority::default(), async move { email.split('@').nth(1).unwrap() });
+    ///     }
+    ///     group.collect_into().await
+    /// }).await;
+    /// assert_eq!(domains, HashSet::from(["x.org", "y.org"]));
+    ///
+    /// let squares: BTreeMap<i32, i32> = with_spawn_group(|mut group| async move {
+    ///     for i in 1..=3 {
+    ///         group.spawn_task(Priority::default(), async move { (i, i * i) });
+    ///     }
+    ///     group.collect_into().await
+    /// }).await;
+    /// assert_eq!(squares, BTreeMap::from([(1, 1), (2, 4), (3, 9)]));
+    /// # });
+    /// ```
+    pub async fn collect_into<C>(self) -> C
+    where
+        C: Default + Extend<ValueType>,
+    {
+        self.fold_items(C::default(), |mut collection, item| {
+            collection.extend(Some(item));
+            collection
+        })
+        .await
+    }
+
+    /// Blocks the current thread until all remaining child tasks finished, and collects their results
+    /// into any collection that can be extended with them, like ``collect_into`` does for asynchronous callers
+    ///
+    /// # Deadlocks
+    /// It must not be called from asynchronous code, for the same reason as ``wait_for_all_blocking``.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{spawn_group::SpawnGroupBuilder, Priority};
+    /// use std::collections::BTreeSet;
+    ///
+    /// let mut group = SpawnGroupBuilder::new().build();
+    /// for path in ["b.txt", "a.txt", "b.txt"] {
+    ///     group.spawn_task(Priority::default(), async move { path.to_uppercase() });
+    /// }
+    ///
+    /// let paths: BTreeSet<String> = group.collect_blocking();
+    /// assert_eq!(paths, BTreeSet::from(["A.TXT".to_string(), "B.TXT".to_string()]));
+    /// ```
+    pub fn collect_blocking<C>(self) -> C
+    where
+        C: Default + Extend<ValueType>,
+    {
+        crate::block_on(self.collect_into())
+    }
+
+    /// Forwards the results of the child tasks into a sink as they finish, such as a channel or a framed writer,
+    /// rather than collecting them into a vector first, then flushes the sink once every child task finished.
+    ///
+    /// The next result is only pulled once the sink is ready for it, so a slow sink leaves the results waiting in the spawn group.
+    /// With ``set_max_buffered`` and ``set_max_concurrency`` that backpressure reaches the child tasks themselves,
+    /// since child tasks waiting for room for their result keep holding their turn to run.
+    ///
+    /// Only available with the ``sink`` feature.
+    ///
+    /// # Parameters
+    ///
+    /// * `sink`: the sink receiving the results
+    ///
+    /// # Returns
+    /// - Ok: once every result was sent and the sink was flushed
+    /// - Err: the error of the sink, which stops the forwarding, leaving the remaining child tasks to the drop policy
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let mut received: Vec<u64> = with_spawn_group(|mut group| async move {
+    ///     for i in 0..5 {
+    ///         group.spawn_task(Priority::default(), async move { i * i });
+    ///     }
+    ///     let mut received = vec![];
+    ///     group.drain_into(&mut received).await.unwrap();
+    ///     received
+    /// }).await;
+    ///
+    /// received.sort();
+    /// assert_eq!(received, vec![0, 1, 4, 9, 16]);
+    /// # });
+    /// ```
+    #[cfg(feature = "sink")]
+    pub async fn drain_into<Si>(self, sink: Si) -> Result<(), Si::Error>
+    where
+        Si: futures_sink::Sink<ValueType> + Unpin,
+    {
+        self.runtime.drain_into(sink).await
+    }
+
+    /// Sends the results of the child tasks down a channel as they finish, such as one feeding a downstream consumer task,
+    /// then drops the sender once every child task finished, so the receiver ends unless other senders are left.
+    ///
+    /// The next result is only sent once the channel has room for it, so a slow receiver leaves the results waiting in the spawn group.
+    /// Once the receiver was dropped, which is also noticed while waiting for the next result, every remaining child task gets cancelled.
+    ///
+    /// Only available with the ``channel`` feature.
+    ///
+    /// # Parameters
+    ///
+    /// * `tx`: the sender of the channel receiving the results
+    ///
+    /// # Returns
+    /// - true: once every result was sent
+    /// - false: if the receiver was dropped first, which cancelled the spawn group
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let (tx, rx) = futures_channel::mpsc::channel(1);
+    /// let forwarded = with_spawn_group(|mut group| async move {
+    ///     for i in 0..5 {
+    ///         group.spawn_task(Priority::default(), async move { i * i });
+    ///     }
+    ///     group.forward_to(tx).await
+    /// });
+    /// let (forwarded, mut received) = futures_lite::future::zip(forwarded, rx.collect::<Vec<u64>>()).await;
+    ///
+    /// assert!(forwarded);
+    /// received.sort();
+    /// assert_eq!(received, vec![0, 1, 4, 9, 16]);
+    /// # });
+    /// ```
+    ///
+    /// Dropping the receiver cancels the child tasks still running
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    /// use std::future::pending;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let (tx, rx) = futures_channel::mpsc::channel::<u64>(1);
+    /// drop(rx);
+    /// let forwarded = with_spawn_group(|mut group| async move {
+    ///     for _ in 0..3 {
+    ///         group.spawn_task(Priority::default(), pending());
+    ///     }
+    ///     group.forward_to(tx).await
+    /// }).await;
+    ///
+    /// assert!(!forwarded);
+    /// # });
+    /// ```
+    ///
+    /// The receiver going away is noticed even while a long running child task keeps the next result from coming
+    ///
+    /// ```rust
+    /// use futures_lite::{future, StreamExt};
+    /// use spawn_groups::{with_spawn_group, Priority};
+    /// use std::future::pending;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let (tx, mut rx) = futures_channel::mpsc::channel::<u64>(1);
+    /// let forwarded = with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async { 1 });
+    ///     group.spawn_task(Priority::default(), pending());
+    ///     let receive_one = async move {
+    ///         assert_eq!(rx.next().await, Some(1));
+    ///         drop(rx);
+    ///     };
+    ///     future::zip(group.forward_to(tx), receive_one).await.0
+    /// }).await;
+    ///
+    /// assert!(!forwarded);
+    /// # });
+    /// ```
+    #[cfg(feature = "channel")]
+    pub async fn forward_to(mut self, tx: futures_channel::mpsc::Sender<ValueType>) -> bool {
+        let forwarded: bool = self.runtime.forward_to(tx).await;
+        if !forwarded {
+            self.cancel_all_tasks();
+        }
+        forwarded
+    }
+}
+
+impl<ValueType: Send + Clone> SpawnGroup<ValueType> {
+    /// Copies the results of the child tasks that already finished but weren't consumed yet,
+    /// leaving them in the spawn group, unlike ``try_drain`` which pops them
+    ///
+    /// Only available when the results can be cloned.
+    ///
+    /// # Returns
+    /// A vector of copies of the results in the order their child tasks finished, which is empty if none is waiting
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async { "page 1".to_string() });
+    ///     while group.results_snapshot().is_empty() {
+    ///         sleep(Duration::from_millis(1)).await;
+    ///     }
+    ///     // peeking leaves the results for whoever consumes the spawn group
+    ///     assert_eq!(group.results_snapshot(), vec!["page 1"]);
+    ///     assert_eq!(group.wait_for_all_items().await, vec!["page 1"]);
+    ///     assert!(group.results_snapshot().is_empty());
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn results_snapshot(&self) -> Vec<ValueType> {
+        self.runtime.results_snapshot()
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    fn increment_count(&self) {
+        self.count.fetch_add(1, Ordering::Acquire);
+    }
+
+    fn count(&self) -> usize {
+        self.count.load(Ordering::Acquire)
+    }
+
+    fn decrement_count(&self) {
+        _ = self
+            .count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+                count.checked_sub(1)
+            });
+    }
+
+    fn decrement_count_to_zero(&self) {
+        self.count.store(0, Ordering::Release);
+    }
+
+    /// Cancels all running child tasks like ``cancel_all`` does, but waits for the grace period on the caller's executor
+    async fn cancel_all_tasks_async(&mut self) {
+        self.runtime.cancel_async().await;
+        self.is_cancelled = true;
+        self.decrement_count_to_zero();
+    }
+}
+
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    /// A Boolean value that indicates whether the group has any remaining tasks.
+    ///
+    /// At the start of the body of a ``with_spawn_group()`` call, , or before calling ``spawn_task`` or ``spawn_task_unless_cancelled`` methods
+    /// the spawn group is always empty.
+    ///  
+    /// # Returns
+    /// - true: if there's no child task still running
+    /// - false: if any child task is still running
+    pub fn is_empty(&self) -> bool {
+        if self.count() == 0 || self.runtime.stream().task_count() == 0 {
+            return true;
+        }
+        false
+    }
+
+    /// Returns whether every child task finished and all their results were consumed, without blocking
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    /// use futures_lite::StreamExt;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async { 1 });
+    ///     assert!(!group.is_finished());
+    ///     while group.next().await.is_some() {}
+    ///     assert!(group.is_finished());
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn is_finished(&self) -> bool {
+        self.runtime.is_finished()
+    }
+
+    /// Returns the number of child tasks still running, without blocking.
+    ///
+    /// It reaches zero exactly when waiting for all child tasks would return
+    pub fn pending_count(&self) -> usize {
+        self.runtime.stream().task_count()
+    }
+
+    /// Returns the number of child tasks that finished so far, without blocking.
+    ///
+    /// Child tasks that were cancelled are not counted
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     for i in 0..3 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     group.wait_for_all().await;
+    ///     assert_eq!(group.pending_count(), 0);
+    ///     assert_eq!(group.completed_count(), 3);
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn completed_count(&self) -> usize {
+        self.runtime.stream().completed_count()
+    }
+
+    /// Returns a stream of the spawn group's progress as the number of completed child tasks
+    /// along with the number of child tasks spawned so far, yielded whenever a child task completes.
+    ///
+    /// It doesn't consume any result, so it can be polled alongside the results,
+    /// and each of its clones yields every progress update on its own.
+    /// It ends once no child task is running anymore. Cancelled child tasks never count as completed.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     for i in 0..4 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             sleep(Duration::from_millis(20 * i)).await;
+    ///             i
+    ///         });
+    ///     }
+    ///     let progress = group.progress();
+    ///     let bar = progress.clone();
+    ///     let updates: Vec<(usize, usize)> = progress.collect().await;
+    ///
+    ///     assert_eq!(updates.last(), Some(&(4, 4)));
+    ///     assert!(updates.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    ///     assert_eq!(bar.last().await, Some((4, 4)));
+    ///     // the results are all still there
+    ///     assert_eq!(group.wait_for_all_items().await.len(), 4);
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn progress(&self) -> impl Stream<Item = (usize, usize)> + Clone {
+        self.runtime.stream().progress()
+    }
+
+    /// Waits until at least ``count`` child tasks completed, without consuming any of their results,
+    /// so they all stay in the stream for later.
+    ///
+    /// Returns right away when that many child tasks already completed, including when ``count`` is zero.
+    ///
+    /// # Parameters
+    ///
+    /// * `count`: the number of completed child tasks to wait for
+    ///
+    /// # Returns
+    /// Whether that many child tasks completed, which is false if no child task was left running before that,
+    /// because too few were spawned or the rest were cancelled
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     for replica in 0..5 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             sleep(Duration::from_millis(10 * replica)).await;
+    ///             replica
+    ///         });
+    ///     }
+    ///     // proceeds once a quorum of the replicas responded
+    ///     assert!(group.wait_until_completed(3).await);
+    ///     assert!(group.completed_count() >= 3);
+    ///     // every response is still there
+    ///     assert_eq!(group.wait_for_all_items().await.len(), 5);
+    ///     assert!(!group.wait_until_completed(6).await);
+    /// }).await;
+    /// # });
+    /// ```
+    pub async fn wait_until_completed(&self, count: usize) -> bool {
+        self.runtime.wait_until_completed(count).await
+    }
+
+    /// Sets what happens to the child tasks spawned while the threads of the spawn group are saturated,
+    /// which is while child tasks are already waiting for one of them.
+    ///
+    /// ``SaturationPolicy::Reject`` rejects them the way ``close_gracefully`` does, applying backpressure to whoever spawns them,
+    /// while ``SaturationPolicy::Block`` blocks the spawning thread until the waiting child tasks got a thread,
+    /// so it must not be used to spawn from inside the spawn group's own child tasks.
+    ///
+    /// The default is ``SaturationPolicy::Queue``
+    ///
+    /// # Parameters
+    ///
+    /// * `saturation_policy`: the saturation policy to use
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{spawn_group::SpawnGroupBuilder, Priority, SaturationPolicy};
+    /// use std::time::Duration;
+    ///
+    /// # // Tokio doesn't tell how many child tasks wait for a thread, so they never get rejected there
+    /// # #[cfg(not(feature = "tokio"))]
+    /// # spawn_groups::block_on(async move {
+    /// let mut group = SpawnGroupBuilder::new().threads(1).build();
+    /// group.set_saturation_policy(SaturationPolicy::Reject);
+    /// // keeps the only thread busy
+    /// group.spawn_task(Priority::default(), async {
+    ///     std::thread::sleep(Duration::from_millis(500));
+    ///     0
+    /// });
+    /// let mut accepted = 0;
+    /// while group.spawn_task_unless_cancelled(Priority::default(), async { 1 }) {
+    ///     accepted += 1;
+    ///     std::thread::sleep(Duration::from_millis(1));
+    /// }
+    ///
+    /// // once a child task waits for the busy thread, new ones get rejected
+    /// assert_eq!(group.wait_for_all_items().await.len(), accepted + 1);
+    /// # });
+    /// ```
+    ///
+    /// Blocking holds every new child task back until the waiting ones got the thread, so none is turned away:
+    ///
+    /// ```rust
+    /// use spawn_groups::{spawn_group::SpawnGroupBuilder, Priority, SaturationPolicy};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let mut group = SpawnGroupBuilder::new().threads(1).build();
+    /// group.set_saturation_policy(SaturationPolicy::Block);
+    /// for i in 0..3 {
+    ///     assert!(group.spawn_task_unless_cancelled(Priority::default(), async move {
+    ///         std::thread::sleep(Duration::from_millis(20));
+    ///         i
+    ///     }));
+    /// }
+    /// let mut results = group.wait_for_all_items().await;
+    /// results.sort();
+    /// assert_eq!(results, vec![0, 1, 2]);
+    /// # });
+    /// ```
+    pub fn set_saturation_policy(&mut self, saturation_policy: SaturationPolicy) {
+        self.runtime.set_saturation_policy(saturation_policy);
+    }
+
+    /// Sets the order in which the child tasks still running get cancelled when the spawn group is cancelled.
+    ///
+    /// ``cancel_and_wait`` only cancels each child task once the one before it stopped,
+    /// so their futures are dropped in that order, while ``cancel_all`` and dropping the spawn group
+    /// only tell the backend to stop them in that order, which may drop their futures in another one.
+    ///
+    /// The default is ``CancelOrder::Spawned``
+    ///
+    /// # Parameters
+    ///
+    /// * `cancel_order`: the cancel order to use
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::future::pending;
+    /// use spawn_groups::{with_spawn_group, CancelOrder, Priority};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// struct Connection(Priority, Arc<Mutex<Vec<Priority>>>);
+    ///
+    /// impl Drop for Connection {
+    ///     fn drop(&mut self) {
+    ///         self.1.lock().unwrap().push(self.0);
+    ///     }
+    /// }
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let closed = Arc::new(Mutex::new(vec![]));
+    /// let log = closed.clone();
+    /// with_spawn_group(|mut group| async move {
+    ///     group.set_cancel_order(CancelOrder::LowestPriorityFirst);
+    ///     for priority in [Priority::HIGH, Priority::BACKGROUND, Priority::USERINITIATED, Priority::LOW] {
+    ///         let connection = Connection(priority, log.clone());
+    ///         group.spawn_task(priority, async move {
+    ///             let _connection = connection;
+    ///             pending::<()>().await
+    ///         });
+    ///     }
+    ///     group.cancel_and_wait().await;
+    /// }).await;
+    ///
+    /// let closed = closed.lock().unwrap().clone();
+    /// assert_eq!(closed, vec![Priority::BACKGROUND, Priority::LOW, Priority::HIGH, Priority::USERINITIATED]);
+    /// # });
+    /// ```
+    pub fn set_cancel_order(&mut self, cancel_order: CancelOrder) {
+        self.runtime.set_cancel_order(cancel_order);
+    }
+
+    /// Sets what happens when a child task panics.
+    ///
+    /// The default is ``PanicPolicy::Isolate``
+    ///
+    /// # Parameters
+    ///
+    /// * `panic_policy`: the panic policy to use
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{spawn_group::SpawnGroupBuilder, PanicPolicy, Priority};
+    /// use std::panic::{catch_unwind, AssertUnwindSafe};
+    ///
+    /// let mut group = SpawnGroupBuilder::new().threads(1).build();
+    /// group.set_panic_policy(PanicPolicy::Propagate);
+    /// group.spawn_task(Priority::default(), async { panic!("boom") });
+    /// let result = catch_unwind(AssertUnwindSafe(|| spawn_groups::block_on(group.wait_for_all())));
+    /// assert!(result.is_err());
+    ///
+    /// // the only thread survived the panic, so the spawn group keeps working
+    /// group.spawn_task(Priority::default(), async { 1 });
+    /// assert_eq!(spawn_groups::block_on(group.wait_for_all_items()), vec![1]);
+    /// assert_eq!(group.pending_count(), 0);
+    /// ```
+    ///
+    /// A panic leaves the other child tasks running under ``PanicPolicy::Isolate``, while it cancels them under ``PanicPolicy::CancelAll``
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, PanicPolicy, Priority};
+    /// use std::future::pending;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async { panic!("boom") });
+    ///     for i in 0..3 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     let mut results = group.wait_for_all_items().await;
+    ///     results.sort();
+    ///     assert_eq!(results, vec![0, 1, 2]);
+    /// }).await;
+    ///
+    /// with_spawn_group(|mut group| async move {
+    ///     group.set_panic_policy(PanicPolicy::CancelAll);
+    ///     for _ in 0..3 {
+    ///         group.spawn_task(Priority::default(), pending::<i32>());
+    ///     }
+    ///     group.spawn_task(Priority::default(), async { panic!("boom") });
+    ///     // the stuck child tasks were cancelled, so the wait returns
+    ///     assert_eq!(group.wait_for_all_items().await, vec![]);
+    ///     assert!(group.cancellation_token().is_cancelled());
+    ///     assert!(!group.spawn_task_unless_cancelled(Priority::default(), async { 1 }));
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn set_panic_policy(&mut self, panic_policy: PanicPolicy) {
+        self.runtime.set_panic_policy(panic_policy);
+    }
+
+    /// Returns the number of child tasks that panicked so far
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     for i in 0..3 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             if i == 1 {
+    ///                 panic!("child task {i} panicked");
+    ///             }
+    ///             i
+    ///         });
+    ///     }
+    ///     let mut results = group.wait_for_all_items().await;
+    ///     results.sort();
+    ///     assert_eq!(results, vec![0, 2]);
+    ///     assert_eq!(group.panicked_count(), 1);
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn panicked_count(&self) -> usize {
+        self.runtime.panicked_count()
+    }
+
+    /// Returns whether the spawn group was cancelled, either explicitly or by a method that cancels the remaining child tasks
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::default(), async { 1 });
+    ///     group.wait_for_all().await;
+    ///     assert!(!group.was_cancelled());
+    ///
+    ///     group.spawn_task(Priority::default(), std::future::pending());
+    ///     group.cancel_all();
+    ///     assert!(group.was_cancelled());
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn was_cancelled(&self) -> bool {
+        self.runtime.was_cancelled()
+    }
+
+    /// Describes the child tasks that have not finished yet, which helps finding out why a spawn group hangs
+    ///
+    /// They are listed in the order the spawn group waits for them.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_type_spawn_group, GetType, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_type_spawn_group(i32::TYPE, |mut group| async move {
+    ///     group.spawn_named_task(Priority::LOW, "first low", std::future::pending());
+    ///     group.spawn_named_task(Priority::HIGH, "high", std::future::pending());
+    ///     group.spawn_named_task(Priority::LOW, "second low", std::future::pending());
+    ///     group.spawn_named_task(Priority::Custom(10), "custom", std::future::pending());
+    ///
+    ///     let names: Vec<_> = group
+    ///         .debug_snapshot()
+    ///         .into_iter()
+    ///         .map(|task| task.name.unwrap())
+    ///         .collect();
+    ///     assert_eq!(names, vec!["custom", "high", "first low", "second low"]);
+    ///     group.cancel_all();
+    /// }).await;
+    /// # });
+    /// ```
+    pub fn debug_snapshot(&self) -> Vec<TaskInfo> {
+        self.runtime.debug_snapshot()
+    }
+
+    /// The number of threads of the spawn group running a child task right now
+    ///
+    /// Along with ``pool_queued()``, this helps tuning the thread count and the maximum concurrency:
+    /// a long queue while every thread is busy means the spawn group is short of threads,
+    /// while threads that are rarely busy mean it has more than it needs.
+    /// With the ``tokio`` feature, every child task of the spawn group that hasn't finished counts as running.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_type_spawn_group, GetType, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_type_spawn_group(i32::TYPE, |mut group| async move {
+    ///     for i in 0..4 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     println!("active: {}, queued: {}", group.pool_active(), group.pool_queued());
+    ///     group.wait_for_all().await;
+    /// }).await;
+    /// # });
+    /// ```
+    ///
+    /// A spawn group sharing a Tokio runtime with the rest of the application only counts its own child tasks:
+    ///
+    /// ```rust
+    /// # #[cfg(feature = "tokio")]
+    /// # fn main() {
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// let runtime = tokio::runtime::Runtime::new().unwrap();
+    /// runtime.block_on(async {
+    ///     for _ in 0..10 {
+    ///         tokio::spawn(std::future::pending::<()>());
+    ///     }
+    ///     with_spawn_group(|mut group| async move {
+    ///         assert_eq!(group.pool_active(), 0);
+    ///         group.spawn_task(Priority::default(), std::future::pending::<()>());
+    ///         assert_eq!(group.pool_active(), 1);
+    ///         assert_eq!(group.pool_queued(), 0);
+    ///         group.cancel_all();
+    ///     })
+    ///     .await;
+    /// });
+    /// # }
+    /// # #[cfg(not(feature = "tokio"))]
+    /// # fn main() {}
+    /// ```
+    pub fn pool_active(&self) -> usize {
+        self.runtime.pool_active()
+    }
+
+    /// The number of child tasks of the spawn group waiting for a thread to run them
+    ///
+    /// With the ``tokio`` feature this is unavailable and always zero,
+    /// since Tokio doesn't tell which of its queued tasks belong to the spawn group.
+    pub fn pool_queued(&self) -> usize {
+        self.runtime.pool_queued()
     }
-}
 
-impl<ValueType: Send + 'static> SpawnGroup<ValueType> {
-    /// Spawns a new task into the spawn group
+    /// Reports how deep the queues of the spawn group are to a callback at a fixed interval,
+    /// such as to feed a time series of its load into a metrics backend
+    ///
+    /// The callback runs on a background thread of its own, rather than on the spawn group's threads,
+    /// first right away and then once every interval, until the spawn group is dropped or closed,
+    /// which waits for a call in progress to return.
+    ///
     /// # Parameters
     ///
-    /// * `priority`: priority to use
-    /// * `closure`: an async closure that return a value of type ``ValueType``
-    pub fn spawn_task<F>(&mut self, priority: Priority, closure: F)
+    /// * `interval`: how long to wait between two snapshots
+    /// * `report`: the callback receiving every snapshot
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, spawn_group::SpawnGroupBuilder, Priority, QueueSnapshot};
+    /// use std::sync::{Arc, Mutex};
+    /// use std::time::Duration;
+    ///
+    /// let snapshots: Arc<Mutex<Vec<QueueSnapshot>>> = Arc::new(Mutex::new(vec![]));
+    /// let series = snapshots.clone();
+    /// let mut group = SpawnGroupBuilder::new().threads(2).build();
+    /// group.sample_queue_depth(Duration::from_millis(10), move |snapshot| {
+    ///     series.lock().unwrap().push(snapshot);
+    /// });
+    /// for i in 0..4 {
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         sleep(Duration::from_millis(100)).await;
+    ///         i
+    ///     });
+    /// }
+    /// assert_eq!(group.wait_for_all_blocking().len(), 4);
+    /// drop(group);
+    ///
+    /// let sampled = snapshots.lock().unwrap().len();
+    /// assert!(snapshots.lock().unwrap().iter().any(|snapshot| snapshot.pending_tasks > 0));
+    /// // dropping the spawn group stopped the sampler
+    /// std::thread::sleep(Duration::from_millis(50));
+    /// assert_eq!(snapshots.lock().unwrap().len(), sampled);
+    /// ```
+    pub fn sample_queue_depth<F>(&self, interval: Duration, report: F)
     where
-        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
+        F: Fn(QueueSnapshot) + Send + 'static,
     {
-        self.add_task(priority, closure);
+        self.runtime.sample_queue_depth(interval, report);
     }
 
-    /// Spawn a new task only if the group is not cancelled yet,
-    /// otherwise does nothing
+    /// Returns how long the child tasks that returned so far took from being spawned to returning,
+    /// as their count along with their mean, maximum and 99th percentile latency.
     ///
-    /// # Parameters
+    /// Only available with the ``stats`` feature.
     ///
-    /// * `priority`: priority to use
-    /// * `closure`: an async closure that return a value of type ``ValueType``
-    pub fn spawn_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
-    where
-        F: Future<Output = <SpawnGroup<ValueType> as Shared>::Result> + Send + 'static,
-    {
-        self.add_task_unlessed_cancelled(priority, closure);
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// with_spawn_group(|mut group| async move {
+    ///     for i in 1..=4 {
+    ///         group.spawn_task(Priority::default(), async move {
+    ///             sleep(Duration::from_millis(10 * i)).await;
+    ///         });
+    ///     }
+    ///     group.wait_for_all().await;
+    ///
+    ///     let stats = group.stats();
+    ///     println!("{stats:?}");
+    ///     assert_eq!(stats.count, 4);
+    ///     assert!(stats.max_latency >= Duration::from_millis(40));
+    ///     assert!(stats.mean_latency <= stats.p99 && stats.p99 <= stats.max_latency);
+    /// }).await;
+    /// # });
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> TaskStats {
+        self.runtime.stats()
     }
 
-    /// Cancels all running task in the spawn group
-    pub fn cancel_all(&mut self) {
-        self.cancel_all_tasks();
+    /// Grows or shrinks the threads of the spawn group to ``thread_count``, clamping zero to a single thread.
+    ///
+    /// It is safe to call while child tasks are running: surplus threads exit once they got through the work
+    /// already queued up for them, and the work queued afterwards runs on the resized threads.
+    /// Together with ``pool_queued()``, this lets a long-lived spawn group scale its threads with its load.
+    /// With the ``tokio`` feature, the runtime can't be resized, so this does nothing.
+    ///
+    /// # Parameters
+    ///
+    /// * `thread_count`: the number of threads to drive the child tasks on
+    ///
+    /// # Returns
+    /// Whether the threads were resized, which is never the case with the ``tokio`` feature
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{sleep, spawn_group::SpawnGroupBuilder, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let mut group = SpawnGroupBuilder::new().threads(1).build();
+    /// for i in 0..8 {
+    ///     group.spawn_task(Priority::default(), async move {
+    ///         sleep(Duration::from_millis(20)).await;
+    ///         i
+    ///     });
+    /// }
+    /// assert_eq!(group.set_thread_count(4), !cfg!(feature = "tokio"));
+    /// group.set_thread_count(0);
+    ///
+    /// let mut results = group.wait_for_all_items().await;
+    /// results.sort();
+    /// assert_eq!(results, (0..8).collect::<Vec<_>>());
+    /// # });
+    /// ```
+    pub fn set_thread_count(&mut self, thread_count: usize) -> bool {
+        self.runtime.set_thread_count(thread_count)
     }
 }
 
 impl<ValueType: Send> SpawnGroup<ValueType> {
-    /// Returns the first element of the stream, or None if it is empty.
-    pub async fn first(&self) -> Option<ValueType> {
-        self.runtime.stream().first().await
+    /// Returns an instance of the `Stream` trait.
+    ///
+    /// The stream yields the results of the child tasks as they finish and ends once no child task is still running
+    /// and every result has been yielded.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    /// use futures_lite::StreamExt;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let mut results: Vec<i32> = with_spawn_group(|mut group| async move {
+    ///     for i in 0..10 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     group.stream().filter(|x| x % 2 == 0).map(|x| x * 10).collect().await
+    /// }).await;
+    ///
+    /// results.sort();
+    /// assert_eq!(results, vec![0, 20, 40, 60, 80]);
+    /// # });
+    /// ```
+    pub fn stream(&self) -> impl Stream<Item = ValueType> {
+        self.runtime.stream()
     }
-}
 
-impl<ValueType: Send> SpawnGroup<ValueType> {
-    /// Waits for all remaining child tasks for finish.
-    pub async fn wait_for_all(&self) {
-        self.wait().await;
+    /// Returns a stream which yields the results of the child tasks in batches of `size` results
+    /// in the order they finished, and the remaining results in a smaller batch once all child tasks finished.
+    ///
+    /// # Panics
+    /// If the `size` parameter is zero
+    ///
+    /// # Parameters
+    ///
+    /// * `size`: the number of results in each batch
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let sizes: Vec<usize> = with_spawn_group(|mut group| async move {
+    ///     for i in 0..7 {
+    ///         group.spawn_task(Priority::default(), async move { i });
+    ///     }
+    ///     group.chunks(3).map(|chunk| chunk.len()).collect().await
+    /// }).await;
+    ///
+    /// assert_eq!(sizes, vec![3, 3, 1]);
+    /// # });
+    /// ```
+    pub fn chunks(&self, size: usize) -> impl Stream<Item = Vec<ValueType>> {
+        self.runtime.stream().chunks(size)
     }
-}
 
-impl<ValueType: Send> SpawnGroup<ValueType> {
-    fn increment_count(&self) {
-        self.count.fetch_add(1, Ordering::Acquire);
+    /// Returns a stream of the results of the child tasks transformed by a closure
+    ///
+    /// The closure runs whenever a result is pulled from the stream, not on the spawn group's threads.
+    ///
+    /// # Parameters
+    ///
+    /// * `f`: the closure to transform each result with
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use spawn_groups::{with_spawn_group, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let mut lengths: Vec<usize> = with_spawn_group(|mut group| async move {
+    ///     for word in ["a", "bb", "ccc"] {
+    ///         group.spawn_task(Priority::default(), async move { word.to_string() });
+    ///     }
+    ///     group.map_items(|word| word.len()).wait_for_all_items().await
+    /// }).await;
+    ///
+    /// lengths.sort();
+    /// assert_eq!(lengths, vec![1, 2, 3]);
+    /// # });
+    /// ```
+    ///
+    /// Every stream over the same spawn group pulls from the same results, so each result is yielded by exactly one of them:
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{spawn_group::SpawnGroupBuilder, Priority};
+    ///
+    /// let mut group = SpawnGroupBuilder::new().build();
+    /// for i in 0..1000 {
+    ///     group.spawn_task(Priority::default(), async move { i });
+    /// }
+    ///
+    /// let (mut first, mut second) = std::thread::scope(|scope| {
+    ///     let first = scope.spawn(|| spawn_groups::block_on(group.map_items(|i| i).collect::<Vec<i32>>()));
+    ///     let second = scope.spawn(|| spawn_groups::block_on(group.map_items(|i| i).collect::<Vec<i32>>()));
+    ///     (first.join().unwrap(), second.join().unwrap())
+    /// });
+    ///
+    /// first.append(&mut second);
+    /// first.sort();
+    /// assert_eq!(first, (0..1000).collect::<Vec<i32>>());
+    /// ```
+    pub fn map_items<F, B>(&self, f: F) -> Map<'_, ValueType, F>
+    where
+        F: Fn(ValueType) -> B,
+    {
+        Map { group: self, f }
     }
 
-    fn count(&self) -> usize {
-        self.count.load(Ordering::Acquire)
+    /// Turns the spawn group into a stream that yields the results of higher priority child tasks first
+    ///
+    /// Results are only reordered among the ones that are ready by the time the stream is polled,
+    /// so this is best-effort: a result of a lower priority child task that finished first
+    /// is still yielded when no result of a higher priority one is ready yet.
+    /// Results of the same priority are yielded in the order they finished.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{sleep, with_spawn_group, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let results: Vec<&str> = with_spawn_group(|mut group| async move {
+    ///     group.spawn_task(Priority::BACKGROUND, async move { "background" });
+    ///     group.spawn_task(Priority::LOW, async move { "low" });
+    ///     group.spawn_task(Priority::HIGH, async move { "high" });
+    ///     // gives every child task the time to finish, so all results are ready at once
+    ///     sleep(Duration::from_millis(200)).await;
+    ///     group.into_stream_ordered().collect().await
+    /// }).await;
+    ///
+    /// assert_eq!(results, vec!["high", "low", "background"]);
+    /// # });
+    /// ```
+    pub fn into_stream_ordered(self) -> StreamOrdered<ValueType> {
+        StreamOrdered { group: self }
     }
 
-    fn decrement_count_to_zero(&self) {
-        self.count.store(0, Ordering::Release);
+    /// Creates a spawn group with the default configuration and spawns every future of ``futures`` into it as a child task
+    /// of the default priority, for moving code built on ``FuturesUnordered`` over to spawn groups
+    ///
+    /// The futures must be ``Unpin``, such as the boxed futures a ``FuturesUnordered`` usually holds,
+    /// since that's the only way a ``FuturesUnordered`` hands its futures out.
+    ///
+    /// Only available with the ``futures`` feature.
+    ///
+    /// # Parameters
+    ///
+    /// * `futures`: the futures to spawn
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_util::{future::BoxFuture, stream::FuturesUnordered};
+    /// use spawn_groups::spawn_group::SpawnGroup;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let futures: FuturesUnordered<BoxFuture<'static, u64>> =
+    ///     (0..4u64).map(|i| Box::pin(async move { i * 10 }) as BoxFuture<'static, u64>).collect();
+    /// let group = SpawnGroup::from_futures_unordered(futures);
+    ///
+    /// let mut results = group.wait_for_all_items().await;
+    /// results.sort();
+    /// assert_eq!(results, vec![0, 10, 20, 30]);
+    /// # });
+    /// ```
+    #[cfg(feature = "futures")]
+    pub fn from_futures_unordered<Fut>(futures: futures_util::stream::FuturesUnordered<Fut>) -> Self
+    where
+        Fut: Future<Output = ValueType> + Send + Unpin + 'static,
+    {
+        let mut group: Self = SpawnGroupBuilder::new().build();
+        for future in futures {
+            group.spawn_task(Priority::default(), future);
+        }
+        group
     }
-}
 
-impl<ValueType: Send> SpawnGroup<ValueType> {
-    /// A Boolean value that indicates whether the group has any remaining tasks.
+    /// Hands the child tasks back as a ``FuturesUnordered`` the caller can poll directly, such as from within ``select!``,
+    /// consuming the spawn group.
+    ///
+    /// Every child task still running gets a future resolving to its result once it finishes, or to ``None`` if it was cancelled or panicked,
+    /// while every result already stored gets a future which is ready right away.
+    /// The child tasks keep running on the spawn group's backend meanwhile, and the spawn group's drop policy applies
+    /// once the last of the futures is dropped. Child tasks spawned with ``spawn_stream_task`` only contribute their first result.
+    ///
+    /// Only available with the ``futures`` feature.
     ///
-    /// At the start of the body of a ``with_spawn_group()`` call, , or before calling ``spawn_task`` or ``spawn_task_unless_cancelled`` methods
-    /// the spawn group is always empty.
-    ///  
     /// # Returns
-    /// - true: if there's no child task still running
-    /// - false: if any child task is still running
-    pub fn is_empty(&self) -> bool {
-        if self.count() == 0 || self.runtime.stream().task_count() == 0 {
-            return true;
+    /// A ``FuturesUnordered`` yielding the results of the child tasks as they finish
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{sleep, spawn_group::SpawnGroupBuilder, Priority};
+    /// use std::time::Duration;
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let mut group = SpawnGroupBuilder::new().build();
+    /// group.spawn_task(Priority::default(), async { 1 });
+    /// group.wait_for_all().await;
+    /// group.spawn_task(Priority::default(), async {
+    ///     sleep(Duration::from_millis(20)).await;
+    ///     2
+    /// });
+    ///
+    /// let results: Vec<Option<u64>> = group.into_futures_unordered().collect().await;
+    /// assert_eq!(results, vec![Some(1), Some(2)]);
+    /// # });
+    /// ```
+    #[cfg(feature = "futures")]
+    pub fn into_futures_unordered(
+        self,
+    ) -> futures_util::stream::FuturesUnordered<
+        futures_util::future::BoxFuture<'static, Option<ValueType>>,
+    > {
+        use futures_util::{future::BoxFuture, stream::FuturesUnordered};
+
+        let (running, items) = self.runtime.route_results();
+        let futures: FuturesUnordered<BoxFuture<'static, Option<ValueType>>> = items
+            .into_iter()
+            .map(|item| {
+                Box::pin(std::future::ready(Some(item))) as BoxFuture<'static, Option<ValueType>>
+            })
+            .collect();
+        // every future keeps the spawn group alive, so its child tasks aren't waited for or cancelled underneath them
+        let group: Arc<Self> = Arc::new(self);
+        for result in running {
+            let group: Arc<Self> = group.clone();
+            futures.push(Box::pin(async move {
+                let _group: Arc<Self> = group;
+                result.await
+            }));
         }
-        false
+        futures
     }
-}
 
-impl<ValueType: Send> SpawnGroup<ValueType> {
-    /// Returns an instance of the `Stream` trait.
-    pub fn stream(&self) -> impl Stream<Item = ValueType> {
-        self.runtime.stream()
+    /// Turns the spawn group and another one into a single stream of the results of both of their child tasks,
+    /// yielded as they become available
+    ///
+    /// The stream takes turns between the spawn groups whenever several of them have a result ready,
+    /// so a busy spawn group can't hold back the results of the other ones,
+    /// and it only ends once the child tasks of every spawn group finished and all their results were yielded.
+    /// More spawn groups can be added by calling ``merge()`` on the stream itself.
+    ///
+    /// # Parameters
+    ///
+    /// * `other`: the spawn group whose results get interleaved with this spawn group's results
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use futures_lite::StreamExt;
+    /// use spawn_groups::{spawn_group::{SpawnGroup, SpawnGroupBuilder}, Priority};
+    ///
+    /// # spawn_groups::block_on(async move {
+    /// let regions: Vec<SpawnGroup<u32>> = (0..3)
+    ///     .map(|region| {
+    ///         let mut group = SpawnGroupBuilder::new().threads(1).build();
+    ///         for i in 0..4 {
+    ///             group.spawn_task(Priority::default(), async move { region * 10 + i });
+    ///         }
+    ///         group
+    ///     })
+    ///     .collect();
+    ///
+    /// let mut regions = regions.into_iter();
+    /// let (east, west) = (regions.next().unwrap(), regions.next().unwrap());
+    /// let merged = regions.fold(east.merge(west), |merged, group| merged.merge(group));
+    /// let mut results: Vec<u32> = merged.collect().await;
+    /// results.sort();
+    /// assert_eq!(results, vec![0, 1, 2, 3, 10, 11, 12, 13, 20, 21, 22, 23]);
+    /// # });
+    /// ```
+    pub fn merge(self, other: SpawnGroup<ValueType>) -> Merge<ValueType> {
+        Merge {
+            groups: vec![self, other],
+            next: 0,
+        }
     }
 }
 
@@ -178,36 +3653,40 @@ impl<ValueType: Send> SpawnGroup<ValueType> {
     }
 }
 
+impl<ValueType: Send> SpawnGroup<ValueType> {
+    pub(crate) fn scope(&self) -> Scope<ValueType> {
+        Scope::new(self.runtime.clone())
+    }
+}
+
 impl<ValueType: Send> Drop for SpawnGroup<ValueType> {
     fn drop(&mut self) {
-        if self.wait_at_drop {
-            self.runtime.wait_for_all_tasks();
-        } else {
-            self.runtime.end()
+        self.runtime.stop_sampling();
+        match self.drop_policy {
+            // blocking while unwinding could hang on child tasks the panic left waiting
+            DropPolicy::WaitAll if std::thread::panicking() => self.runtime.cancel(),
+            DropPolicy::WaitAll => self.runtime.wait_for_all_tasks(),
+            DropPolicy::Cancel => self.runtime.cancel(),
+            DropPolicy::Detach => self.runtime.detach_to_background(),
         }
     }
 }
 
 impl<ValueType: Send> Initializible for SpawnGroup<ValueType> {
     fn init() -> Self {
-        SpawnGroup {
-            runtime: RuntimeEngine::init(),
-            is_cancelled: false,
-            count: Arc::new(AtomicUsize::new(0)),
-            wait_at_drop: true,
-        }
+        SpawnGroup::with_threads(0)
     }
 }
 
 impl<ValueType: Send + 'static> Shared for SpawnGroup<ValueType> {
     type Result = ValueType;
 
-    fn add_task<F>(&mut self, priority: Priority, closure: F)
+    fn add_task<F>(&mut self, priority: Priority, closure: F) -> TaskId
     where
         F: Future<Output = Self::Result> + Send + 'static,
     {
         self.increment_count();
-        self.runtime.write_task(priority, closure);
+        self.runtime.write_task(priority, closure)
     }
 
     fn cancel_all_tasks(&mut self) {
@@ -216,16 +3695,118 @@ impl<ValueType: Send + 'static> Shared for SpawnGroup<ValueType> {
         self.decrement_count_to_zero();
     }
 
-    fn add_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F)
+    fn add_task_unlessed_cancelled<F>(&mut self, priority: Priority, closure: F) -> bool
     where
         F: Future<Output = Self::Result> + Send + 'static,
     {
-        if !self.is_cancelled {
-            self.add_task(priority, closure)
+        if !self.runtime.write_task_unless_cancelled(priority, closure) {
+            return false;
+        }
+        self.increment_count();
+        true
+    }
+}
+
+/// Blocks the current thread until all remaining child tasks finish,
+/// then iterates over their results in the order they finished
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::{spawn_group::SpawnGroupBuilder, Priority};
+///
+/// let mut group = SpawnGroupBuilder::new().build();
+/// for i in 0..5 {
+///     group.spawn_task(Priority::default(), async move { i });
+/// }
+///
+/// let mut sum = 0;
+/// for result in group {
+///     sum += result;
+/// }
+/// assert_eq!(sum, 10);
+/// ```
+impl<ValueType: Send + 'static> IntoIterator for SpawnGroup<ValueType> {
+    type Item = ValueType;
+    type IntoIter = std::vec::IntoIter<ValueType>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        crate::block_on(self.wait_for_all_items()).into_iter()
+    }
+}
+
+/// Creates a spawn group like ``SpawnGroupBuilder::new().build()`` does,
+/// with a child task spawned for each future at ``Priority::default()``
+///
+/// # Example
+///
+/// ```rust
+/// use spawn_groups::spawn_group::SpawnGroup;
+///
+/// # spawn_groups::block_on(async move {
+/// let mut group: SpawnGroup<u32> = (0..4).map(|i| async move { i * 10 }).collect();
+/// group.extend((4..6).map(|i| async move { i * 10 }));
+///
+/// let mut results = group.wait_for_all_items().await;
+/// results.sort();
+/// assert_eq!(results, vec![0, 10, 20, 30, 40, 50]);
+/// # });
+/// ```
+impl<ValueType: Send + 'static, F> FromIterator<F> for SpawnGroup<ValueType>
+where
+    F: Future<Output = ValueType> + Send + 'static,
+{
+    fn from_iter<I: IntoIterator<Item = F>>(futures: I) -> Self {
+        let mut group: SpawnGroup<ValueType> = SpawnGroup::init();
+        group.extend(futures);
+        group
+    }
+}
+
+/// Spawns a child task for each future at ``Priority::default()``
+impl<ValueType: Send + 'static, F> Extend<F> for SpawnGroup<ValueType>
+where
+    F: Future<Output = ValueType> + Send + 'static,
+{
+    fn extend<I: IntoIterator<Item = F>>(&mut self, futures: I) {
+        for future in futures {
+            self.add_task(Priority::default(), future);
         }
     }
 }
 
+/// Yields the results of the child tasks as they finish, and ends once every child task finished
+/// and all their results were yielded.
+///
+/// Awaiting the next result parks the consumer until a child task stores its result or finishes,
+/// so waiting burns no CPU
+///
+/// # Example
+///
+/// ```rust
+/// use futures_lite::{future, Stream};
+/// use spawn_groups::{sleep, with_spawn_group, Priority};
+/// use std::{pin::Pin, time::Duration};
+///
+/// # spawn_groups::block_on(async move {
+/// with_spawn_group(|mut group| async move {
+///     group.spawn_task(Priority::default(), async {
+///         sleep(Duration::from_millis(200)).await;
+///         "slow"
+///     });
+///     let mut polls = 0;
+///     let result = future::poll_fn(|cx| {
+///         polls += 1;
+///         Pin::new(&mut group).poll_next(cx)
+///     })
+///     .await;
+///
+///     assert_eq!(result, Some("slow"));
+///     // polled once before the child task finished and once after it stored its result
+///     assert!(polls <= 3, "polled {polls} times");
+/// }).await;
+/// # });
+/// ```
 impl<ValueType: Send> Stream for SpawnGroup<ValueType> {
     type Item = ValueType;
 
@@ -237,7 +3818,254 @@ impl<ValueType: Send> Stream for SpawnGroup<ValueType> {
 #[async_trait]
 impl<ValueType: Send + 'static> Waitable for SpawnGroup<ValueType> {
     async fn wait(&self) {
-        self.runtime.wait_for_all_tasks();
+        self.runtime.wait_for_all_tasks_async().await;
         self.decrement_count_to_zero();
     }
 }
+
+/// Map
+///
+/// A stream of the results of a spawn group's child tasks transformed by a closure,
+/// created by calling ``map_items()`` on a spawn group
+pub struct Map<'a, ValueType: Send + 'static, F> {
+    group: &'a SpawnGroup<ValueType>,
+    f: F,
+}
+
+impl<ValueType: Send, F: Fn(ValueType) -> B, B> Map<'_, ValueType, F> {
+    /// A Boolean value that indicates whether the spawn group has any remaining tasks.
+    pub fn is_empty(&self) -> bool {
+        self.group.is_empty()
+    }
+
+    /// Waits for all remaining child tasks to finish and returns their transformed results
+    /// in the order they finished.
+    pub async fn wait_for_all_items(self) -> Vec<B> {
+        let results: Vec<ValueType> = self.group.wait_for_all_items().await;
+        results.into_iter().map(self.f).collect()
+    }
+}
+
+impl<ValueType: Send, F: Fn(ValueType) -> B, B> Stream for Map<'_, ValueType, F> {
+    type Item = B;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.group
+            .runtime
+            .stream()
+            .poll_next(cx)
+            .map(|item| item.map(&self.f))
+    }
+}
+
+/// StreamOrdered
+///
+/// A stream of the results of a spawn group's child tasks which yields results of higher priority child tasks first,
+/// created by calling ``into_stream_ordered()`` on a spawn group
+pub struct StreamOrdered<ValueType: Send + 'static> {
+    group: SpawnGroup<ValueType>,
+}
+
+impl<ValueType: Send> StreamOrdered<ValueType> {
+    /// A Boolean value that indicates whether the spawn group has any remaining tasks.
+    pub fn is_empty(&self) -> bool {
+        self.group.is_empty()
+    }
+}
+
+impl<ValueType: Send> Stream for StreamOrdered<ValueType> {
+    type Item = ValueType;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.group.runtime.stream().poll_next_by_priority(cx)
+    }
+}
+
+/// Merge
+///
+/// A stream of the results of the child tasks of several spawn groups, yielded as they become available,
+/// created by calling ``merge()`` on a spawn group
+pub struct Merge<ValueType: Send + 'static> {
+    groups: Vec<SpawnGroup<ValueType>>,
+    next: usize,
+}
+
+impl<ValueType: Send> Merge<ValueType> {
+    /// Adds another spawn group whose results get interleaved with the results of the spawn groups already merged
+    pub fn merge(mut self, other: SpawnGroup<ValueType>) -> Self {
+        self.groups.push(other);
+        self
+    }
+
+    /// A Boolean value that indicates whether none of the spawn groups has any remaining tasks.
+    pub fn is_empty(&self) -> bool {
+        self.groups.iter().all(|group| group.is_empty())
+    }
+
+    /// Returns the number of child tasks of all the spawn groups that haven't finished yet
+    pub fn pending_count(&self) -> usize {
+        self.groups.iter().map(|group| group.pending_count()).sum()
+    }
+
+    /// Returns the number of child tasks of all the spawn groups that completed so far
+    pub fn completed_count(&self) -> usize {
+        self.groups
+            .iter()
+            .map(|group| group.completed_count())
+            .sum()
+    }
+}
+
+impl<ValueType: Send> Stream for Merge<ValueType> {
+    type Item = ValueType;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this: &mut Self = self.get_mut();
+        let count: usize = this.groups.len();
+        let mut ended: usize = 0;
+        // starts from the spawn group after the one that yielded last, so every spawn group gets its turn
+        for offset in 0..count {
+            let index: usize = (this.next + offset) % count;
+            match this.groups[index].runtime.stream().poll_next(cx) {
+                Poll::Ready(Some(item)) => {
+                    this.next = (index + 1) % count;
+                    return Poll::Ready(Some(item));
+                }
+                Poll::Ready(None) => ended += 1,
+                Poll::Pending => {}
+            }
+        }
+        match ended == count {
+            true => Poll::Ready(None),
+            false => Poll::Pending,
+        }
+    }
+}
+
+/// ConcurrentMap
+///
+/// A stream of the outputs of a closure's futures over an iterator of inputs, yielded in the order they finished,
+/// created by calling ``spawn_group_map()``
+///
+/// It only pulls the next input once fewer than its concurrency limit of child tasks are either running
+/// or waiting for their result to be consumed. Dropping it cancels the child tasks still running.
+///
+/// # Example
+///
+/// ```rust
+/// use futures_lite::StreamExt;
+/// use spawn_groups::{sleep, spawn_group_map};
+/// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+/// use std::time::Duration;
+///
+/// # spawn_groups::block_on(async move {
+/// let (pulled, finished) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+/// let (pulled_count, finished_count) = (pulled.clone(), finished.clone());
+/// // an endless iterator only ever has a few of its inputs in flight
+/// let inputs = (0..).inspect(move |_| _ = pulled_count.fetch_add(1, Ordering::SeqCst));
+/// let mut outputs = spawn_group_map(inputs, 4, move |i: u64| {
+///     let finished = finished_count.clone();
+///     async move {
+///         if i >= 4 {
+///             sleep(Duration::from_secs(10)).await;
+///         }
+///         finished.fetch_add(1, Ordering::SeqCst);
+///         i
+///     }
+/// });
+/// for _ in 0..4 {
+///     assert!(outputs.next().await.unwrap() < 4);
+/// }
+/// assert!(pulled.load(Ordering::SeqCst) <= 8);
+///
+/// // dropping the stream stops pulling inputs and cancels the child tasks in flight
+/// drop(outputs);
+/// let pulled_before = pulled.load(Ordering::SeqCst);
+/// sleep(Duration::from_millis(50)).await;
+/// assert_eq!(pulled.load(Ordering::SeqCst), pulled_before);
+/// assert_eq!(finished.load(Ordering::SeqCst), 4);
+/// # });
+/// ```
+pub struct ConcurrentMap<Inputs, F, ValueType: Send + 'static> {
+    group: SpawnGroup<ValueType>,
+    inputs: Inputs,
+    f: F,
+    concurrency: usize,
+}
+
+// neither the inputs nor the closure are ever pinned
+impl<Inputs, F, ValueType: Send + 'static> Unpin for ConcurrentMap<Inputs, F, ValueType> {}
+
+impl<Inputs, F, ValueType: Send + 'static> ConcurrentMap<Inputs, F, ValueType> {
+    pub(crate) fn new(inputs: Inputs, concurrency: usize, f: F) -> Self {
+        Self {
+            group: SpawnGroupBuilder::new()
+                .drop_policy(DropPolicy::Cancel)
+                .build(),
+            inputs,
+            f,
+            concurrency,
+        }
+    }
+}
+
+impl<Inputs, F, Fut, ValueType> Stream for ConcurrentMap<Inputs, F, ValueType>
+where
+    Inputs: Iterator,
+    F: FnMut(Inputs::Item) -> Fut,
+    Fut: Future<Output = ValueType> + Send + 'static,
+    ValueType: Send + 'static,
+{
+    type Item = ValueType;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this: &mut Self = self.get_mut();
+        // results waiting to be consumed still count against the limit, so a slow consumer slows down the inputs
+        while this.concurrency == 0 || this.group.runtime.stream().item_count() < this.concurrency {
+            let Some(input) = this.inputs.next() else {
+                break;
+            };
+            this.group.spawn_task(Priority::default(), (this.f)(input));
+        }
+        this.group.runtime.stream().poll_next(cx)
+    }
+}
+
+/// All
+///
+/// A future that waits for all remaining child tasks of a spawn group to finish and outputs their results,
+/// created by calling ``all()`` on a spawn group
+pub struct All<'a, ValueType: Send + 'static> {
+    group: &'a SpawnGroup<ValueType>,
+    items: Vec<Buffered<ValueType>>,
+    finished: bool,
+}
+
+// the results are only ever moved around, never pinned
+impl<ValueType: Send + 'static> Unpin for All<'_, ValueType> {}
+
+impl<ValueType: Send + 'static> Future for All<'_, ValueType> {
+    type Output = Vec<ValueType>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this: &mut Self = self.get_mut();
+        let (items, finished) = this.group.runtime.stream().poll_drain(cx);
+        this.items.extend(items);
+        if !finished {
+            return Poll::Pending;
+        }
+        this.group.runtime.wait_for_all_tasks();
+        this.group.decrement_count_to_zero();
+        this.finished = true;
+        Poll::Ready(this.items.drain(..).map(|(_, _, item, _)| item).collect())
+    }
+}
+
+impl<ValueType: Send + 'static> Drop for All<'_, ValueType> {
+    fn drop(&mut self) {
+        if !self.finished && !self.items.is_empty() {
+            let items: Vec<Buffered<ValueType>> = std::mem::take(&mut self.items);
+            crate::block_on(self.group.runtime.stream().requeue_items(items));
+        }
+    }
+}
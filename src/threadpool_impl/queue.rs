@@ -32,6 +32,16 @@ impl<ItemType> Clone for ThreadSafeQueue<ItemType> {
     }
 }
 
+impl<ItemType> ThreadSafeQueue<ItemType> {
+    /// Counts the queued items that match ``predicate``
+    pub fn count_matching(&self, predicate: impl Fn(&ItemType) -> bool) -> usize {
+        let Ok(buffer_lock) = self.buffer.lock() else {
+            return 0;
+        };
+        buffer_lock.iter().filter(|item| predicate(item)).count()
+    }
+}
+
 impl<ItemType> ThreadSafeQueue<ItemType> {
     pub fn dequeue(&self) -> Option<ItemType> {
         let Ok(mut buffer_lock) = self.buffer.lock() else {
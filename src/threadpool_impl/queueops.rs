@@ -1,5 +1,10 @@
+use std::sync::{Arc, Barrier};
+
 pub(crate) enum QueueOperation<T: Send + ?Sized> {
     Ready(Box<T>),
     NotYet,
-    Wait,
+    /// Makes the thread taking it wait at the barrier, which every thread of the pool takes one of
+    Wait(Arc<Barrier>),
+    /// Makes the thread taking it exit, shrinking the pool by one thread
+    Exit,
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use std::thread;
+use std::{panic, thread};
 
 pub(crate) struct UniqueThread {
     handle: thread::JoinHandle<()>,
@@ -17,7 +17,16 @@ impl UniqueThread {
 }
 
 impl UniqueThread {
+    /// Resumes the thread's panic, unless the current thread is already panicking
     pub(crate) fn join(self) {
-        _ = self.handle.join();
+        if let Err(payload) = self.handle.join() {
+            if !thread::panicking() {
+                panic::resume_unwind(payload);
+            }
+        }
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.handle.is_finished()
     }
 }
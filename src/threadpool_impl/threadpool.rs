@@ -1,44 +1,60 @@
+use parking_lot::{Mutex, MutexGuard};
 use std::{
     backtrace, panic,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc, Barrier,
     },
+    task::Waker,
     thread,
 };
 
 use super::{queueops::QueueOperation, thread::UniqueThread, Func, ThreadSafeQueue};
 
 pub struct ThreadPool {
-    handles: Vec<UniqueThread>,
-    count: usize,
+    handles: Mutex<Vec<UniqueThread>>,
+    count: AtomicUsize,
+    spawned: AtomicUsize,
+    thread_name: String,
     queue: ThreadSafeQueue<QueueOperation<Func>>,
-    barrier: Arc<Barrier>,
     stop_flag: Arc<AtomicBool>,
+    active: Arc<AtomicUsize>,
+    capacity_waiters: Arc<Mutex<Vec<Waker>>>,
 }
 
 impl Default for ThreadPool {
     fn default() -> Self {
+        ThreadPool::new(0)
+    }
+}
+
+impl ThreadPool {
+    /// Creates a thread pool of ``thread_count`` threads
+    /// or as many threads as the available parallelism when ``thread_count`` is zero
+    pub fn new(thread_count: usize) -> Self {
+        ThreadPool::with_name(thread_count, "ThreadPool")
+    }
+
+    /// Creates a thread pool like ``new`` does, whose threads are named ``thread_name`` followed by their index
+    pub fn with_name(thread_count: usize, thread_name: &str) -> Self {
         panic_hook();
-        let queue = ThreadSafeQueue::new();
-        let count: usize;
-        if let Ok(thread_count) = thread::available_parallelism() {
-            count = thread_count.get();
-        } else {
-            count = 1;
-        }
-        let barrier = Arc::new(Barrier::new(count + 1));
-        let stop_flag = Arc::new(AtomicBool::new(false));
-        let handles = (0..count)
-            .map(|index| start(index, queue.clone(), barrier.clone(), stop_flag.clone()))
-            .collect();
-        ThreadPool {
-            handles,
-            queue,
-            count,
-            barrier,
-            stop_flag,
-        }
+        let count: usize = match (thread_count, thread::available_parallelism()) {
+            (0, Ok(parallelism)) => parallelism.get(),
+            (0, Err(_)) => 1,
+            (thread_count, _) => thread_count,
+        };
+        let pool = ThreadPool {
+            handles: Mutex::new(vec![]),
+            count: AtomicUsize::new(0),
+            spawned: AtomicUsize::new(0),
+            thread_name: thread_name.to_string(),
+            queue: ThreadSafeQueue::new(),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            active: Arc::new(AtomicUsize::new(0)),
+            capacity_waiters: Arc::new(Mutex::new(vec![])),
+        };
+        pool.set_thread_count(count);
+        pool
     }
 }
 
@@ -51,12 +67,67 @@ impl ThreadPool {
     }
 }
 
+impl ThreadPool {
+    /// The number of threads running a submitted task right now
+    pub fn active_count(&self) -> usize {
+        self.active.load(Ordering::Acquire)
+    }
+
+    /// The number of submitted tasks waiting for a thread to run them
+    pub fn queued_count(&self) -> usize {
+        self.queue
+            .count_matching(|op| matches!(op, QueueOperation::Ready(_)))
+    }
+
+    /// Returns whether no submitted task waits for a thread,
+    /// otherwise ``waker`` is woken once a thread took the next one of them
+    pub fn poll_capacity(&self, waker: &Waker) -> bool {
+        if self.queued_count() == 0 {
+            return true;
+        }
+        self.capacity_waiters.lock().push(waker.clone());
+        // a thread may have taken the last one before the waker was registered
+        self.queued_count() == 0
+    }
+}
+
+impl ThreadPool {
+    /// Grows or shrinks the pool to ``thread_count`` threads, but never below one thread.
+    ///
+    /// Surplus threads exit once they got to the end of the tasks already submitted,
+    /// so the tasks submitted afterwards run on the resized pool
+    pub fn set_thread_count(&self, thread_count: usize) {
+        let thread_count: usize = thread_count.max(1);
+        // holding the handles keeps waiting for all tasks from seeing a thread count in flux
+        let mut handles: MutexGuard<'_, Vec<UniqueThread>> = self.handles.lock();
+        handles.retain(|handle| !handle.is_finished());
+        let count: usize = self.count.swap(thread_count, Ordering::AcqRel);
+        for _ in thread_count..count {
+            self.queue.enqueue(QueueOperation::Exit);
+        }
+        for _ in count..thread_count {
+            let index: usize = self.spawned.fetch_add(1, Ordering::AcqRel);
+            handles.push(start(
+                format!("{} #{}", self.thread_name, index),
+                self.queue.clone(),
+                self.stop_flag.clone(),
+                self.active.clone(),
+                self.capacity_waiters.clone(),
+            ));
+        }
+    }
+}
+
 impl ThreadPool {
     pub fn wait_for_all(&self) {
-        for _ in 0..self.count {
-            self.queue.enqueue(QueueOperation::Wait);
+        let _handles: MutexGuard<'_, Vec<UniqueThread>> = self.handles.lock();
+        let count: usize = self.count.load(Ordering::Acquire);
+        let barrier: Arc<Barrier> = Arc::new(Barrier::new(count + 1));
+        // threads about to exit take their exit first, so each remaining thread takes one wait
+        for _ in 0..count {
+            self.queue.enqueue(QueueOperation::Wait(barrier.clone()));
         }
-        self.barrier.wait();
+        barrier.wait();
     }
 }
 
@@ -69,28 +140,37 @@ impl ThreadPool {
 
 impl Drop for ThreadPool {
     fn drop(&mut self) {
-        _ = panic::take_hook();
+        if !thread::panicking() {
+            _ = panic::take_hook();
+        }
         self.cancel_all();
-        while let Some(handle) = self.handles.pop() {
+        while let Some(handle) = self.handles.get_mut().pop() {
             handle.join();
         }
     }
 }
 
 fn start(
-    index: usize,
+    name: String,
     queue: ThreadSafeQueue<QueueOperation<Func>>,
-    barrier: Arc<Barrier>,
     stop_flag: Arc<AtomicBool>,
+    active: Arc<AtomicUsize>,
+    capacity_waiters: Arc<Mutex<Vec<Waker>>>,
 ) -> UniqueThread {
-    UniqueThread::new(format!("ThreadPool #{}", index), move || {
+    UniqueThread::new(name, move || {
         for op in queue {
             match (op, stop_flag.load(Ordering::Acquire)) {
                 (QueueOperation::NotYet, false) => continue,
                 (QueueOperation::Ready(work), false) => {
-                    work();
+                    active.fetch_add(1, Ordering::AcqRel);
+                    // one task less waits for a thread, which is what the waiters for capacity wait for
+                    let waiters: Vec<Waker> = std::mem::take(&mut *capacity_waiters.lock());
+                    waiters.into_iter().for_each(Waker::wake);
+                    // a dead thread would never take its wait, wedging everyone waiting for all tasks
+                    _ = panic::catch_unwind(panic::AssertUnwindSafe(work));
+                    active.fetch_sub(1, Ordering::AcqRel);
                 }
-                (QueueOperation::Wait, false) => _ = barrier.wait(),
+                (QueueOperation::Wait(barrier), false) => _ = barrier.wait(),
                 _ => {
                     return;
                 }
@@ -100,15 +180,14 @@ fn start(
 }
 
 fn panic_hook() {
-    panic::set_hook(Box::new(move |info: &panic::PanicInfo<'_>| {
+    panic::set_hook(Box::new(move |info: &panic::PanicHookInfo<'_>| {
         let msg = format!(
             "{} panicked at location {} with {} \nBacktrace:\n{}",
-            thread::current().name().unwrap(),
+            thread::current().name().unwrap_or("<unnamed>"),
             info.location().unwrap(),
-            info.to_string().split('\n').collect::<Vec<_>>()[1],
+            info.to_string().split('\n').next_back().unwrap_or_default(),
             backtrace::Backtrace::capture()
         );
         eprintln!("{}", msg);
-        _ = panic::take_hook();
     }));
 }